@@ -13,6 +13,7 @@ pub mod password;
 pub mod prelude;
 pub mod snowflake;
 pub mod time;
+pub mod totp;
 pub mod validation;
 
 use crate::prelude::*;
@@ -39,25 +40,75 @@ impl DbPool {
         access_token::AccessTokenTable { conn: &self.0 }
     }
 
+    /// Creates a [`ban::BanTable`] interface
+    pub fn ban(&self) -> ban::BanTable {
+        ban::BanTable { conn: &self.0 }
+    }
+
     /// Creates a [`guild::GuildTable`] interface
     pub fn guild(&self) -> guild::GuildTable {
         guild::GuildTable { conn: &self.0 }
     }
 
-    // /// Creates a [`role::RoleTable`] interface
-    // pub fn role(&self) -> role::Role {
-    //     role::Role { conn: &self.0 }
-    // }
+    /// Creates a [`guild_folder::GuildFolderTable`] interface
+    pub fn guild_folder(&self) -> guild_folder::GuildFolderTable {
+        guild_folder::GuildFolderTable { conn: &self.0 }
+    }
+
+    /// Creates a [`invite::InviteTable`] interface
+    pub fn invite(&self) -> invite::InviteTable {
+        invite::InviteTable { conn: &self.0 }
+    }
+
+    /// Creates a [`login_attempt::LoginAttemptTable`] interface
+    pub fn login_attempt(&self) -> login_attempt::LoginAttemptTable {
+        login_attempt::LoginAttemptTable { conn: &self.0 }
+    }
+
+    /// Creates a [`role::RoleTable`] interface
+    pub fn role(&self) -> role::RoleTable {
+        role::RoleTable { conn: &self.0 }
+    }
 
     /// Creates a [`channel::ChannelTable`] interface
     pub fn channel(&self) -> channel::ChannelTable {
         channel::ChannelTable { conn: &self.0 }
     }
 
+    /// Creates a [`channel_overwrite::ChannelOverwriteTable`] interface
+    pub fn channel_overwrite(&self) -> channel_overwrite::ChannelOverwriteTable {
+        channel_overwrite::ChannelOverwriteTable { conn: &self.0 }
+    }
+
+    /// Creates a [`dm::DmTable`] interface
+    pub fn dm(&self) -> dm::DmTable {
+        dm::DmTable { conn: &self.0 }
+    }
+
     /// Creates a [`message::MessageTable`] interface
     pub fn message(&self) -> message::MessageTable {
         message::MessageTable { conn: &self.0 }
     }
+
+    /// Creates a [`mfa::MfaTable`] interface
+    pub fn mfa(&self) -> mfa::MfaTable {
+        mfa::MfaTable { conn: &self.0 }
+    }
+
+    /// Creates a [`password_reset::PasswordResetTable`] interface
+    pub fn password_reset(&self) -> password_reset::PasswordResetTable {
+        password_reset::PasswordResetTable { conn: &self.0 }
+    }
+
+    /// Creates a [`reaction::ReactionTable`] interface
+    pub fn reaction(&self) -> reaction::ReactionTable {
+        reaction::ReactionTable { conn: &self.0 }
+    }
+
+    /// Creates a [`relationship::RelationshipTable`] interface
+    pub fn relationship(&self) -> relationship::RelationshipTable {
+        relationship::RelationshipTable { conn: &self.0 }
+    }
 }
 
 /// Create a new database pool
@@ -9,6 +9,10 @@
 //! leading to a higher risk of a user's ID being leaked. Additionally, this
 //! method allows us to revoke a specific access token, i.e. when a user logs
 //! out or changes their password.
+//!
+//! Tokens expire once they are older than `ACCESS_TOKEN_MAX_AGE` seconds, or
+//! once they have gone unused for `ACCESS_TOKEN_IDLE_TIMEOUT` seconds. Either
+//! can be set to 0 to never expire tokens for that reason.
 
 use crate::prelude::*;
 use jsonwebtoken::{encode, DecodingKey, EncodingKey, Header};
@@ -20,7 +24,7 @@ pub struct AccessTokenTable<'a> {
 
 /// Returns the secret used to sign JWTs. This is read from the `JWT_SECRET`
 /// environment variable.
-fn secret() -> Vec<u8> {
+pub(crate) fn secret() -> Vec<u8> {
     use base64::Engine;
 
     // Read the secret from the env and parse from b64 to [u8]
@@ -30,6 +34,48 @@ fn secret() -> Vec<u8> {
         .expect("JWT_SECRET is not valid base64")
 }
 
+/// The default maximum age of a token, in seconds. 30 days.
+const DEFAULT_MAX_AGE: i64 = 30 * 24 * 60 * 60;
+/// The default time a token can go unused for, in seconds. 7 days.
+const DEFAULT_IDLE_TIMEOUT: i64 = 7 * 24 * 60 * 60;
+/// How often `last_used_at` is updated, in milliseconds, so that not every
+/// request writes to the database.
+const LAST_USED_PRECISION: i64 = 60 * 1000;
+
+/// Reads a duration in seconds from the environment, returning it in
+/// milliseconds.
+fn duration_from_env(name: &str, default: i64) -> i64 {
+    let seconds = match std::env::var(name) {
+        Ok(seconds) => seconds
+            .parse::<i64>()
+            .unwrap_or_else(|_| panic!("{name} is not a number of seconds")),
+        Err(_) => default,
+    };
+    seconds * 1000
+}
+
+/// Whether a token created and last used at the given times has expired.
+fn is_expired(created_at: i64, last_used_at: i64) -> bool {
+    let now = time::now();
+    let max_age = duration_from_env("ACCESS_TOKEN_MAX_AGE", DEFAULT_MAX_AGE);
+    let idle_timeout = duration_from_env("ACCESS_TOKEN_IDLE_TIMEOUT", DEFAULT_IDLE_TIMEOUT);
+
+    (max_age > 0 && now - created_at > max_age)
+        || (idle_timeout > 0 && now - last_used_at > idle_timeout)
+}
+
+/// A logged in session, as shown to the user that owns it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessToken {
+    pub id: Snowflake,
+    pub created_at: u64,
+    pub last_used_at: u64,
+    /// The `User-Agent` of the client that logged in.
+    pub user_agent: Option<String>,
+    /// Whether this is the session the request was made with.
+    pub current: bool,
+}
+
 /// An access token from the database.
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 struct Auth {
@@ -62,15 +108,23 @@ impl Auth {
 
 impl<'a> AccessTokenTable<'a> {
     /// Inserts a new access token for the given user and returns the JWT.
-    pub async fn create(&self, user_id: Snowflake) -> Result<String, CreateError> {
+    pub async fn create(
+        &self,
+        id: Snowflake,
+        user_id: Snowflake,
+        user_agent: Option<&str>,
+    ) -> Result<String, CreateError> {
         let token = rand::thread_rng().gen_range(0..i64::MAX);
         let created_at = time::now();
+        let user_agent = user_agent.map(|ua| ua.chars().take(255).collect::<String>());
 
         let success = sqlx::query!(
-            "INSERT INTO access_tokens (token, user_id, created_at) VALUES ($1, $2, $3)",
+            "INSERT INTO access_tokens (id, token, user_id, created_at, last_used_at, user_agent) VALUES ($1, $2, $3, $4, $4, $5)",
+            id.into_number(),
             token,
             user_id.into_number(),
-            created_at
+            created_at,
+            user_agent
         )
         .execute(self.conn)
         .await?;
@@ -86,26 +140,135 @@ impl<'a> AccessTokenTable<'a> {
 
     /// Checks a JWT and returns the user ID if it is valid.
     /// Returns the user's ID and the access token ID.
+    ///
+    /// Expired tokens are deleted, and the expiry of valid tokens is pushed
+    /// back.
     pub async fn check(&self, token: &str) -> Result<(Snowflake, i64), CheckError> {
         let auth = Auth::decode(token)?;
         let token = auth.tkn;
 
-        let row = {
-            struct UserData {
-                user_id: i64,
-            }
+        let access_token = sqlx::query!(
+            "SELECT user_id, created_at, last_used_at FROM access_tokens WHERE token = $1",
+            token
+        )
+        .fetch_optional(self.conn)
+        .await?
+        .ok_or(CheckError::InvalidToken)?;
+
+        if is_expired(access_token.created_at, access_token.last_used_at) {
+            sqlx::query!("DELETE FROM access_tokens WHERE token = $1", token)
+                .execute(self.conn)
+                .await?;
+            return Err(CheckError::InvalidToken);
+        }
 
-            sqlx::query_as!(
-                UserData,
-                "SELECT user_id FROM access_tokens WHERE token = $1",
-                token
+        let now = time::now();
+        if now - access_token.last_used_at > LAST_USED_PRECISION {
+            sqlx::query!(
+                "UPDATE access_tokens SET last_used_at = $2 WHERE token = $1",
+                token,
+                now
             )
-            .fetch_optional(self.conn)
-            .await?
+            .execute(self.conn)
+            .await?;
+        }
+
+        Ok((access_token.user_id.into(), token))
+    }
+
+    /// Checks an access token is still valid without counting it as used,
+    /// for connections that were authenticated with it earlier. Expired
+    /// tokens are deleted.
+    pub async fn is_valid(&self, token: i64) -> Result<bool, sqlx::Error> {
+        let Some(access_token) = sqlx::query!(
+            "SELECT created_at, last_used_at FROM access_tokens WHERE token = $1",
+            token
+        )
+        .fetch_optional(self.conn)
+        .await?
+        else {
+            return Ok(false);
         };
 
-        row.map(|row| (row.user_id.into(), token))
-            .ok_or(CheckError::InvalidToken)
+        if is_expired(access_token.created_at, access_token.last_used_at) {
+            sqlx::query!("DELETE FROM access_tokens WHERE token = $1", token)
+                .execute(self.conn)
+                .await?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Gets every unexpired session of a user, newest first.
+    ///
+    /// `current` is the access token the request was made with.
+    pub async fn get_all(
+        &self,
+        user_id: Snowflake,
+        current: i64,
+    ) -> Result<Vec<AccessToken>, sqlx::Error> {
+        let tokens = sqlx::query!(
+            "SELECT id, token, created_at, last_used_at, user_agent FROM access_tokens WHERE user_id = $1 ORDER BY created_at DESC",
+            user_id.into_number()
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        #[allow(clippy::cast_sign_loss)]
+        Ok(tokens
+            .into_iter()
+            .filter(|token| !is_expired(token.created_at, token.last_used_at))
+            .map(|token| AccessToken {
+                id: token.id.into(),
+                created_at: token.created_at as u64,
+                last_used_at: token.last_used_at as u64,
+                user_agent: token.user_agent,
+                current: token.token == current,
+            })
+            .collect())
+    }
+
+    /// Gets the access token behind a session ID, so that it can be revoked.
+    pub async fn get_token(
+        &self,
+        user_id: Snowflake,
+        id: Snowflake,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar!(
+            "SELECT token FROM access_tokens WHERE id = $1 AND user_id = $2",
+            id.into_number(),
+            user_id.into_number()
+        )
+        .fetch_optional(self.conn)
+        .await
+    }
+
+    /// Revokes a single access token.
+    pub async fn revoke(&self, token: i64) -> Result<(), RevokeError> {
+        let success = sqlx::query!("DELETE FROM access_tokens WHERE token = $1", token)
+            .execute(self.conn)
+            .await?;
+
+        if success.rows_affected() == 0 {
+            return Err(RevokeError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// Revokes every access token of a user except `keep`, and returns the
+    /// tokens that were revoked.
+    pub async fn revoke_all_except(
+        &self,
+        user_id: Snowflake,
+        keep: i64,
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar!(
+            "DELETE FROM access_tokens WHERE user_id = $1 AND token != $2 RETURNING token",
+            user_id.into_number(),
+            keep
+        )
+        .fetch_all(self.conn)
+        .await
     }
 }
 
@@ -128,3 +291,11 @@ pub enum CheckError {
     #[error("An error occurred while querying the database")]
     DatabaseError(#[from] sqlx::Error),
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum RevokeError {
+    #[error("The access token does not exist")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
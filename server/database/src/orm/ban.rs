@@ -0,0 +1,190 @@
+//! Users banned from a guild.
+//!
+//! A ban stops a user from joining the guild again until it is lifted or
+//! expires. Expired bans are left in the table but otherwise ignored.
+
+use crate::prelude::*;
+
+/// The most days of messages that can be deleted when banning a user.
+pub const MAX_PURGE_DAYS: u32 = 7;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ban {
+    pub guild_id: Snowflake,
+    /// The banned user.
+    pub user: user::User,
+    /// The member who banned the user.
+    pub moderator_id: Snowflake,
+    pub reason: Option<String>,
+    pub created_at: u64,
+    /// When the ban is lifted, or `None` if it is permanent.
+    pub expires_at: Option<u64>,
+}
+
+/// What happened when a user was banned.
+#[derive(Debug, Clone)]
+pub struct Banned {
+    /// Whether the user was removed from the guild.
+    pub was_member: bool,
+    /// The channel and ID of every message that was deleted.
+    pub deleted_messages: Vec<(Snowflake, Snowflake)>,
+}
+
+/// An interface for interacting with the `guild_bans` table of the
+/// database.
+pub struct BanTable<'a> {
+    pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
+}
+
+impl<'a> BanTable<'a> {
+    /// Bans a user from a guild, removing them from it if they are a member.
+    /// Banning a user who is already banned replaces their ban.
+    ///
+    /// `duration` is how many milliseconds from now the ban expires in, and
+    /// `purge_days` is how many days of the user's messages in the guild to
+    /// delete.
+    pub async fn ban(
+        &self,
+        guild: Snowflake,
+        user: Snowflake,
+        moderator: Snowflake,
+        reason: Option<&str>,
+        duration: Option<i64>,
+        purge_days: u32,
+    ) -> Result<Banned, BanError> {
+        let user_exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1) AS "exists!""#,
+            user.into_number()
+        )
+        .fetch_one(self.conn)
+        .await?;
+        if !user_exists {
+            return Err(BanError::UserNotFound);
+        }
+
+        let mut tx = self.conn.begin().await?;
+
+        let was_member = match guild::GuildTable::remove_member_in(&mut tx, guild, user).await {
+            Ok(()) => true,
+            Err(guild::RemoveMemberError::NotInGuild) => false,
+            Err(guild::RemoveMemberError::IsOwner) => return Err(BanError::IsOwner),
+            Err(guild::RemoveMemberError::DatabaseError(e)) => return Err(e.into()),
+        };
+
+        let now = time::now();
+        sqlx::query!(
+            "INSERT INTO guild_bans (guild_id, user_id, moderator_id, reason, created_at, expires_at) VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (guild_id, user_id) DO UPDATE SET moderator_id = $3, reason = $4, created_at = $5, expires_at = $6",
+            guild.into_number(),
+            user.into_number(),
+            moderator.into_number(),
+            reason,
+            now,
+            duration.map(|duration| now + duration)
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let deleted_messages = if purge_days == 0 {
+            Vec::new()
+        } else {
+            // Message IDs are snowflakes, so every message sent since the
+            // cutoff has an ID at least as high as the first snowflake
+            // that could be generated then
+            let cutoff = now - i64::from(purge_days) * 24 * 60 * 60 * 1000;
+            let since = Snowflake::from_timestamp(cutoff.try_into().unwrap_or_default());
+            sqlx::query!(
+                "UPDATE messages SET deleted_at = $4
+                WHERE author_id = $1 AND id >= $2 AND deleted_at IS NULL
+                    AND channel_id IN (SELECT id FROM channels WHERE guild_id = $3)
+                RETURNING id, channel_id",
+                user.into_number(),
+                since.into_number(),
+                guild.into_number(),
+                now
+            )
+            .fetch_all(&mut tx)
+            .await?
+            .into_iter()
+            .map(|message| (message.channel_id.into(), message.id.into()))
+            .collect()
+        };
+
+        tx.commit().await?;
+
+        Ok(Banned {
+            was_member,
+            deleted_messages,
+        })
+    }
+
+    /// Lifts a user's ban from a guild.
+    pub async fn unban(&self, guild: Snowflake, user: Snowflake) -> Result<(), UnbanError> {
+        let success = sqlx::query!(
+            "DELETE FROM guild_bans WHERE guild_id = $1 AND user_id = $2 AND (expires_at IS NULL OR expires_at > $3)",
+            guild.into_number(),
+            user.into_number(),
+            time::now()
+        )
+        .execute(self.conn)
+        .await?;
+
+        if success.rows_affected() == 0 {
+            return Err(UnbanError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// Gets every ban in a guild that hasn't expired, newest first.
+    pub async fn get_all(&self, guild: Snowflake) -> Result<Vec<Ban>, sqlx::Error> {
+        let bans = sqlx::query!(
+            "SELECT b.guild_id, b.moderator_id, b.reason, b.created_at, b.expires_at,
+                u.id, u.username, u.discrim, u.profile_img_id, u.accent_color, u.pronouns, u.bio
+            FROM guild_bans b INNER JOIN users u ON u.id = b.user_id
+            WHERE b.guild_id = $1 AND (b.expires_at IS NULL OR b.expires_at > $2)
+            ORDER BY b.created_at DESC",
+            guild.into_number(),
+            time::now()
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        Ok(bans
+            .into_iter()
+            .map(|ban| Ban {
+                guild_id: ban.guild_id.into(),
+                user: user::User {
+                    id: ban.id.into(),
+                    username: ban.username,
+                    discrim: ban.discrim,
+                    profile_img_id: ban.profile_img_id.map(Into::into),
+                    accent_color: ban.accent_color,
+                    pronouns: ban.pronouns,
+                    bio: ban.bio,
+                },
+                moderator_id: ban.moderator_id.into(),
+                reason: ban.reason,
+                created_at: ban.created_at.try_into().unwrap_or_default(),
+                expires_at: ban.expires_at.and_then(|at| at.try_into().ok()),
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BanError {
+    #[error("The user does not exist")]
+    UserNotFound,
+    #[error("The owner of a guild cannot be banned from it")]
+    IsOwner,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UnbanError {
+    #[error("The user is not banned from the guild")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
@@ -1,9 +1,104 @@
+use std::collections::{HashMap, HashSet};
+
 use crate::prelude::*;
 
+/// What a channel in a guild is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    /// A channel messages are sent in.
+    Text,
+    /// A heading that groups the channels under it. Nothing can be sent in
+    /// a category.
+    Category,
+}
+
+impl ChannelKind {
+    fn into_number(self) -> i16 {
+        match self {
+            Self::Text => 0,
+            Self::Category => 1,
+        }
+    }
+
+    pub(crate) fn from_number(kind: i16) -> Self {
+        if kind == 1 {
+            Self::Category
+        } else {
+            Self::Text
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Channel {
     pub id: Snowflake,
     pub name: String,
+    /// What the channel is for, if set.
+    pub topic: Option<String>,
+    pub kind: ChannelKind,
+    /// The category the channel is in, if any.
+    pub parent_id: Option<Snowflake>,
+}
+
+/// A channel at the top of a guild's sidebar, along with the channels inside
+/// it if it is a category.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChannelNode {
+    #[serde(flatten)]
+    pub channel: Channel,
+    /// The channels in the category, from top to bottom. Always empty for
+    /// text channels.
+    pub children: Vec<Channel>,
+    /// Whether the user collapsed the category in their sidebar.
+    pub collapsed: bool,
+}
+
+impl ChannelNode {
+    /// Nests channels under the categories they are in, keeping their order.
+    /// Each channel is paired with whether the user collapsed it.
+    pub(crate) fn nest(channels: Vec<(Channel, bool)>) -> Vec<Self> {
+        let mut nodes: Vec<Self> = vec![];
+        let mut children = vec![];
+        for (channel, collapsed) in channels {
+            if channel.parent_id.is_some() {
+                children.push(channel);
+            } else {
+                nodes.push(Self {
+                    channel,
+                    children: vec![],
+                    collapsed,
+                });
+            }
+        }
+
+        for child in children {
+            if let Some(node) = nodes
+                .iter_mut()
+                .find(|node| Some(node.channel.id) == child.parent_id)
+            {
+                node.children.push(child);
+            }
+        }
+        nodes
+    }
+}
+
+/// Marks categories as visible if a channel in them is, given the IDs and
+/// parents of a guild's channels and which of them a user can view. A
+/// category the user can't view is still shown if they can view a channel in
+/// it.
+pub(crate) fn show_parents(channels: &[(i64, Option<i64>)], visible: &mut [bool]) {
+    let shown: HashSet<i64> = channels
+        .iter()
+        .zip(visible.iter())
+        .filter_map(|((_, parent), &visible)| parent.filter(|_| visible))
+        .collect();
+    for ((id, _), visible) in channels.iter().zip(visible.iter_mut()) {
+        if shown.contains(id) {
+            *visible = true;
+        }
+    }
 }
 
 /// An interface for interacting with the `channels` table of the database.
@@ -12,107 +107,762 @@ pub struct ChannelTable<'a> {
 }
 
 impl<'a> ChannelTable<'a> {
-    /// Create a new guild in the database.
+    /// Create a new channel in a guild.
+    ///
+    /// The channel is placed directly above `next` and in the same category,
+    /// or at the bottom of `parent` or of the guild if `next` isn't given.
     pub async fn create(
         &self,
         id: Snowflake,
         guild: Snowflake,
         name: &str,
+        kind: ChannelKind,
+        parent: Option<Snowflake>,
         next: Option<Snowflake>,
-    ) -> Result<(), CreateError> {
+    ) -> Result<Channel, CreateError> {
+        let mut tx = self.conn.begin().await?;
+
+        if let Some(parent) = parent {
+            if !Self::is_category(&mut tx, guild, parent).await? {
+                return Err(CreateError::ParentNotFound);
+            }
+        }
+
         // If `next` exists, query to find out whether it exists in the database
         // and that channel belongs to the same guild this one does.
         // Otherwise, we'll just insert this channel at the end of the list.
-        let order = if let Some(next) = next {
-            let order = sqlx::query!(
-                r#"SELECT "order" FROM channels WHERE id = $1 AND guild_id = $2"#,
+        let (parent, order) = if let Some(next) = next {
+            let next = sqlx::query!(
+                r#"SELECT "order", parent_id FROM channels WHERE id = $1 AND guild_id = $2"#,
                 next.into_number(),
                 guild.into_number()
             )
-            .fetch_optional(self.conn)
-            .await?;
+            .fetch_optional(&mut tx)
+            .await?
+            .ok_or(CreateError::NextChannelDoesNotExist)?;
 
-            match order {
-                Some(order) => order.order,
-                None => return Err(CreateError::NextChannelDoesNotExist),
+            let next_parent = next.parent_id.map(Snowflake::from);
+            if parent.is_some() && parent != next_parent {
+                return Err(CreateError::NextChannelNotInParent);
             }
+            (next_parent, next.order)
         } else {
-            let order = sqlx::query!(
-                r#"SELECT MAX("order") FROM channels WHERE guild_id = $1"#,
-                guild.into_number()
+            // The bottom of a category is below its last channel, or directly
+            // below the category if it's empty
+            let order = sqlx::query_scalar!(
+                r#"SELECT MAX("order") FROM channels WHERE guild_id = $1 AND ($2::bigint IS NULL OR id = $2 OR parent_id = $2)"#,
+                guild.into_number(),
+                parent.map(|id| id.into_number())
             )
-            .fetch_one(self.conn)
+            .fetch_one(&mut tx)
             .await?;
 
-            order.max.unwrap_or(0) + 1
+            (parent, order.unwrap_or(0) + 1)
         };
+        if kind == ChannelKind::Category && parent.is_some() {
+            return Err(CreateError::CategoryNested);
+        }
+
+        // Make room for this channel by moving every channel after it down
+        sqlx::query!(
+            r#"UPDATE channels SET "order" = "order" + 1 WHERE guild_id = $1 AND "order" >= $2"#,
+            guild.into_number(),
+            order
+        )
+        .execute(&mut tx)
+        .await?;
 
         let success = sqlx::query!(
-            r#"INSERT INTO channels (id, guild_id, name, "order") VALUES ($1, $2, $3, $4)"#,
+            r#"INSERT INTO channels (id, guild_id, name, "order", kind, parent_id) VALUES ($1, $2, $3, $4, $5, $6)"#,
             id.into_number(),
             guild.into_number(),
             name,
-            order
+            order,
+            kind.into_number(),
+            parent.map(|id| id.into_number())
         )
-        .execute(self.conn)
+        .execute(&mut tx)
         .await?;
 
         if success.rows_affected() != 1 {
             return Err(CreateError::NotInserted);
-        } else if next.is_some() {
-            // Assuming we didn't insert this channel at the end of the list,
-            // we need to update the `order` columns of all the channels
-            // that come after this one.
+        }
+        tx.commit().await?;
+
+        Ok(Channel {
+            id,
+            name: name.to_string(),
+            topic: None,
+            kind,
+            parent_id: parent,
+        })
+    }
+
+    /// Whether a channel is a category in the guild.
+    async fn is_category(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        guild: Snowflake,
+        channel: Snowflake,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM channels WHERE id = $1 AND guild_id = $2 AND kind = $3) AS "exists!""#,
+            channel.into_number(),
+            guild.into_number(),
+            ChannelKind::Category.into_number()
+        )
+        .fetch_one(&mut *tx)
+        .await
+    }
+
+    /// Get a channel in a guild.
+    pub async fn get(
+        &self,
+        guild: Snowflake,
+        channel: Snowflake,
+    ) -> Result<Option<Channel>, sqlx::Error> {
+        let channel = sqlx::query!(
+            "SELECT id, name, topic, kind, parent_id FROM channels WHERE id = $1 AND guild_id = $2",
+            channel.into_number(),
+            guild.into_number()
+        )
+        .fetch_optional(self.conn)
+        .await?;
+
+        Ok(channel.map(|channel| Channel {
+            id: channel.id.into(),
+            name: channel.name,
+            topic: channel.topic,
+            kind: ChannelKind::from_number(channel.kind),
+            parent_id: channel.parent_id.map(Into::into),
+        }))
+    }
+
+    /// Edit a channel in a guild. Fields that are `None` are left unchanged,
+    /// and an empty topic clears it.
+    pub async fn edit(
+        &self,
+        guild: Snowflake,
+        channel: Snowflake,
+        name: Option<&str>,
+        topic: Option<&str>,
+    ) -> Result<Channel, EditError> {
+        let channel = sqlx::query!(
+            "UPDATE channels SET
+                name = COALESCE($3, name),
+                topic = CASE WHEN $4::text IS NULL THEN topic ELSE NULLIF($4, '') END
+            WHERE id = $1 AND guild_id = $2
+            RETURNING id, name, topic, kind, parent_id",
+            channel.into_number(),
+            guild.into_number(),
+            name,
+            topic
+        )
+        .fetch_optional(self.conn)
+        .await?
+        .ok_or(EditError::NotFound)?;
+
+        Ok(Channel {
+            id: channel.id.into(),
+            name: channel.name,
+            topic: channel.topic,
+            kind: ChannelKind::from_number(channel.kind),
+            parent_id: channel.parent_id.map(Into::into),
+        })
+    }
+
+    /// Rewrite the order of the channels in a guild that a user can view.
+    ///
+    /// `channels` must contain every channel in the guild the user can view,
+    /// ordered from top to bottom. The channels they can't view keep their
+    /// places.
+    pub async fn reorder(
+        &self,
+        guild: Snowflake,
+        user: Snowflake,
+        channels: &[Snowflake],
+    ) -> Result<(), ReorderError> {
+        let mut tx = self.conn.begin().await?;
+
+        let existing: Vec<(i64, Option<i64>)> = sqlx::query!(
+            r#"SELECT id, parent_id FROM channels WHERE guild_id = $1 ORDER BY "order", id FOR UPDATE"#,
+            guild.into_number()
+        )
+        .fetch_all(&mut tx)
+        .await?
+        .into_iter()
+        .map(|channel| (channel.id, channel.parent_id))
+        .collect();
+        let visible = self
+            .get_views(guild, Some(user), &existing)
+            .await?
+            .pop()
+            .map_or_else(|| vec![false; existing.len()], |(_, visible)| visible);
+
+        let ids: Vec<i64> = channels.iter().map(Snowflake::into_number).collect();
+        let mut expected = ids.clone();
+        let mut actual: Vec<i64> = existing
+            .iter()
+            .zip(&visible)
+            .filter(|(_, &visible)| visible)
+            .map(|((id, _), _)| *id)
+            .collect();
+        expected.sort_unstable();
+        actual.sort_unstable();
+        if expected != actual || expected.windows(2).any(|w| w[0] == w[1]) {
+            return Err(ReorderError::Mismatch);
+        }
+
+        // The visible channels fill the places of the ones they replace, in
+        // the order given
+        let mut given = ids.into_iter();
+        let ids: Vec<i64> = existing
+            .iter()
+            .zip(&visible)
+            .map(|((id, _), &visible)| {
+                if visible {
+                    given.next().unwrap_or(*id)
+                } else {
+                    *id
+                }
+            })
+            .collect();
+        Self::write_order(&mut tx, guild, &ids).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Sets the `order` of every channel in a guild from its position in
+    /// `ids`.
+    async fn write_order(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        guild: Snowflake,
+        ids: &[i64],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE channels SET "order" = new.idx::integer
+            FROM UNNEST($2::bigint[]) WITH ORDINALITY AS new(id, idx)
+            WHERE channels.id = new.id AND channels.guild_id = $1
+            "#,
+            guild.into_number(),
+            ids
+        )
+        .execute(&mut *tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Moves a channel into a category, or out of one if `parent` is `None`.
+    /// The channel is placed directly above `next`, which must be in the
+    /// same category, or at the bottom of the category if not given.
+    pub async fn move_channel(
+        &self,
+        guild: Snowflake,
+        channel: Snowflake,
+        parent: Option<Snowflake>,
+        next: Option<Snowflake>,
+    ) -> Result<(), MoveError> {
+        let mut tx = self.conn.begin().await?;
+
+        let channels = sqlx::query!(
+            r#"SELECT id, kind, parent_id FROM channels WHERE guild_id = $1 ORDER BY "order" FOR UPDATE"#,
+            guild.into_number()
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        let channel = channel.into_number();
+        let parent = parent.map(|id| id.into_number());
+        let moved = channels
+            .iter()
+            .find(|c| c.id == channel)
+            .ok_or(MoveError::NotFound)?;
+        if let Some(parent) = parent {
+            let is_category = channels.iter().any(|c| {
+                c.id == parent && ChannelKind::from_number(c.kind) == ChannelKind::Category
+            });
+            if !is_category {
+                return Err(MoveError::ParentNotFound);
+            }
+            if ChannelKind::from_number(moved.kind) == ChannelKind::Category {
+                return Err(MoveError::CategoryNested);
+            }
+        }
+
+        let mut ids: Vec<i64> = channels
+            .iter()
+            .map(|c| c.id)
+            .filter(|&id| id != channel)
+            .collect();
+        let index = if let Some(next) = next {
+            let next = next.into_number();
+            if next == channel
+                || !channels
+                    .iter()
+                    .any(|c| c.id == next && c.parent_id == parent)
+            {
+                return Err(MoveError::NextChannelNotInParent);
+            }
+            ids.iter().position(|&id| id == next)
+        } else if let Some(parent) = parent {
+            // Below the category's last channel, or the category itself
+            channels
+                .iter()
+                .filter(|c| c.id != channel && (c.id == parent || c.parent_id == Some(parent)))
+                .filter_map(|c| ids.iter().position(|&id| id == c.id))
+                .max()
+                .map(|index| index + 1)
+        } else {
+            None
+        };
+        ids.insert(index.unwrap_or(ids.len()), channel);
+
+        sqlx::query!(
+            "UPDATE channels SET parent_id = $2 WHERE id = $1",
+            channel,
+            parent
+        )
+        .execute(&mut tx)
+        .await?;
+        Self::write_order(&mut tx, guild, &ids).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Sets whether a user has collapsed a category in their sidebar.
+    pub async fn set_collapsed(
+        &self,
+        guild: Snowflake,
+        channel: Snowflake,
+        user: Snowflake,
+        collapsed: bool,
+    ) -> Result<(), SetCollapsedError> {
+        let mut tx = self.conn.begin().await?;
+        if !Self::is_category(&mut tx, guild, channel).await? {
+            return Err(SetCollapsedError::NotFound);
+        }
+
+        if collapsed {
             sqlx::query!(
-                r#"UPDATE channels SET "order" = "order" + 1 WHERE guild_id = $1 AND "order" >= $2 AND id != $3"#,
-                guild.into_number(),
-                order,
-                id.into_number()
+                "INSERT INTO collapsed_categories (user_id, channel_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+                user.into_number(),
+                channel.into_number()
             )
-            .execute(self.conn)
+            .execute(&mut tx)
             .await?;
+        } else {
+            sqlx::query!(
+                "DELETE FROM collapsed_categories WHERE user_id = $1 AND channel_id = $2",
+                user.into_number(),
+                channel.into_number()
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Delete a channel from a guild along with its messages and overwrites.
+    /// The channels in a deleted category are moved out of it. A guild's
+    /// last text channel can't be deleted.
+    pub async fn delete(&self, guild: Snowflake, channel: Snowflake) -> Result<(), DeleteError> {
+        let mut tx = self.conn.begin().await?;
+
+        // Lock every channel in the guild so that two channels can't be
+        // deleted at once, leaving none
+        let channels = sqlx::query!(
+            "SELECT id, kind FROM channels WHERE guild_id = $1 FOR UPDATE",
+            guild.into_number()
+        )
+        .fetch_all(&mut tx)
+        .await?;
+        let channel = channel.into_number();
+        let kind = channels
+            .iter()
+            .find(|c| c.id == channel)
+            .map(|c| ChannelKind::from_number(c.kind))
+            .ok_or(DeleteError::NotFound)?;
+        let text_channels = channels
+            .iter()
+            .filter(|c| ChannelKind::from_number(c.kind) == ChannelKind::Text)
+            .count();
+        if kind == ChannelKind::Text && text_channels == 1 {
+            return Err(DeleteError::LastChannel);
         }
 
+        sqlx::query!(
+            "UPDATE channels SET parent_id = NULL WHERE parent_id = $1",
+            channel
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM collapsed_categories WHERE channel_id = $1",
+            channel
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM message_reactions WHERE message_id IN (SELECT id FROM messages WHERE channel_id = $1)",
+            channel
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM message_revisions WHERE message_id IN (SELECT id FROM messages WHERE channel_id = $1)",
+            channel
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!("DELETE FROM messages WHERE channel_id = $1", channel)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query!(
+            "DELETE FROM channel_overwrites WHERE channel_id = $1",
+            channel
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!("DELETE FROM channels WHERE id = $1", channel)
+            .execute(&mut tx)
+            .await?;
+
+        tx.commit().await?;
         Ok(())
     }
 
+    /// Gets every member of a guild along with the channels in it they can
+    /// view, from top to bottom.
+    pub async fn get_visible_orders(
+        &self,
+        guild: Snowflake,
+    ) -> Result<Vec<(Snowflake, Vec<Snowflake>)>, sqlx::Error> {
+        let channels: Vec<(i64, Option<i64>)> = sqlx::query!(
+            r#"SELECT id, parent_id FROM channels WHERE guild_id = $1 ORDER BY "order", id"#,
+            guild.into_number()
+        )
+        .fetch_all(self.conn)
+        .await?
+        .into_iter()
+        .map(|channel| (channel.id, channel.parent_id))
+        .collect();
+
+        Ok(self
+            .get_views(guild, None, &channels)
+            .await?
+            .into_iter()
+            .map(|(user_id, visible)| {
+                let ids = channels
+                    .iter()
+                    .zip(visible)
+                    .filter(|(_, visible)| *visible)
+                    .map(|((id, _), _)| Snowflake::from(*id))
+                    .collect();
+                (user_id, ids)
+            })
+            .collect())
+    }
+
+    /// Works out which of a guild's `channels`, given as their IDs and
+    /// parents, every member can view, or only `user_id` if given.
+    async fn get_views(
+        &self,
+        guild: Snowflake,
+        user_id: Option<Snowflake>,
+        channels: &[(i64, Option<i64>)],
+    ) -> Result<Vec<(Snowflake, Vec<bool>)>, sqlx::Error> {
+        let members = sqlx::query!(
+            r#"
+            SELECT
+                gm.user_id,
+                g.owner_id,
+                COALESCE(d.permission_mask, 0) | COALESCE(BIT_OR(r.permission_mask), 0) AS "role_mask!",
+                COALESCE(MAX(r."order"), 0) AS "rank!",
+                ARRAY_REMOVE(ARRAY_AGG(r.id), NULL) AS "roles!"
+            FROM
+                guilds g
+                INNER JOIN guild_members gm ON gm.guild_id = g.id
+                LEFT JOIN roles d ON d.id = g.id
+                LEFT JOIN guild_member_roles gmr ON gmr.guild_member_id = gm.id
+                LEFT JOIN roles r ON r.id = gmr.role_id
+            WHERE
+                g.id = $1
+                AND ($2::bigint IS NULL OR gm.user_id = $2)
+            GROUP BY
+                gm.user_id,
+                g.owner_id,
+                d.permission_mask
+            "#,
+            guild.into_number(),
+            user_id.map(|id| id.into_number())
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        let mut overwrites: HashMap<Snowflake, Vec<channel_overwrite::Overwrite>> = HashMap::new();
+        let all_overwrites = channel_overwrite::ChannelOverwriteTable { conn: self.conn }
+            .get_in_guilds(&[guild.into_number()])
+            .await?;
+        for overwrite in all_overwrites {
+            overwrites
+                .entry(overwrite.channel_id)
+                .or_default()
+                .push(overwrite);
+        }
+
+        Ok(members
+            .into_iter()
+            .map(|member| {
+                let perms = role::MemberPermissions::compute(
+                    member.owner_id == member.user_id,
+                    member.role_mask,
+                    member.rank,
+                );
+                let roles: Vec<Snowflake> = member.roles.into_iter().map(Into::into).collect();
+                let mut visible: Vec<bool> = channels
+                    .iter()
+                    .map(|(id, _)| {
+                        let channel_overwrites = overwrites
+                            .get(&Snowflake::from(*id))
+                            .map_or(&[][..], Vec::as_slice);
+                        channel_overwrite::apply(
+                            perms,
+                            guild,
+                            member.user_id.into(),
+                            &roles,
+                            channel_overwrites,
+                        )
+                        .contains(role::Permissions::VIEW_CHANNELS)
+                    })
+                    .collect();
+                show_parents(channels, &mut visible);
+                (member.user_id.into(), visible)
+            })
+            .collect())
+    }
+
+    /// Gets the IDs of every user that can see this channel, i.e. who should
+    /// be notified of new messages in it.
+    pub async fn get_recipient_ids(
+        &self,
+        channel_id: Snowflake,
+    ) -> Result<Vec<Snowflake>, sqlx::Error> {
+        let members = self.get_member_permissions(channel_id, None).await?;
+
+        Ok(members
+            .into_iter()
+            .filter(|(_, perms)| perms.contains(role::Permissions::VIEW_CHANNELS))
+            .map(|(user_id, _)| user_id)
+            .collect())
+    }
+
+    /// Gets the permissions of a user in a channel, after applying the
+    /// channel's overwrites.
+    pub async fn get_permissions(
+        &self,
+        guild_id: Snowflake,
+        channel_id: Snowflake,
+        user_id: Snowflake,
+    ) -> Result<role::MemberPermissions, GetPermissionsError> {
+        let guild = sqlx::query_scalar!(
+            "SELECT guild_id FROM channels WHERE id = $1",
+            channel_id.into_number()
+        )
+        .fetch_optional(self.conn)
+        .await?
+        .flatten();
+        if guild != Some(guild_id.into_number()) {
+            return Err(GetPermissionsError::NotFound);
+        }
+
+        match self
+            .get_member_permissions(channel_id, Some(user_id))
+            .await?
+            .pop()
+        {
+            Some((_, perms)) => Ok(perms),
+            None => Err(GetPermissionsError::NotFound),
+        }
+    }
+
+    /// Gets the permissions of a user in a channel, after applying the
+    /// channel's overwrites, without knowing which guild it is in.
+    pub async fn get_user_permissions(
+        &self,
+        channel_id: Snowflake,
+        user_id: Snowflake,
+    ) -> Result<role::MemberPermissions, GetPermissionsError> {
+        match self
+            .get_member_permissions(channel_id, Some(user_id))
+            .await?
+            .pop()
+        {
+            Some((_, perms)) => Ok(perms),
+            None => Err(GetPermissionsError::NotFound),
+        }
+    }
+
     /// Checks if a user has permission to read the contents of this channel.
     pub async fn has_read_permission(
         &self,
         channel_id: Snowflake,
         user_id: Snowflake,
     ) -> Result<(), HasReadPermissionError> {
-        let channel = sqlx::query!(
-            "SELECT EXISTS(SELECT 1 FROM channels WHERE id = $1 AND guild_id IN (SELECT guild_id FROM guild_members WHERE user_id = $2))",
-            channel_id.into_number(),
-            user_id.into_number()
-        )
-        .fetch_one(self.conn)
-        .await?;
-
-        match channel.exists {
-            Some(false) | None => Err(HasReadPermissionError::NotFound),
-            _ => Ok(()),
+        match self
+            .get_member_permissions(channel_id, Some(user_id))
+            .await?
+            .pop()
+        {
+            Some((_, perms)) if perms.contains(role::Permissions::VIEW_CHANNELS) => Ok(()),
+            _ => Err(HasReadPermissionError::NotFound),
         }
     }
 
+    /// Checks if a user has permission to send messages into this channel.
     pub async fn has_write_permission(
         &self,
         channel_id: Snowflake,
         user_id: Snowflake,
     ) -> Result<(), HasWritePermissionError> {
-        let channel = sqlx::query!(
-            "SELECT EXISTS(SELECT 1 FROM channels WHERE id = $1 AND guild_id IN (SELECT guild_id FROM guild_members WHERE user_id = $2))",
+        match self
+            .get_member_permissions(channel_id, Some(user_id))
+            .await?
+            .pop()
+        {
+            Some((_, perms)) if !perms.contains(role::Permissions::VIEW_CHANNELS) => {
+                Err(HasWritePermissionError::NotFound)
+            }
+            Some((_, perms)) if !perms.contains(role::Permissions::SEND_MESSAGES) => {
+                Err(HasWritePermissionError::PermissionDenied)
+            }
+            Some(_) => Ok(()),
+            None => Err(HasWritePermissionError::NotFound),
+        }
+    }
+
+    /// Computes the permissions in a channel of every member of its guild,
+    /// or only of `user_id` if given. Members of a DM have [`dm::PERMISSIONS`].
+    ///
+    /// Members are left out if the channel does not exist or they are not in
+    /// its guild or DM.
+    async fn get_member_permissions(
+        &self,
+        channel_id: Snowflake,
+        user_id: Option<Snowflake>,
+    ) -> Result<Vec<(Snowflake, role::MemberPermissions)>, sqlx::Error> {
+        let members = sqlx::query!(
+            r#"
+            SELECT
+                gm.user_id,
+                g.id AS guild_id,
+                g.owner_id,
+                COALESCE(d.permission_mask, 0) | COALESCE(BIT_OR(r.permission_mask), 0) AS "role_mask!",
+                COALESCE(MAX(r."order"), 0) AS "rank!",
+                c.kind,
+                ARRAY_REMOVE(ARRAY_AGG(r.id), NULL) AS "roles!"
+            FROM
+                channels c
+                INNER JOIN guilds g ON g.id = c.guild_id
+                INNER JOIN guild_members gm ON gm.guild_id = g.id
+                LEFT JOIN roles d ON d.id = g.id
+                LEFT JOIN guild_member_roles gmr ON gmr.guild_member_id = gm.id
+                LEFT JOIN roles r ON r.id = gmr.role_id
+            WHERE
+                c.id = $1
+                AND ($2::bigint IS NULL OR gm.user_id = $2)
+            GROUP BY
+                gm.user_id,
+                c.kind,
+                g.id,
+                d.permission_mask
+            "#,
             channel_id.into_number(),
-            user_id.into_number()
+            user_id.map(|id| id.into_number())
         )
-        .fetch_one(self.conn)
+        .fetch_all(self.conn)
         .await?;
-
-        match channel.exists {
-            Some(false) | None => Err(HasWritePermissionError::NotFound),
-            _ => Ok(()),
+        if members.is_empty() {
+            return self.get_dm_member_permissions(channel_id, user_id).await;
         }
+
+        let overwrites = channel_overwrite::ChannelOverwriteTable { conn: self.conn }
+            .get_all(channel_id)
+            .await?;
+
+        Ok(members
+            .into_iter()
+            .map(|member| {
+                let perms = role::MemberPermissions::compute(
+                    member.owner_id == member.user_id,
+                    member.role_mask,
+                    member.rank,
+                );
+                let roles: Vec<Snowflake> = member.roles.into_iter().map(Into::into).collect();
+                let mut perms = channel_overwrite::apply(
+                    perms,
+                    member.guild_id.into(),
+                    member.user_id.into(),
+                    &roles,
+                    &overwrites,
+                );
+                if ChannelKind::from_number(member.kind) == ChannelKind::Category {
+                    perms.permissions.remove(role::Permissions::SEND_MESSAGES);
+                }
+                (member.user_id.into(), perms)
+            })
+            .collect())
+    }
+
+    /// The permissions of every member of a DM, or only of `user_id` if
+    /// given. Empty if the channel is not a DM.
+    ///
+    /// Members of a DM between two users can't send messages if either has
+    /// blocked the other.
+    async fn get_dm_member_permissions(
+        &self,
+        channel_id: Snowflake,
+        user_id: Option<Snowflake>,
+    ) -> Result<Vec<(Snowflake, role::MemberPermissions)>, sqlx::Error> {
+        let members = sqlx::query!(
+            r#"
+            SELECT
+                m.user_id,
+                d.kind = 0 AND EXISTS(
+                    SELECT 1 FROM relationships r
+                    WHERE r.kind = 3 AND (r.user_id = d.low_user_id OR r.user_id = d.high_user_id)
+                        AND (r.target_id = d.low_user_id OR r.target_id = d.high_user_id)
+                ) AS "blocked!"
+            FROM
+                dm_members m
+                INNER JOIN dm_channels d ON d.channel_id = m.channel_id
+            WHERE
+                m.channel_id = $1
+                AND ($2::bigint IS NULL OR m.user_id = $2)
+            "#,
+            channel_id.into_number(),
+            user_id.map(|id| id.into_number())
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        Ok(members
+            .into_iter()
+            .map(|member| {
+                let mut permissions = dm::PERMISSIONS;
+                if member.blocked {
+                    permissions.remove(role::Permissions::SEND_MESSAGES);
+                }
+                let perms = role::MemberPermissions {
+                    permissions,
+                    rank: 0,
+                };
+                (member.user_id.into(), perms)
+            })
+            .collect())
     }
 }
 
@@ -120,6 +870,12 @@ impl<'a> ChannelTable<'a> {
 pub enum CreateError {
     #[error("The `next` channel does not exist or does not belong to this guild")]
     NextChannelDoesNotExist,
+    #[error("The `next` channel is not in the category the channel is being created in")]
+    NextChannelNotInParent,
+    #[error("The parent does not exist or is not a category in this guild")]
+    ParentNotFound,
+    #[error("Categories cannot be put in other categories")]
+    CategoryNested,
     #[error("The entry was not inserted into the database")]
     NotInserted,
     #[error("An error occurred while querying the database")]
@@ -134,10 +890,68 @@ pub enum HasReadPermissionError {
     DatabaseError(#[from] sqlx::Error),
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum GetPermissionsError {
+    #[error("The channel does not exist in the guild or the user is not in the guild")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum HasWritePermissionError {
     #[error("The channel does not exist or the user does not have permission to view it")]
     NotFound,
+    #[error("The user does not have permission to send messages in the channel")]
+    PermissionDenied,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EditError {
+    #[error("The channel does not exist in the guild")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReorderError {
+    #[error("The channels given were not exactly the channels in the guild")]
+    Mismatch,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteError {
+    #[error("The channel does not exist in the guild")]
+    NotFound,
+    #[error("A guild's last channel cannot be deleted")]
+    LastChannel,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MoveError {
+    #[error("The channel does not exist in the guild")]
+    NotFound,
+    #[error("The parent does not exist or is not a category in this guild")]
+    ParentNotFound,
+    #[error("The `next` channel is not in the category the channel is being moved to")]
+    NextChannelNotInParent,
+    #[error("Categories cannot be put in other categories")]
+    CategoryNested,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SetCollapsedError {
+    #[error("The channel does not exist or is not a category in this guild")]
+    NotFound,
     #[error("An error occurred while querying the database")]
     DatabaseError(#[from] sqlx::Error),
 }
@@ -0,0 +1,243 @@
+//! Per-channel permission overwrites.
+//!
+//! An overwrite allows or denies a set of permissions for a single role or
+//! member on a single channel. A member's permissions in a channel are found
+//! by starting from their permissions in the guild, then applying:
+//!
+//! 1. the overwrite for the default role,
+//! 2. the overwrites for every other role they have, with all of their denies
+//!    applied before all of their allows,
+//! 3. the overwrite for the member themselves.
+//!
+//! Administrators (and the owner) ignore overwrites entirely.
+
+use crate::prelude::*;
+
+/// What an overwrite applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverwriteKind {
+    Role,
+    Member,
+}
+
+impl OverwriteKind {
+    fn into_number(self) -> i16 {
+        match self {
+            Self::Role => 0,
+            Self::Member => 1,
+        }
+    }
+
+    fn from_number(kind: i16) -> Self {
+        if kind == 0 {
+            Self::Role
+        } else {
+            Self::Member
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Overwrite {
+    pub channel_id: Snowflake,
+    /// The ID of the role or user the overwrite applies to.
+    pub target_id: Snowflake,
+    pub kind: OverwriteKind,
+    /// Permissions granted in the channel, even if the guild denies them.
+    pub allow: role::Permissions,
+    /// Permissions taken away in the channel, even if the guild grants them.
+    pub deny: role::Permissions,
+}
+
+/// Applies a channel's overwrites on top of a member's guild permissions.
+///
+/// `roles` are the IDs of the roles the member has been given, not including
+/// the default role. Overwrites that do not apply to the member are ignored.
+pub(crate) fn apply(
+    mut perms: role::MemberPermissions,
+    guild: Snowflake,
+    user: Snowflake,
+    roles: &[Snowflake],
+    overwrites: &[Overwrite],
+) -> role::MemberPermissions {
+    if perms.contains(role::Permissions::ADMINISTRATOR) {
+        return perms;
+    }
+
+    let mut apply_one = |allow: role::Permissions, deny: role::Permissions| {
+        perms.permissions.remove(deny);
+        perms.permissions.insert(allow);
+    };
+
+    let find = |kind: OverwriteKind, target: Snowflake| {
+        overwrites
+            .iter()
+            .find(|o| o.kind == kind && o.target_id == target)
+    };
+
+    if let Some(o) = find(OverwriteKind::Role, guild) {
+        apply_one(o.allow, o.deny);
+    }
+
+    let (allow, deny) = roles
+        .iter()
+        .filter_map(|role| find(OverwriteKind::Role, *role))
+        .fold(
+            (role::Permissions::empty(), role::Permissions::empty()),
+            |(allow, deny), o| (allow | o.allow, deny | o.deny),
+        );
+    apply_one(allow, deny);
+
+    if let Some(o) = find(OverwriteKind::Member, user) {
+        apply_one(o.allow, o.deny);
+    }
+
+    perms
+}
+
+/// An interface for interacting with the `channel_overwrites` table of the
+/// database.
+pub struct ChannelOverwriteTable<'a> {
+    pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
+}
+
+impl<'a> ChannelOverwriteTable<'a> {
+    /// Get every overwrite on a channel.
+    pub async fn get_all(&self, channel: Snowflake) -> Result<Vec<Overwrite>, sqlx::Error> {
+        let overwrites = sqlx::query_as!(
+            OverwriteRow,
+            "SELECT channel_id, target_id, kind, allow, deny FROM channel_overwrites WHERE channel_id = $1 ORDER BY kind, target_id",
+            channel.into_number()
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        Ok(overwrites.into_iter().map(Into::into).collect())
+    }
+
+    /// Get every overwrite on every channel in the guilds.
+    pub(crate) async fn get_in_guilds(
+        &self,
+        guilds: &[i64],
+    ) -> Result<Vec<Overwrite>, sqlx::Error> {
+        let overwrites = sqlx::query_as!(
+            OverwriteRow,
+            "SELECT o.channel_id, o.target_id, o.kind, o.allow, o.deny FROM channel_overwrites o
+            INNER JOIN channels c ON c.id = o.channel_id
+            WHERE c.guild_id = ANY($1)",
+            guilds
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        Ok(overwrites.into_iter().map(Into::into).collect())
+    }
+
+    /// Create or replace the overwrite for a role or member on a channel.
+    ///
+    /// The role must belong to the channel's guild, and the member must be
+    /// in it.
+    pub async fn upsert(
+        &self,
+        channel: Snowflake,
+        target: Snowflake,
+        kind: OverwriteKind,
+        allow: role::Permissions,
+        deny: role::Permissions,
+    ) -> Result<Overwrite, UpsertError> {
+        let target_exists = match kind {
+            OverwriteKind::Role => sqlx::query_scalar!(
+                "SELECT EXISTS(SELECT 1 FROM roles r INNER JOIN channels c ON c.guild_id = r.guild_id WHERE c.id = $1 AND r.id = $2)",
+                channel.into_number(),
+                target.into_number()
+            )
+            .fetch_one(self.conn)
+            .await?,
+            OverwriteKind::Member => sqlx::query_scalar!(
+                "SELECT EXISTS(SELECT 1 FROM guild_members gm INNER JOIN channels c ON c.guild_id = gm.guild_id WHERE c.id = $1 AND gm.user_id = $2)",
+                channel.into_number(),
+                target.into_number()
+            )
+            .fetch_one(self.conn)
+            .await?,
+        };
+        if target_exists != Some(true) {
+            return Err(UpsertError::TargetNotFound);
+        }
+
+        let overwrite = sqlx::query_as!(
+            OverwriteRow,
+            r#"
+            INSERT INTO channel_overwrites (channel_id, target_id, kind, allow, deny)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (channel_id, target_id) DO UPDATE SET
+                kind = EXCLUDED.kind,
+                allow = EXCLUDED.allow,
+                deny = EXCLUDED.deny
+            RETURNING channel_id, target_id, kind, allow, deny
+            "#,
+            channel.into_number(),
+            target.into_number(),
+            kind.into_number(),
+            allow.bits(),
+            deny.bits()
+        )
+        .fetch_one(self.conn)
+        .await?;
+
+        Ok(overwrite.into())
+    }
+
+    /// Delete the overwrite for a role or member on a channel.
+    pub async fn delete(&self, channel: Snowflake, target: Snowflake) -> Result<(), DeleteError> {
+        let success = sqlx::query!(
+            "DELETE FROM channel_overwrites WHERE channel_id = $1 AND target_id = $2",
+            channel.into_number(),
+            target.into_number()
+        )
+        .execute(self.conn)
+        .await?;
+
+        if success.rows_affected() == 0 {
+            return Err(DeleteError::NotFound);
+        }
+        Ok(())
+    }
+}
+
+struct OverwriteRow {
+    channel_id: i64,
+    target_id: i64,
+    kind: i16,
+    allow: i64,
+    deny: i64,
+}
+
+impl From<OverwriteRow> for Overwrite {
+    fn from(overwrite: OverwriteRow) -> Self {
+        Overwrite {
+            channel_id: overwrite.channel_id.into(),
+            target_id: overwrite.target_id.into(),
+            kind: OverwriteKind::from_number(overwrite.kind),
+            allow: role::Permissions::from_bits_truncate(overwrite.allow),
+            deny: role::Permissions::from_bits_truncate(overwrite.deny),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpsertError {
+    #[error("The role or member does not exist in the channel's guild")]
+    TargetNotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteError {
+    #[error("The overwrite does not exist")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
@@ -0,0 +1,516 @@
+//! Direct messages, which are channels outside of any guild.
+//!
+//! A DM is between two users, and each pair of users only has one. A group
+//! DM has up to [`MAX_GROUP_MEMBERS`] members and an owner, who is the only
+//! member that can remove others. Any member can add more members or leave.
+//!
+//! Every member of a DM can view it and send messages into it, see
+//! [`PERMISSIONS`]. Users can't open a DM with, or add to a group DM, someone
+//! who has blocked them or whom they have blocked, and can't send messages in
+//! a DM with them.
+
+use crate::prelude::*;
+
+/// The most members a group DM can have, including its owner.
+pub const MAX_GROUP_MEMBERS: usize = 10;
+/// The permissions every member of a DM has in it.
+pub const PERMISSIONS: role::Permissions =
+    role::Permissions::VIEW_CHANNELS.union(role::Permissions::SEND_MESSAGES);
+
+/// Whether a DM is between two users or is a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DmKind {
+    Direct,
+    Group,
+}
+
+impl DmKind {
+    fn into_number(self) -> i16 {
+        match self {
+            Self::Direct => 0,
+            Self::Group => 1,
+        }
+    }
+
+    fn from_number(kind: i16) -> Self {
+        if kind == 0 {
+            Self::Direct
+        } else {
+            Self::Group
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DmChannel {
+    pub id: Snowflake,
+    pub kind: DmKind,
+    /// The name of a group DM, or `None` if it wasn't given one.
+    pub name: Option<String>,
+    /// The member who can remove others from a group DM. `None` for DMs.
+    pub owner_id: Option<Snowflake>,
+    /// Every member, including the user who requested the DM, in the order
+    /// they joined.
+    pub members: Vec<user::User>,
+    /// The ID of the newest message, or `None` if nothing has been sent.
+    pub last_message_id: Option<Snowflake>,
+}
+
+/// An interface for interacting with the `dm_channels` and `dm_members`
+/// tables of the database.
+pub struct DmTable<'a> {
+    pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
+}
+
+impl<'a> DmTable<'a> {
+    /// Opens the DM between two users, creating it with the ID `id` if they
+    /// don't have one yet. Returns the ID of the DM and whether it was
+    /// created.
+    pub async fn open(
+        &self,
+        id: Snowflake,
+        user: Snowflake,
+        recipient: Snowflake,
+    ) -> Result<(Snowflake, bool), OpenError> {
+        if user == recipient {
+            return Err(OpenError::CannotDmSelf);
+        }
+        let (low, high) = if user < recipient {
+            (user, recipient)
+        } else {
+            (recipient, user)
+        };
+
+        let existing = sqlx::query_scalar!(
+            "SELECT channel_id FROM dm_channels WHERE low_user_id = $1 AND high_user_id = $2",
+            low.into_number(),
+            high.into_number()
+        )
+        .fetch_optional(self.conn)
+        .await?;
+        if let Some(existing) = existing {
+            return Ok((existing.into(), false));
+        }
+
+        let recipient_exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1) AS "exists!""#,
+            recipient.into_number()
+        )
+        .fetch_one(self.conn)
+        .await?;
+        if !recipient_exists {
+            return Err(OpenError::RecipientNotFound);
+        }
+        let blocked = relationship::RelationshipTable { conn: self.conn }
+            .any_blocked(user, &[recipient])
+            .await?;
+        if blocked {
+            return Err(OpenError::Blocked);
+        }
+
+        let mut tx = self.conn.begin().await?;
+        Self::insert_channel(&mut tx, id, "").await?;
+        let success = sqlx::query!(
+            "INSERT INTO dm_channels (channel_id, kind, low_user_id, high_user_id) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (low_user_id, high_user_id) DO NOTHING",
+            id.into_number(),
+            DmKind::Direct.into_number(),
+            low.into_number(),
+            high.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+
+        // The DM was opened by the other user at the same time
+        if success.rows_affected() != 1 {
+            tx.rollback().await?;
+            let existing = sqlx::query_scalar!(
+                "SELECT channel_id FROM dm_channels WHERE low_user_id = $1 AND high_user_id = $2",
+                low.into_number(),
+                high.into_number()
+            )
+            .fetch_one(self.conn)
+            .await?;
+            return Ok((existing.into(), false));
+        }
+
+        Self::insert_members(&mut tx, id, &[low, high]).await?;
+        tx.commit().await?;
+        Ok((id, true))
+    }
+
+    /// Creates a group DM owned by `owner` with the other users in it.
+    pub async fn create_group(
+        &self,
+        id: Snowflake,
+        owner: Snowflake,
+        recipients: &[Snowflake],
+        name: Option<&str>,
+    ) -> Result<(), CreateGroupError> {
+        let mut members = vec![owner];
+        for recipient in recipients {
+            if !members.contains(recipient) {
+                members.push(*recipient);
+            }
+        }
+        if members.len() > MAX_GROUP_MEMBERS {
+            return Err(CreateGroupError::TooManyMembers);
+        }
+
+        let ids: Vec<i64> = members.iter().map(Snowflake::into_number).collect();
+        let found = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM users WHERE id = ANY($1)"#,
+            &ids
+        )
+        .fetch_one(self.conn)
+        .await?;
+        if usize::try_from(found).ok() != Some(members.len()) {
+            return Err(CreateGroupError::UserNotFound);
+        }
+        let blocked = relationship::RelationshipTable { conn: self.conn }
+            .any_blocked_between(&members)
+            .await?;
+        if blocked {
+            return Err(CreateGroupError::Blocked);
+        }
+
+        let mut tx = self.conn.begin().await?;
+        Self::insert_channel(&mut tx, id, name.unwrap_or_default()).await?;
+        sqlx::query!(
+            "INSERT INTO dm_channels (channel_id, kind, owner_id) VALUES ($1, $2, $3)",
+            id.into_number(),
+            DmKind::Group.into_number(),
+            owner.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+        Self::insert_members(&mut tx, id, &members).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn insert_channel(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        id: Snowflake,
+        name: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO channels (id, guild_id, name) VALUES ($1, NULL, $2)",
+            id.into_number(),
+            name
+        )
+        .execute(&mut *tx)
+        .await?;
+        Ok(())
+    }
+
+    async fn insert_members(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        id: Snowflake,
+        members: &[Snowflake],
+    ) -> Result<(), sqlx::Error> {
+        let members: Vec<i64> = members.iter().map(Snowflake::into_number).collect();
+        sqlx::query!(
+            "INSERT INTO dm_members (channel_id, user_id, joined_at) SELECT $1, *, $3 FROM UNNEST($2::bigint[])",
+            id.into_number(),
+            &members,
+            time::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Gets a DM the user is a member of.
+    pub async fn get(
+        &self,
+        channel: Snowflake,
+        user: Snowflake,
+    ) -> Result<Option<DmChannel>, sqlx::Error> {
+        Ok(self.get_many(user, Some(channel)).await?.pop())
+    }
+
+    /// Gets every DM the user is a member of, most recently active first.
+    pub async fn get_all(&self, user: Snowflake) -> Result<Vec<DmChannel>, sqlx::Error> {
+        self.get_many(user, None).await
+    }
+
+    async fn get_many(
+        &self,
+        user: Snowflake,
+        channel: Option<Snowflake>,
+    ) -> Result<Vec<DmChannel>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            WITH dms AS (
+                SELECT
+                    d.channel_id,
+                    d.kind,
+                    d.owner_id,
+                    c.name,
+                    (SELECT MAX(m.id) FROM messages m WHERE m.channel_id = d.channel_id) AS last_message_id
+                FROM
+                    dm_channels d
+                    INNER JOIN channels c ON c.id = d.channel_id
+                WHERE
+                    d.channel_id IN (SELECT channel_id FROM dm_members WHERE user_id = $1)
+                    AND ($2::bigint IS NULL OR d.channel_id = $2)
+            )
+            SELECT
+                dms.channel_id AS "channel_id!",
+                dms.kind AS "kind!",
+                dms.owner_id,
+                dms.name AS "name!",
+                dms.last_message_id,
+                u.id AS user_id,
+                u.username,
+                u.discrim,
+                u.profile_img_id,
+                u.accent_color,
+                u.pronouns,
+                u.bio
+            FROM
+                dms
+                INNER JOIN dm_members dm ON dm.channel_id = dms.channel_id
+                INNER JOIN users u ON u.id = dm.user_id
+            ORDER BY
+                COALESCE(dms.last_message_id, dms.channel_id) DESC,
+                dms.channel_id,
+                dm.joined_at,
+                u.id
+            "#,
+            user.into_number(),
+            channel.map(|id| id.into_number())
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        // Each member is a separate row, and rows of the same DM are together
+        let mut dms: Vec<DmChannel> = vec![];
+        for row in rows {
+            let member = user::User {
+                id: row.user_id.into(),
+                username: row.username,
+                discrim: row.discrim,
+                profile_img_id: row.profile_img_id.map(Into::into),
+                accent_color: row.accent_color,
+                pronouns: row.pronouns,
+                bio: row.bio,
+            };
+
+            match dms.last_mut() {
+                Some(dm) if dm.id == row.channel_id.into() => dm.members.push(member),
+                _ => dms.push(DmChannel {
+                    id: row.channel_id.into(),
+                    kind: DmKind::from_number(row.kind),
+                    name: Some(row.name).filter(|name| !name.is_empty()),
+                    owner_id: row.owner_id.map(Into::into),
+                    members: vec![member],
+                    last_message_id: row.last_message_id.map(Into::into),
+                }),
+            }
+        }
+
+        Ok(dms)
+    }
+
+    /// Gets the IDs of every member of a DM.
+    pub async fn get_member_ids(&self, channel: Snowflake) -> Result<Vec<Snowflake>, sqlx::Error> {
+        let members = sqlx::query_scalar!(
+            "SELECT user_id FROM dm_members WHERE channel_id = $1",
+            channel.into_number()
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        Ok(members.into_iter().map(Into::into).collect())
+    }
+
+    /// Adds a user to a group DM that `actor` is a member of.
+    pub async fn add_member(
+        &self,
+        channel: Snowflake,
+        actor: Snowflake,
+        user: Snowflake,
+    ) -> Result<(), AddMemberError> {
+        let mut tx = self.conn.begin().await?;
+
+        let kind = sqlx::query_scalar!(
+            "SELECT d.kind FROM dm_channels d INNER JOIN dm_members m ON m.channel_id = d.channel_id
+            WHERE d.channel_id = $1 AND m.user_id = $2 FOR UPDATE OF d",
+            channel.into_number(),
+            actor.into_number()
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        .ok_or(AddMemberError::NotFound)?;
+        if DmKind::from_number(kind) != DmKind::Group {
+            return Err(AddMemberError::NotGroup);
+        }
+
+        let user_exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1) AS "exists!""#,
+            user.into_number()
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        if !user_exists {
+            return Err(AddMemberError::UserNotFound);
+        }
+
+        let members: Vec<Snowflake> = sqlx::query_scalar!(
+            "SELECT user_id FROM dm_members WHERE channel_id = $1",
+            channel.into_number()
+        )
+        .fetch_all(&mut tx)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+        // Nobody in the group can be made to share it with someone they've
+        // blocked or been blocked by
+        let blocked = relationship::RelationshipTable { conn: self.conn }
+            .any_blocked(user, &members)
+            .await?;
+        if blocked {
+            return Err(AddMemberError::Blocked);
+        }
+        if members.len() >= MAX_GROUP_MEMBERS {
+            return Err(AddMemberError::TooManyMembers);
+        }
+
+        let success = sqlx::query!(
+            "INSERT INTO dm_members (channel_id, user_id, joined_at) VALUES ($1, $2, $3)
+            ON CONFLICT (channel_id, user_id) DO NOTHING",
+            channel.into_number(),
+            user.into_number(),
+            time::now()
+        )
+        .execute(&mut tx)
+        .await?;
+        if success.rows_affected() != 1 {
+            return Err(AddMemberError::AlreadyMember);
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Removes a user from a group DM. Members can remove themselves, but
+    /// only the owner can remove others. If the owner leaves, the member who
+    /// joined first becomes the owner.
+    ///
+    /// Returns the owner of the group DM afterwards, which is `None` if no
+    /// one is left in it.
+    pub async fn remove_member(
+        &self,
+        channel: Snowflake,
+        actor: Snowflake,
+        user: Snowflake,
+    ) -> Result<Option<Snowflake>, RemoveMemberError> {
+        let mut tx = self.conn.begin().await?;
+
+        let dm = sqlx::query!(
+            "SELECT d.kind, d.owner_id FROM dm_channels d INNER JOIN dm_members m ON m.channel_id = d.channel_id
+            WHERE d.channel_id = $1 AND m.user_id = $2 FOR UPDATE OF d",
+            channel.into_number(),
+            actor.into_number()
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        .ok_or(RemoveMemberError::NotFound)?;
+        if DmKind::from_number(dm.kind) != DmKind::Group {
+            return Err(RemoveMemberError::NotGroup);
+        }
+        let owner: Option<Snowflake> = dm.owner_id.map(Into::into);
+        if actor != user && owner != Some(actor) {
+            return Err(RemoveMemberError::PermissionDenied);
+        }
+
+        let success = sqlx::query!(
+            "DELETE FROM dm_members WHERE channel_id = $1 AND user_id = $2",
+            channel.into_number(),
+            user.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+        if success.rows_affected() != 1 {
+            return Err(RemoveMemberError::NotMember);
+        }
+
+        let owner = if owner == Some(user) {
+            let owner = sqlx::query_scalar!(
+                "UPDATE dm_channels SET owner_id = (
+                    SELECT user_id FROM dm_members WHERE channel_id = $1 ORDER BY joined_at, user_id LIMIT 1
+                ) WHERE channel_id = $1 RETURNING owner_id",
+                channel.into_number()
+            )
+            .fetch_one(&mut tx)
+            .await?;
+            owner.map(Into::into)
+        } else {
+            owner
+        };
+
+        tx.commit().await?;
+        Ok(owner)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum OpenError {
+    #[error("Users cannot open a DM with themselves")]
+    CannotDmSelf,
+    #[error("The recipient does not exist")]
+    RecipientNotFound,
+    #[error("One of the users has blocked the other")]
+    Blocked,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateGroupError {
+    #[error("One of the recipients does not exist")]
+    UserNotFound,
+    #[error("One of the members has blocked another")]
+    Blocked,
+    #[error("Group DMs can have at most {MAX_GROUP_MEMBERS} members")]
+    TooManyMembers,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AddMemberError {
+    #[error("The DM does not exist or the user is not a member of it")]
+    NotFound,
+    #[error("Members can only be added to group DMs")]
+    NotGroup,
+    #[error("The user being added does not exist")]
+    UserNotFound,
+    #[error("A member has blocked the user being added, or been blocked by them")]
+    Blocked,
+    #[error("The user is already a member of the group DM")]
+    AlreadyMember,
+    #[error("Group DMs can have at most {MAX_GROUP_MEMBERS} members")]
+    TooManyMembers,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RemoveMemberError {
+    #[error("The DM does not exist or the user is not a member of it")]
+    NotFound,
+    #[error("Members can only be removed from group DMs")]
+    NotGroup,
+    #[error("Only the owner can remove other members")]
+    PermissionDenied,
+    #[error("The user being removed is not a member of the group DM")]
+    NotMember,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
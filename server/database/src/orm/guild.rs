@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use super::UNIQUE_VIOLATION;
 use crate::prelude::*;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -7,7 +8,42 @@ pub struct Guild {
     pub id: Snowflake,
     pub owner: user::User,
     pub name: String,
-    pub channels: Vec<channel::Channel>,
+    /// The sidebar folder the user put this guild in, if any.
+    pub folder_id: Option<Snowflake>,
+    /// The channels at the top of the sidebar, with the channels in each
+    /// category nested under it.
+    pub channels: Vec<channel::ChannelNode>,
+}
+
+/// A guild without its channels.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildSummary {
+    pub id: Snowflake,
+    pub owner: user::User,
+    pub name: String,
+}
+
+/// A user in a guild.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Member {
+    /// The ID of the membership, which orders members by when they joined.
+    pub id: Snowflake,
+    pub guild_id: Snowflake,
+    pub user: user::User,
+    /// What the member is called in the guild instead of their username.
+    pub nickname: Option<String>,
+    pub joined_at: u64,
+    /// The IDs of the roles the member has been given, not including the
+    /// default role.
+    pub roles: Vec<Snowflake>,
+}
+
+/// A page of members, in the order they joined.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MemberPage {
+    pub members: Vec<Member>,
+    /// Whether there are more members after the last one.
+    pub has_more: bool,
 }
 
 /// An interface for interacting with the `guilds` table of the database.
@@ -16,115 +52,255 @@ pub struct GuildTable<'a> {
 }
 
 impl<'a> GuildTable<'a> {
-    /// Create a new guild in the database.
+    /// Create a new guild in the database, along with its default role.
     pub async fn create(
         &self,
         id: Snowflake,
         owner: Snowflake,
         name: &str,
     ) -> Result<(), CreateError> {
+        let mut tx = self.conn.begin().await?;
+
         let success = sqlx::query!(
             "INSERT INTO guilds (id, owner_id, name) VALUES ($1, $2, $3)",
             id.into_number(),
             owner.into_number(),
             name,
         )
-        .execute(self.conn)
+        .execute(&mut tx)
+        .await?;
+
+        if success.rows_affected() != 1 {
+            return Err(CreateError::NotInserted);
+        }
+
+        // The default role shares its ID with the guild
+        let success = sqlx::query!(
+            r#"INSERT INTO roles (id, guild_id, name, color, permission_mask, "order") VALUES ($1, $1, 'everyone', '000000', $2, 0)"#,
+            id.into_number(),
+            role::Permissions::DEFAULT.bits()
+        )
+        .execute(&mut tx)
         .await?;
 
-        if success.rows_affected() == 1 {
-            Ok(())
-        } else {
-            Err(CreateError::NotInserted)
+        if success.rows_affected() != 1 {
+            return Err(CreateError::NotInserted);
         }
+
+        tx.commit().await?;
+        Ok(())
     }
 
-    /// Get all the guilds a user has joined.
+    /// Get all the guilds a user has joined, in the order of their sidebar.
     pub async fn get_joined(&self, user: Snowflake) -> Result<Vec<Guild>, GetJoinedError> {
+        self.load_joined(user, None).await
+    }
+
+    /// Get a single guild the user has joined, or `None` if they aren't in
+    /// it.
+    pub async fn get_joined_guild(
+        &self,
+        user: Snowflake,
+        guild: Snowflake,
+    ) -> Result<Option<Guild>, GetJoinedError> {
+        Ok(self.load_joined(user, Some(guild)).await?.pop())
+    }
+
+    /// Loads the guilds a user is in, or just `only` if given.
+    ///
+    /// Each table is read with its own query over the whole set of guilds,
+    /// so the number of rows read grows with the number of guilds and
+    /// channels rather than their product.
+    ///
+    /// Only channels the user can view are included.
+    async fn load_joined(
+        &self,
+        user: Snowflake,
+        only: Option<Snowflake>,
+    ) -> Result<Vec<Guild>, GetJoinedError> {
         debug!("Getting guilds");
 
-        // Get all the guilds the user is in, ordered by `order`
-        let guilds_with_channels = sqlx::query!(
-            r#"
-            SELECT
-                g.id,
-                g.name,
-                g.owner_id,
-                u.username as owner_username,
-                u.discrim as owner_discrim,
-                u.profile_img_id as owner_profile_img_id,
-                u.accent_color as owner_accent_color,
-                u.pronouns as owner_pronouns,
-                u.bio as owner_bio,
-                c.id as channel_id,
-                c.name as channel_name
-            FROM
-                guilds g
-                INNER JOIN users u ON g.owner_id = u.id
-                INNER JOIN channels c ON g.id = c.guild_id
-                INNER JOIN guild_members gm ON g.id = gm.guild_id
-            WHERE
-                g.id IN (
-                    SELECT
-                        guild_id
-                    FROM
-                        guild_members
-                    WHERE
-                        user_id = $1
-                )
-            ORDER BY
-                gm.order,
-                c.order
-            "#,
-            user.into_number()
+        let memberships = sqlx::query!(
+            r#"SELECT guild_id, folder_id FROM guild_members
+            WHERE user_id = $1 AND ($2::bigint IS NULL OR guild_id = $2)
+            ORDER BY "order", id"#,
+            user.into_number(),
+            only.map(|id| id.into_number())
         )
         .fetch_all(self.conn)
         .await?;
+        if memberships.is_empty() {
+            return Ok(vec![]);
+        }
+        let guild_ids: Vec<i64> = memberships.iter().map(|m| m.guild_id).collect();
 
-        debug!("Got guilds with channels");
-
-        // SQL returns each channel as a separate row, so we need to group them
-        let mut guild_mapping = HashMap::new();
-        let mut guild_order: Vec<i64> = vec![];
-        for guild in guilds_with_channels {
-            let guild_id = guild.id;
-            let inserted_guild = guild_mapping.entry(guild_id).or_insert_with(|| {
-                // Insert from within this callback so that we only insert the
-                // guild once.
-                guild_order.push(guild_id);
-
-                Guild {
-                    id: guild_id.into(),
-                    owner: user::User {
-                        id: guild.owner_id.into(),
-                        username: guild.owner_username,
-                        discrim: guild.owner_discrim,
-                        profile_img_id: guild.owner_profile_img_id.map(Into::into),
-                        accent_color: guild.owner_accent_color,
-                        pronouns: guild.owner_pronouns,
-                        bio: guild.owner_bio,
-                    },
-                    name: guild.name,
-                    channels: vec![],
-                }
-            });
+        // The user's permissions in each guild, before channel overwrites
+        let mut guilds: HashMap<i64, _> = sqlx::query!(
+            r#"SELECT g.id, g.name, g.owner_id,
+                COALESCE(d.permission_mask, 0) | COALESCE(BIT_OR(r.permission_mask), 0) AS "role_mask!",
+                COALESCE(MAX(r."order"), 0) AS "rank!",
+                ARRAY_REMOVE(ARRAY_AGG(r.id), NULL) AS "roles!"
+            FROM guilds g
+                INNER JOIN guild_members gm ON gm.guild_id = g.id AND gm.user_id = $2
+                LEFT JOIN roles d ON d.id = g.id
+                LEFT JOIN guild_member_roles gmr ON gmr.guild_member_id = gm.id
+                LEFT JOIN roles r ON r.id = gmr.role_id
+            WHERE g.id = ANY($1)
+            GROUP BY g.id, d.permission_mask"#,
+            &guild_ids,
+            user.into_number()
+        )
+        .fetch_all(self.conn)
+        .await?
+        .into_iter()
+        .map(|guild| (guild.id, guild))
+        .collect();
+
+        let mut owner_ids: Vec<i64> = guilds.values().map(|guild| guild.owner_id).collect();
+        owner_ids.sort_unstable();
+        owner_ids.dedup();
+        let owners: HashMap<i64, user::User> = sqlx::query!(
+            "SELECT id, username, discrim, profile_img_id, accent_color, pronouns, bio FROM users WHERE id = ANY($1)",
+            &owner_ids
+        )
+        .fetch_all(self.conn)
+        .await?
+        .into_iter()
+        .map(|owner| {
+            let user = user::User {
+                id: owner.id.into(),
+                username: owner.username,
+                discrim: owner.discrim,
+                profile_img_id: owner.profile_img_id.map(Into::into),
+                accent_color: owner.accent_color,
+                pronouns: owner.pronouns,
+                bio: owner.bio,
+            };
+            (owner.id, user)
+        })
+        .collect();
+
+        let perms = guilds
+            .values()
+            .map(|guild| {
+                let perms = role::MemberPermissions::compute(
+                    guild.owner_id == user.into_number(),
+                    guild.role_mask,
+                    guild.rank,
+                );
+                let roles = guild.roles.iter().copied().map(Into::into).collect();
+                (guild.id, (perms, roles))
+            })
+            .collect();
+        let mut guild_channels = self.load_visible_channels(user, &guild_ids, &perms).await?;
 
-            inserted_guild.channels.push(channel::Channel {
-                id: guild.channel_id.into(),
-                name: guild.channel_name,
+        // Put everything together in the order of the user's sidebar
+        let mut joined = vec![];
+        for membership in memberships {
+            let Some(guild) = guilds.remove(&membership.guild_id) else {
+                continue;
+            };
+            let Some(owner) = owners.get(&guild.owner_id) else {
+                warn!("Owner {} of guild {} not found", guild.owner_id, guild.id);
+                continue;
+            };
+            let channels = guild_channels.remove(&guild.id).unwrap_or_default();
+            joined.push(Guild {
+                id: guild.id.into(),
+                owner: owner.clone(),
+                name: guild.name,
+                folder_id: membership.folder_id.map(Into::into),
+                channels: channel::ChannelNode::nest(channels),
             });
         }
 
-        debug!("Mapped guilds with channels");
+        Ok(joined)
+    }
 
-        let mut guilds = vec![];
-        for id in guild_order {
-            guilds.push(guild_mapping.remove(&id).unwrap());
+    /// Loads the channels of the guilds that the user can view, grouped by
+    /// guild and paired with whether the user collapsed them. `perms` are the
+    /// user's permissions and roles in each guild.
+    ///
+    /// A category the user can't view is still included if they can view a
+    /// channel in it, see [`channel::show_parents`].
+    async fn load_visible_channels(
+        &self,
+        user: Snowflake,
+        guild_ids: &[i64],
+        perms: &HashMap<i64, (role::MemberPermissions, Vec<Snowflake>)>,
+    ) -> Result<HashMap<i64, Vec<(channel::Channel, bool)>>, sqlx::Error> {
+        let channels = sqlx::query!(
+            r#"SELECT c.id, c.guild_id, c.name, c.topic, c.kind, c.parent_id,
+                EXISTS(
+                    SELECT 1 FROM collapsed_categories cc WHERE cc.channel_id = c.id AND cc.user_id = $2
+                ) AS "collapsed!"
+            FROM channels c
+            WHERE c.guild_id = ANY($1)
+            ORDER BY c."order", c.id"#,
+            guild_ids,
+            user.into_number()
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        let mut overwrites: HashMap<Snowflake, Vec<channel_overwrite::Overwrite>> = HashMap::new();
+        let all_overwrites = channel_overwrite::ChannelOverwriteTable { conn: self.conn }
+            .get_in_guilds(guild_ids)
+            .await?;
+        for overwrite in all_overwrites {
+            overwrites
+                .entry(overwrite.channel_id)
+                .or_default()
+                .push(overwrite);
         }
 
-        debug!("Pushed guilds with channels");
+        debug!("Got channels and overwrites");
+
+        let can_view = |guild_id: i64, channel_id: i64| {
+            let Some((perms, roles)) = perms.get(&guild_id) else {
+                return false;
+            };
+            let channel_overwrites = overwrites
+                .get(&Snowflake::from(channel_id))
+                .map_or(&[][..], Vec::as_slice);
+            channel_overwrite::apply(*perms, guild_id.into(), user, roles, channel_overwrites)
+                .contains(role::Permissions::VIEW_CHANNELS)
+        };
+        let mut visible: Vec<bool> = channels
+            .iter()
+            .map(|channel| {
+                channel
+                    .guild_id
+                    .map_or(false, |guild| can_view(guild, channel.id))
+            })
+            .collect();
+        let tree: Vec<(i64, Option<i64>)> = channels
+            .iter()
+            .map(|channel| (channel.id, channel.parent_id))
+            .collect();
+        channel::show_parents(&tree, &mut visible);
+
+        let mut guild_channels: HashMap<i64, Vec<(channel::Channel, bool)>> = HashMap::new();
+        for (channel, visible) in channels.into_iter().zip(visible) {
+            let Some(guild_id) = channel.guild_id else {
+                continue;
+            };
+            if !visible {
+                continue;
+            }
+            guild_channels.entry(guild_id).or_default().push((
+                channel::Channel {
+                    id: channel.id.into(),
+                    name: channel.name,
+                    topic: channel.topic,
+                    kind: channel::ChannelKind::from_number(channel.kind),
+                    parent_id: channel.parent_id.map(Into::into),
+                },
+                channel.collapsed,
+            ));
+        }
 
-        Ok(guilds)
+        Ok(guild_channels)
     }
 
     /// Make a user join a guild.
@@ -134,14 +310,50 @@ impl<'a> GuildTable<'a> {
         guild: Snowflake,
         user: Snowflake,
     ) -> Result<(), JoinError> {
-        let success = sqlx::query!(
+        let mut tx = self.conn.begin().await?;
+        Self::join_in(&mut tx, id, guild, user).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// [`Self::join`] as part of a larger transaction.
+    pub(crate) async fn join_in(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        id: Snowflake,
+        guild: Snowflake,
+        user: Snowflake,
+    ) -> Result<(), JoinError> {
+        let is_banned = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM guild_bans WHERE guild_id = $1 AND user_id = $2 AND (expires_at IS NULL OR expires_at > $3)
+            ) AS "exists!""#,
+            guild.into_number(),
+            user.into_number(),
+            time::now()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if is_banned {
+            return Err(JoinError::Banned);
+        }
+
+        // The unique constraint on the guild and user catches both members
+        // joining again and two joins racing each other
+        let success = match sqlx::query!(
             "INSERT INTO guild_members (id, guild_id, user_id) VALUES ($1, $2, $3)",
             id.into_number(),
             guild.into_number(),
             user.into_number(),
         )
-        .execute(self.conn)
-        .await?;
+        .execute(&mut *tx)
+        .await
+        {
+            Ok(success) => success,
+            Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some(UNIQUE_VIOLATION) => {
+                return Err(JoinError::AlreadyMember);
+            }
+            Err(e) => return Err(e.into()),
+        };
 
         if success.rows_affected() != 1 {
             return Err(JoinError::NotInserted);
@@ -153,34 +365,508 @@ impl<'a> GuildTable<'a> {
             id.into_number(),
             user.into_number(),
         )
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Rewrite the order of the guilds in a user's sidebar.
+    ///
+    /// `guilds` must contain every guild the user is in, ordered from top to
+    /// bottom.
+    pub async fn reorder_joined(
+        &self,
+        user: Snowflake,
+        guilds: &[Snowflake],
+    ) -> Result<(), ReorderJoinedError> {
+        let mut tx = self.conn.begin().await?;
+
+        let existing = sqlx::query_scalar!(
+            "SELECT guild_id FROM guild_members WHERE user_id = $1 FOR UPDATE",
+            user.into_number()
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        let ids: Vec<i64> = guilds.iter().map(Snowflake::into_number).collect();
+        let mut expected = ids.clone();
+        let mut actual = existing;
+        expected.sort_unstable();
+        actual.sort_unstable();
+        if expected != actual || expected.windows(2).any(|w| w[0] == w[1]) {
+            return Err(ReorderJoinedError::Mismatch);
+        }
+
+        Self::write_joined_order(&mut tx, user, &ids).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Moves a guild in a user's sidebar to directly above `next`, or to the
+    /// bottom if not given.
+    ///
+    /// Returns every guild the user is in, in its new order.
+    pub async fn move_joined(
+        &self,
+        user: Snowflake,
+        guild: Snowflake,
+        next: Option<Snowflake>,
+    ) -> Result<Vec<Snowflake>, MoveJoinedError> {
+        let mut tx = self.conn.begin().await?;
+
+        let existing = sqlx::query_scalar!(
+            r#"SELECT guild_id FROM guild_members WHERE user_id = $1 ORDER BY "order", id FOR UPDATE"#,
+            user.into_number()
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        let guild = guild.into_number();
+        if !existing.contains(&guild) {
+            return Err(MoveJoinedError::NotInGuild);
+        }
+        let mut ids: Vec<i64> = existing.into_iter().filter(|&id| id != guild).collect();
+        let index = match next {
+            Some(next) => ids
+                .iter()
+                .position(|&id| id == next.into_number())
+                .ok_or(MoveJoinedError::NextNotFound)?,
+            None => ids.len(),
+        };
+        ids.insert(index, guild);
+
+        Self::write_joined_order(&mut tx, user, &ids).await?;
+
+        tx.commit().await?;
+        Ok(ids.into_iter().map(Into::into).collect())
+    }
+
+    /// Sets the `order` of every guild a user is in from its position in
+    /// `ids`.
+    async fn write_joined_order(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        user: Snowflake,
+        ids: &[i64],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE guild_members SET "order" = new.idx::integer
+            FROM UNNEST($2::bigint[]) WITH ORDINALITY AS new(id, idx)
+            WHERE guild_members.guild_id = new.id AND guild_members.user_id = $1
+            "#,
+            user.into_number(),
+            ids
+        )
+        .execute(&mut *tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the IDs of every member of a guild.
+    pub async fn get_member_ids(&self, guild: Snowflake) -> Result<Vec<Snowflake>, sqlx::Error> {
+        let members = sqlx::query_scalar!(
+            "SELECT user_id FROM guild_members WHERE guild_id = $1",
+            guild.into_number()
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        Ok(members.into_iter().map(Into::into).collect())
+    }
+
+    /// Gets the members of a guild who joined after the member with the ID
+    /// `after`, optionally only those whose username starts with `prefix`,
+    /// ignoring case.
+    pub async fn get_members(
+        &self,
+        guild: Snowflake,
+        after: Option<Snowflake>,
+        prefix: Option<&str>,
+        limit: i64,
+    ) -> Result<MemberPage, sqlx::Error> {
+        let pattern = prefix.map(|prefix| {
+            let escaped = prefix
+                .to_lowercase()
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("{escaped}%")
+        });
+        let mut members = self
+            .query_members(guild, after, pattern.as_deref(), None, limit + 1)
+            .await?;
+
+        let has_more = members.len() > usize::try_from(limit).unwrap_or_default();
+        members.truncate(usize::try_from(limit).unwrap_or_default());
+        Ok(MemberPage { members, has_more })
+    }
+
+    /// Gets a single member of a guild.
+    pub async fn get_member(
+        &self,
+        guild: Snowflake,
+        user: Snowflake,
+    ) -> Result<Option<Member>, sqlx::Error> {
+        Ok(self
+            .query_members(guild, None, None, Some(user), 1)
+            .await?
+            .pop())
+    }
+
+    async fn query_members(
+        &self,
+        guild: Snowflake,
+        after: Option<Snowflake>,
+        pattern: Option<&str>,
+        user: Option<Snowflake>,
+        limit: i64,
+    ) -> Result<Vec<Member>, sqlx::Error> {
+        let members = sqlx::query!(
+            r#"SELECT gm.id AS member_id, gm.guild_id, gm.nickname,
+                ARRAY(SELECT role_id FROM guild_member_roles WHERE guild_member_id = gm.id ORDER BY role_id) AS "roles!",
+                u.id, u.username, u.discrim, u.profile_img_id, u.accent_color, u.pronouns, u.bio
+            FROM guild_members gm INNER JOIN users u ON u.id = gm.user_id
+            WHERE gm.guild_id = $1
+                AND ($2::bigint IS NULL OR gm.id > $2)
+                AND ($3::text IS NULL OR lower(u.username) LIKE $3)
+                AND ($4::bigint IS NULL OR gm.user_id = $4)
+            ORDER BY gm.id
+            LIMIT $5"#,
+            guild.into_number(),
+            after.map(|id| id.into_number()),
+            pattern,
+            user.map(|id| id.into_number()),
+            limit
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        Ok(members
+            .into_iter()
+            .map(|member| {
+                let id = Snowflake::from(member.member_id);
+                Member {
+                    id,
+                    guild_id: member.guild_id.into(),
+                    user: user::User {
+                        id: member.id.into(),
+                        username: member.username,
+                        discrim: member.discrim,
+                        profile_img_id: member.profile_img_id.map(Into::into),
+                        accent_color: member.accent_color,
+                        pronouns: member.pronouns,
+                        bio: member.bio,
+                    },
+                    nickname: member.nickname,
+                    // Members are given their ID when they join
+                    joined_at: id.timestamp,
+                    roles: member.roles.into_iter().map(Into::into).collect(),
+                }
+            })
+            .collect())
+    }
+
+    /// Sets or clears the nickname of a member of a guild.
+    pub async fn set_nickname(
+        &self,
+        guild: Snowflake,
+        user: Snowflake,
+        nickname: Option<&str>,
+    ) -> Result<(), SetNicknameError> {
+        let success = sqlx::query!(
+            "UPDATE guild_members SET nickname = $3 WHERE guild_id = $1 AND user_id = $2",
+            guild.into_number(),
+            user.into_number(),
+            nickname
+        )
         .execute(self.conn)
         .await?;
 
+        if success.rows_affected() == 0 {
+            return Err(SetNicknameError::NotInGuild);
+        }
         Ok(())
     }
 
-    /// Gets the permissions of a user in the guild
+    /// Gets the permissions of a user in the guild, computed from the
+    /// default role and every role they have been given.
     pub async fn get_permissions(
         &self,
         guild: Snowflake,
         user: Snowflake,
-    ) -> Result<bool, GetPermissionsError> {
-        let guild = sqlx::query!(
+    ) -> Result<role::MemberPermissions, GetPermissionsError> {
+        let member = sqlx::query!(
             r#"
-            SELECT id, owner_id
-            FROM guilds
-            WHERE guilds.id = $1
+            SELECT
+                g.owner_id,
+                COALESCE(d.permission_mask, 0) | COALESCE(BIT_OR(r.permission_mask), 0) AS "role_mask!",
+                COALESCE(MAX(r."order"), 0) AS "rank!"
+            FROM
+                guilds g
+                INNER JOIN guild_members gm ON gm.guild_id = g.id AND gm.user_id = $2
+                LEFT JOIN roles d ON d.id = g.id
+                LEFT JOIN guild_member_roles gmr ON gmr.guild_member_id = gm.id
+                LEFT JOIN roles r ON r.id = gmr.role_id
+            WHERE
+                g.id = $1
+            GROUP BY
+                g.owner_id,
+                d.permission_mask
             "#,
-            guild.into_number()
+            guild.into_number(),
+            user.into_number()
         )
         .fetch_optional(self.conn)
         .await?;
 
-        let Some(guild) = guild else {
+        let Some(member) = member else {
             return Err(GetPermissionsError::NotInGuild);
         };
 
-        Ok(guild.owner_id == user.into_number())
+        Ok(role::MemberPermissions::compute(
+            member.owner_id == user.into_number(),
+            member.role_mask,
+            member.rank,
+        ))
+    }
+
+    /// Gets the name and owner of a guild.
+    pub async fn get_summary(&self, guild: Snowflake) -> Result<Option<GuildSummary>, sqlx::Error> {
+        let guild = sqlx::query!(
+            "SELECT g.id, g.name, g.owner_id, u.username, u.discrim, u.profile_img_id, u.accent_color, u.pronouns, u.bio
+            FROM guilds g INNER JOIN users u ON u.id = g.owner_id WHERE g.id = $1",
+            guild.into_number()
+        )
+        .fetch_optional(self.conn)
+        .await?;
+
+        Ok(guild.map(|guild| GuildSummary {
+            id: guild.id.into(),
+            owner: user::User {
+                id: guild.owner_id.into(),
+                username: guild.username,
+                discrim: guild.discrim,
+                profile_img_id: guild.profile_img_id.map(Into::into),
+                accent_color: guild.accent_color,
+                pronouns: guild.pronouns,
+                bio: guild.bio,
+            },
+            name: guild.name,
+        }))
+    }
+
+    /// Changes the name of a guild.
+    pub async fn rename(&self, guild: Snowflake, name: &str) -> Result<(), RenameError> {
+        let success = sqlx::query!(
+            "UPDATE guilds SET name = $2 WHERE id = $1",
+            guild.into_number(),
+            name
+        )
+        .execute(self.conn)
+        .await?;
+
+        if success.rows_affected() != 1 {
+            return Err(RenameError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// Makes another member the owner of a guild. Only the current owner can
+    /// do this.
+    pub async fn transfer(
+        &self,
+        guild: Snowflake,
+        owner: Snowflake,
+        new_owner: Snowflake,
+    ) -> Result<(), TransferError> {
+        let mut tx = self.conn.begin().await?;
+
+        let current_owner = sqlx::query_scalar!(
+            "SELECT owner_id FROM guilds WHERE id = $1 FOR UPDATE",
+            guild.into_number()
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        if current_owner != Some(owner.into_number()) {
+            return Err(TransferError::NotOwner);
+        }
+
+        let is_member = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM guild_members WHERE guild_id = $1 AND user_id = $2) AS "exists!""#,
+            guild.into_number(),
+            new_owner.into_number()
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        if !is_member {
+            return Err(TransferError::NotMember);
+        }
+
+        sqlx::query!(
+            "UPDATE guilds SET owner_id = $2 WHERE id = $1",
+            guild.into_number(),
+            new_owner.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Removes a member from a guild, along with their roles and their
+    /// overwrites in its channels. The owner can't be removed.
+    pub async fn remove_member(
+        &self,
+        guild: Snowflake,
+        user: Snowflake,
+    ) -> Result<(), RemoveMemberError> {
+        let mut tx = self.conn.begin().await?;
+        Self::remove_member_in(&mut tx, guild, user).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// [`Self::remove_member`] as part of a larger transaction.
+    pub(crate) async fn remove_member_in(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        guild: Snowflake,
+        user: Snowflake,
+    ) -> Result<(), RemoveMemberError> {
+        let member = sqlx::query!(
+            "SELECT gm.id, g.owner_id FROM guild_members gm INNER JOIN guilds g ON g.id = gm.guild_id
+            WHERE gm.guild_id = $1 AND gm.user_id = $2 FOR UPDATE OF gm",
+            guild.into_number(),
+            user.into_number()
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(RemoveMemberError::NotInGuild)?;
+        if member.owner_id == user.into_number() {
+            return Err(RemoveMemberError::IsOwner);
+        }
+
+        sqlx::query!(
+            "DELETE FROM guild_member_roles WHERE guild_member_id = $1",
+            member.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM channel_overwrites WHERE target_id = $1 AND kind = 1 AND channel_id IN (SELECT id FROM channels WHERE guild_id = $2)",
+            user.into_number(),
+            guild.into_number()
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM collapsed_categories WHERE user_id = $1 AND channel_id IN (SELECT id FROM channels WHERE guild_id = $2)",
+            user.into_number(),
+            guild.into_number()
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!("DELETE FROM guild_members WHERE id = $1", member.id)
+            .execute(&mut *tx)
+            .await?;
+        guild_folder::GuildFolderTable::delete_empty(tx, &[user.into_number()]).await?;
+
+        Ok(())
+    }
+
+    /// Deletes a guild and everything in it, returning the users that were
+    /// members of it. Only the owner can do this.
+    pub async fn delete(
+        &self,
+        guild: Snowflake,
+        owner: Snowflake,
+    ) -> Result<Vec<Snowflake>, DeleteError> {
+        let mut tx = self.conn.begin().await?;
+        let guild = guild.into_number();
+
+        // Locking the guild stops it being transferred while it's deleted
+        let current_owner = sqlx::query_scalar!(
+            "SELECT owner_id FROM guilds WHERE id = $1 FOR UPDATE",
+            guild
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        if current_owner != Some(owner.into_number()) {
+            return Err(DeleteError::NotOwner);
+        }
+
+        // Children are deleted before the rows they reference
+        sqlx::query!(
+            "DELETE FROM message_reactions WHERE message_id IN (
+                SELECT m.id FROM messages m INNER JOIN channels c ON c.id = m.channel_id WHERE c.guild_id = $1
+            )",
+            guild
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM message_revisions WHERE message_id IN (
+                SELECT m.id FROM messages m INNER JOIN channels c ON c.id = m.channel_id WHERE c.guild_id = $1
+            )",
+            guild
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM messages WHERE channel_id IN (SELECT id FROM channels WHERE guild_id = $1)",
+            guild
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM channel_overwrites WHERE channel_id IN (SELECT id FROM channels WHERE guild_id = $1)",
+            guild
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM collapsed_categories WHERE channel_id IN (SELECT id FROM channels WHERE guild_id = $1)",
+            guild
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!("DELETE FROM channels WHERE guild_id = $1", guild)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query!(
+            "DELETE FROM guild_member_roles WHERE guild_member_id IN (SELECT id FROM guild_members WHERE guild_id = $1)",
+            guild
+        )
+        .execute(&mut tx)
+        .await?;
+        let members = sqlx::query_scalar!(
+            "DELETE FROM guild_members WHERE guild_id = $1 RETURNING user_id",
+            guild
+        )
+        .fetch_all(&mut tx)
+        .await?;
+        guild_folder::GuildFolderTable::delete_empty(&mut tx, &members).await?;
+        sqlx::query!("DELETE FROM roles WHERE guild_id = $1", guild)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query!("DELETE FROM invites WHERE guild_id = $1", guild)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query!("DELETE FROM guild_emojis WHERE guild_id = $1", guild)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query!("DELETE FROM guild_bans WHERE guild_id = $1", guild)
+            .execute(&mut tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM guilds WHERE id = $1", guild)
+            .execute(&mut tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(members.into_iter().map(Snowflake::from).collect())
     }
 }
 
@@ -194,6 +880,10 @@ pub enum CreateError {
 
 #[derive(Debug, thiserror::Error)]
 pub enum JoinError {
+    #[error("The user is already a member of the guild")]
+    AlreadyMember,
+    #[error("The user is banned from the guild")]
+    Banned,
     #[error("The entry was not inserted into the database")]
     NotInserted,
     #[error("An error occurred while querying the database")]
@@ -206,6 +896,24 @@ pub enum GetJoinedError {
     DatabaseError(#[from] sqlx::Error),
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum ReorderJoinedError {
+    #[error("The guilds given are not exactly the guilds the user is in")]
+    Mismatch,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MoveJoinedError {
+    #[error("The user is not in the guild")]
+    NotInGuild,
+    #[error("The user is not in the guild to place it before")]
+    NextNotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum GetPermissionsError {
     #[error("The user is not in the guild")]
@@ -213,3 +921,47 @@ pub enum GetPermissionsError {
     #[error("An error occurred while querying the database")]
     DatabaseError(#[from] sqlx::Error),
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum SetNicknameError {
+    #[error("The user is not a member of the guild")]
+    NotInGuild,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RenameError {
+    #[error("The guild does not exist")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransferError {
+    #[error("The guild does not exist or the user is not its owner")]
+    NotOwner,
+    #[error("The new owner is not a member of the guild")]
+    NotMember,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RemoveMemberError {
+    #[error("The user is not in the guild")]
+    NotInGuild,
+    #[error("The owner cannot be removed from the guild")]
+    IsOwner,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteError {
+    #[error("The guild does not exist or the user is not its owner")]
+    NotOwner,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
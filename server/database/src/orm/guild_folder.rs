@@ -0,0 +1,236 @@
+//! Folders that group guilds in a user's sidebar.
+//!
+//! Folders don't have a position of their own. A folder sits where the
+//! highest of its guilds is in the user's guild order, and the rest of its
+//! guilds are gathered under it. A folder is deleted once it has no guilds
+//! left in it.
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Folder {
+    pub id: Snowflake,
+    pub name: String,
+    /// The hex color code of the folder, without the leading `#`.
+    pub color: Option<String>,
+    /// The guilds in the folder, from top to bottom.
+    pub guild_ids: Vec<Snowflake>,
+}
+
+/// An interface for interacting with the `guild_folders` table of the
+/// database.
+pub struct GuildFolderTable<'a> {
+    pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
+}
+
+impl<'a> GuildFolderTable<'a> {
+    /// Gets every folder the user has made.
+    pub async fn get_all(&self, user: Snowflake) -> Result<Vec<Folder>, sqlx::Error> {
+        self.get_many(user, None).await
+    }
+
+    async fn get_many(
+        &self,
+        user: Snowflake,
+        folder: Option<Snowflake>,
+    ) -> Result<Vec<Folder>, sqlx::Error> {
+        let folders = sqlx::query!(
+            r#"SELECT f.id, f.name, f.color,
+                ARRAY(SELECT guild_id FROM guild_members WHERE folder_id = f.id ORDER BY "order", id) AS "guild_ids!"
+            FROM guild_folders f
+            WHERE f.user_id = $1 AND ($2::bigint IS NULL OR f.id = $2)
+            ORDER BY f.id"#,
+            user.into_number(),
+            folder.map(|id| id.into_number())
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        Ok(folders
+            .into_iter()
+            .map(|folder| Folder {
+                id: folder.id.into(),
+                name: folder.name,
+                color: folder.color,
+                guild_ids: folder.guild_ids.into_iter().map(Into::into).collect(),
+            })
+            .collect())
+    }
+
+    /// Creates a folder with the given guilds in it, moving them out of any
+    /// other folder they were in.
+    pub async fn create(
+        &self,
+        id: Snowflake,
+        user: Snowflake,
+        name: &str,
+        color: Option<&str>,
+        guilds: &[Snowflake],
+    ) -> Result<Folder, CreateError> {
+        let mut tx = self.conn.begin().await?;
+
+        sqlx::query!(
+            "INSERT INTO guild_folders (id, user_id, name, color) VALUES ($1, $2, $3, $4)",
+            id.into_number(),
+            user.into_number(),
+            name,
+            color
+        )
+        .execute(&mut tx)
+        .await?;
+        if !Self::set_guilds(&mut tx, user, id, guilds).await? {
+            return Err(CreateError::GuildNotJoined);
+        }
+
+        tx.commit().await?;
+        Ok(self.get(user, id).await?)
+    }
+
+    /// Edits a folder. Fields that are `None` are left unchanged, an empty
+    /// colour resets it, and `guilds` replaces the guilds in the folder.
+    pub async fn edit(
+        &self,
+        user: Snowflake,
+        folder: Snowflake,
+        name: Option<&str>,
+        color: Option<&str>,
+        guilds: Option<&[Snowflake]>,
+    ) -> Result<Folder, EditError> {
+        let mut tx = self.conn.begin().await?;
+
+        let success = sqlx::query!(
+            "UPDATE guild_folders SET
+                name = COALESCE($3, name),
+                color = CASE WHEN $4::text IS NULL THEN color ELSE NULLIF($4, '') END
+            WHERE id = $1 AND user_id = $2",
+            folder.into_number(),
+            user.into_number(),
+            name,
+            color
+        )
+        .execute(&mut tx)
+        .await?;
+        if success.rows_affected() == 0 {
+            return Err(EditError::NotFound);
+        }
+
+        if let Some(guilds) = guilds {
+            sqlx::query!(
+                "UPDATE guild_members SET folder_id = NULL WHERE folder_id = $1",
+                folder.into_number()
+            )
+            .execute(&mut tx)
+            .await?;
+            if !Self::set_guilds(&mut tx, user, folder, guilds).await? {
+                return Err(EditError::GuildNotJoined);
+            }
+        }
+
+        tx.commit().await?;
+        Ok(self.get(user, folder).await?)
+    }
+
+    /// Deletes a folder, leaving its guilds where they are in the sidebar.
+    pub async fn delete(&self, user: Snowflake, folder: Snowflake) -> Result<(), DeleteError> {
+        let mut tx = self.conn.begin().await?;
+
+        sqlx::query!(
+            "UPDATE guild_members SET folder_id = NULL WHERE folder_id = $1 AND user_id = $2",
+            folder.into_number(),
+            user.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+        let success = sqlx::query!(
+            "DELETE FROM guild_folders WHERE id = $1 AND user_id = $2",
+            folder.into_number(),
+            user.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+        if success.rows_affected() == 0 {
+            return Err(DeleteError::NotFound);
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Gets a folder that was just written to within the same request.
+    async fn get(&self, user: Snowflake, folder: Snowflake) -> Result<Folder, sqlx::Error> {
+        self.get_many(user, Some(folder))
+            .await?
+            .pop()
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    /// Moves the user's memberships of `guilds` into the folder, then deletes
+    /// any other folder left empty. Returns `false` if the user isn't in every
+    /// guild.
+    async fn set_guilds(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        user: Snowflake,
+        folder: Snowflake,
+        guilds: &[Snowflake],
+    ) -> Result<bool, sqlx::Error> {
+        let mut ids: Vec<i64> = guilds.iter().map(Snowflake::into_number).collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        let success = sqlx::query!(
+            "UPDATE guild_members SET folder_id = $3 WHERE user_id = $1 AND guild_id = ANY($2)",
+            user.into_number(),
+            &ids,
+            folder.into_number()
+        )
+        .execute(&mut *tx)
+        .await?;
+        if success.rows_affected() != ids.len() as u64 {
+            return Ok(false);
+        }
+
+        Self::delete_empty(tx, &[user.into_number()]).await?;
+        Ok(true)
+    }
+
+    /// Deletes every folder of the given users that has no guilds in it.
+    pub(crate) async fn delete_empty(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        users: &[i64],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM guild_folders f WHERE f.user_id = ANY($1)
+                AND NOT EXISTS(SELECT 1 FROM guild_members gm WHERE gm.folder_id = f.id)",
+            users
+        )
+        .execute(&mut *tx)
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateError {
+    #[error("The user is not a member of one of the guilds")]
+    GuildNotJoined,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EditError {
+    #[error("The folder does not exist or belongs to another user")]
+    NotFound,
+    #[error("The user is not a member of one of the guilds")]
+    GuildNotJoined,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteError {
+    #[error("The folder does not exist or belongs to another user")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
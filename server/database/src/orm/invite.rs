@@ -0,0 +1,300 @@
+//! Invite codes that let users join a guild.
+
+use crate::prelude::*;
+
+/// The characters an invite code is made up of.
+const CODE_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+/// The length of a newly generated invite code.
+const CODE_LENGTH: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invite {
+    pub code: String,
+    pub guild_id: Snowflake,
+    pub inviter_id: Snowflake,
+    pub created_at: u64,
+    /// When the invite stops working, or `None` if it never expires.
+    pub expires_at: Option<u64>,
+    /// How many times the invite can be used, or `None` if unlimited.
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+}
+
+/// What a user sees about a guild before accepting an invite to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvitePreview {
+    pub code: String,
+    pub guild_id: Snowflake,
+    pub guild_name: String,
+    pub member_count: i64,
+    pub owner: user::User,
+    pub expires_at: Option<u64>,
+}
+
+/// An interface for interacting with the `invites` table of the database.
+pub struct InviteTable<'a> {
+    pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
+}
+
+impl<'a> InviteTable<'a> {
+    /// Create a new invite to a guild with a random code.
+    ///
+    /// `max_age` is how many milliseconds from now the invite expires in.
+    pub async fn create(
+        &self,
+        guild: Snowflake,
+        inviter: Snowflake,
+        max_uses: Option<i32>,
+        max_age: Option<i64>,
+    ) -> Result<Invite, CreateError> {
+        let code: String = {
+            let mut rng = rand::thread_rng();
+            (0..CODE_LENGTH)
+                .map(|_| char::from(CODE_CHARS[rng.gen_range(0..CODE_CHARS.len())]))
+                .collect()
+        };
+        let created_at = time::now();
+        let expires_at = max_age.map(|age| created_at + age);
+
+        let success = sqlx::query!(
+            "INSERT INTO invites (code, guild_id, inviter_id, created_at, expires_at, max_uses) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (code) DO NOTHING",
+            code,
+            guild.into_number(),
+            inviter.into_number(),
+            created_at,
+            expires_at,
+            max_uses
+        )
+        .execute(self.conn)
+        .await?;
+
+        if success.rows_affected() != 1 {
+            return Err(CreateError::NotInserted);
+        }
+
+        Ok(Invite {
+            code,
+            guild_id: guild,
+            inviter_id: inviter,
+            created_at: created_at.try_into().unwrap_or_default(),
+            expires_at: expires_at.and_then(|at| at.try_into().ok()),
+            max_uses,
+            uses: 0,
+        })
+    }
+
+    /// Get an invite by its code, whether or not it can still be used.
+    pub async fn get(&self, code: &str) -> Result<Option<Invite>, sqlx::Error> {
+        let invite = sqlx::query_as!(
+            InviteRow,
+            "SELECT code, guild_id, inviter_id, created_at, expires_at, max_uses, uses FROM invites WHERE code = $1",
+            code
+        )
+        .fetch_optional(self.conn)
+        .await?;
+
+        Ok(invite.map(Into::into))
+    }
+
+    /// Get every invite to a guild, newest first.
+    pub async fn get_all(&self, guild: Snowflake) -> Result<Vec<Invite>, sqlx::Error> {
+        let invites = sqlx::query_as!(
+            InviteRow,
+            "SELECT code, guild_id, inviter_id, created_at, expires_at, max_uses, uses FROM invites WHERE guild_id = $1 ORDER BY created_at DESC",
+            guild.into_number()
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        Ok(invites.into_iter().map(Into::into).collect())
+    }
+
+    /// Get the guild an invite leads to, if the invite can still be used.
+    pub async fn preview(&self, code: &str) -> Result<InvitePreview, PreviewError> {
+        let invite = sqlx::query!(
+            r#"
+            SELECT
+                i.code,
+                i.expires_at,
+                i.max_uses,
+                i.uses,
+                g.id AS guild_id,
+                g.name AS guild_name,
+                (SELECT COUNT(*) FROM guild_members WHERE guild_id = g.id) AS "member_count!",
+                u.id AS owner_id,
+                u.username AS owner_username,
+                u.discrim AS owner_discrim,
+                u.profile_img_id AS owner_profile_img_id,
+                u.accent_color AS owner_accent_color,
+                u.pronouns AS owner_pronouns,
+                u.bio AS owner_bio
+            FROM
+                invites i
+                INNER JOIN guilds g ON g.id = i.guild_id
+                INNER JOIN users u ON u.id = g.owner_id
+            WHERE
+                i.code = $1
+            "#,
+            code
+        )
+        .fetch_optional(self.conn)
+        .await?
+        .ok_or(PreviewError::NotFound)?;
+
+        if invite.expires_at.map_or(false, |at| at <= time::now()) {
+            return Err(PreviewError::Expired);
+        }
+        if invite.max_uses.map_or(false, |max| invite.uses >= max) {
+            return Err(PreviewError::Exhausted);
+        }
+
+        Ok(InvitePreview {
+            code: invite.code,
+            guild_id: invite.guild_id.into(),
+            guild_name: invite.guild_name,
+            member_count: invite.member_count,
+            owner: user::User {
+                id: invite.owner_id.into(),
+                username: invite.owner_username,
+                discrim: invite.owner_discrim,
+                profile_img_id: invite.owner_profile_img_id.map(Into::into),
+                accent_color: invite.owner_accent_color,
+                pronouns: invite.owner_pronouns,
+                bio: invite.owner_bio,
+            },
+            expires_at: invite.expires_at.and_then(|at| at.try_into().ok()),
+        })
+    }
+
+    /// Use an invite to make a user join its guild, and return the guild's
+    /// ID.
+    ///
+    /// The invite is locked while the user joins so that concurrent uses
+    /// cannot go over its maximum.
+    pub async fn accept(
+        &self,
+        code: &str,
+        member_id: Snowflake,
+        user: Snowflake,
+    ) -> Result<Snowflake, AcceptError> {
+        let mut tx = self.conn.begin().await?;
+
+        let invite = sqlx::query!(
+            "SELECT guild_id, expires_at, max_uses, uses FROM invites WHERE code = $1 FOR UPDATE",
+            code
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        .ok_or(AcceptError::NotFound)?;
+
+        if invite.expires_at.map_or(false, |at| at <= time::now()) {
+            return Err(AcceptError::Expired);
+        }
+        if invite.max_uses.map_or(false, |max| invite.uses >= max) {
+            return Err(AcceptError::Exhausted);
+        }
+
+        let guild = Snowflake::from(invite.guild_id);
+        match guild::GuildTable::join_in(&mut tx, member_id, guild, user).await {
+            Ok(()) => {}
+            Err(guild::JoinError::AlreadyMember) => return Err(AcceptError::AlreadyMember),
+            Err(guild::JoinError::Banned) => return Err(AcceptError::Banned),
+            Err(guild::JoinError::NotInserted) => return Err(AcceptError::NotInserted),
+            Err(guild::JoinError::DatabaseError(e)) => return Err(AcceptError::DatabaseError(e)),
+        }
+
+        sqlx::query!("UPDATE invites SET uses = uses + 1 WHERE code = $1", code)
+            .execute(&mut tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(guild)
+    }
+
+    /// Delete an invite from a guild.
+    pub async fn revoke(&self, guild: Snowflake, code: &str) -> Result<(), RevokeError> {
+        let success = sqlx::query!(
+            "DELETE FROM invites WHERE code = $1 AND guild_id = $2",
+            code,
+            guild.into_number()
+        )
+        .execute(self.conn)
+        .await?;
+
+        if success.rows_affected() == 0 {
+            return Err(RevokeError::NotFound);
+        }
+        Ok(())
+    }
+}
+
+struct InviteRow {
+    code: String,
+    guild_id: i64,
+    inviter_id: i64,
+    created_at: i64,
+    expires_at: Option<i64>,
+    max_uses: Option<i32>,
+    uses: i32,
+}
+
+impl From<InviteRow> for Invite {
+    #[allow(clippy::cast_sign_loss)]
+    fn from(invite: InviteRow) -> Self {
+        Invite {
+            code: invite.code,
+            guild_id: invite.guild_id.into(),
+            inviter_id: invite.inviter_id.into(),
+            created_at: invite.created_at as u64,
+            expires_at: invite.expires_at.map(|at| at as u64),
+            max_uses: invite.max_uses,
+            uses: invite.uses,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateError {
+    #[error("The entry was not inserted into the database")]
+    NotInserted,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PreviewError {
+    #[error("The invite does not exist")]
+    NotFound,
+    #[error("The invite has expired")]
+    Expired,
+    #[error("The invite has been used the maximum amount of times")]
+    Exhausted,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AcceptError {
+    #[error("The invite does not exist")]
+    NotFound,
+    #[error("The invite has expired")]
+    Expired,
+    #[error("The invite has been used the maximum amount of times")]
+    Exhausted,
+    #[error("The user is already a member of the guild")]
+    AlreadyMember,
+    #[error("The user is banned from the guild")]
+    Banned,
+    #[error("The entry was not inserted into the database")]
+    NotInserted,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RevokeError {
+    #[error("The invite does not exist in the guild")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
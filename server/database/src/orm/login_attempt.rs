@@ -0,0 +1,312 @@
+//! Every attempt to log in, used to slow down anyone guessing passwords.
+//!
+//! Failures are counted both for the email address and for the IP address
+//! they came from. After a few failures, each attempt has to wait twice as
+//! long as the last, until enough failures lock logins out for a while.
+//!
+//! Methods that depend on the time take it as an argument, so that it can
+//! come from a fake [`time::Clock`].
+
+use crate::prelude::*;
+
+/// Failures older than this are forgotten, in milliseconds. 1 hour.
+pub(crate) const WINDOW: i64 = 60 * 60 * 1000;
+/// The delay after the first failure past the free ones, in milliseconds.
+const BASE_DELAY: i64 = 1000;
+/// How long logins are locked out for, in milliseconds. 15 minutes.
+const LOCKOUT: i64 = 15 * 60 * 1000;
+/// How long attempts are kept for, in milliseconds. 30 days.
+const RETENTION: i64 = 30 * 24 * 60 * 60 * 1000;
+
+/// How many failures are allowed before logins are slowed down or locked.
+pub(crate) struct Policy {
+    /// Failures allowed without any delay.
+    pub(crate) free: i64,
+    /// Failures after which logins are locked out for [`LOCKOUT`].
+    pub(crate) lockout: i64,
+}
+impl Policy {
+    /// How long after the last failure the next attempt can be made.
+    fn delay(&self, failures: i64) -> i64 {
+        if failures >= self.lockout {
+            LOCKOUT
+        } else if failures > self.free {
+            let doublings = u32::try_from(failures - self.free - 1).unwrap_or(u32::MAX);
+            2i64.saturating_pow(doublings)
+                .saturating_mul(BASE_DELAY)
+                .min(LOCKOUT)
+        } else {
+            0
+        }
+    }
+
+    /// How long until the next attempt can be made, or 0 if it can be made
+    /// now.
+    pub(crate) fn retry_after(&self, failures: Failures, now: i64) -> i64 {
+        failures
+            .last
+            .map_or(0, |last| (last + self.delay(failures.count) - now).max(0))
+    }
+}
+
+/// The recent failures from an email or IP address, or at a user's 2FA
+/// codes.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Failures {
+    pub(crate) count: i64,
+    /// When the most recent failure was made, if there were any.
+    pub(crate) last: Option<i64>,
+}
+
+/// A single account is only guessed at by a few people.
+const ACCOUNT_POLICY: Policy = Policy {
+    free: 3,
+    lockout: 10,
+};
+/// Many people can share an IP address, so it's given more leeway.
+const IP_POLICY: Policy = Policy {
+    free: 10,
+    lockout: 50,
+};
+
+/// A failed attempt to log in to a user's account.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginAttempt {
+    pub id: Snowflake,
+    pub ip: String,
+    pub user_agent: Option<String>,
+    pub attempted_at: u64,
+}
+
+/// An interface for interacting with the `login_attempts` table of the
+/// database.
+pub struct LoginAttemptTable<'a> {
+    pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
+}
+
+impl<'a> LoginAttemptTable<'a> {
+    /// Records an attempt to log in as failed, unless there have been too
+    /// many failures from the email or IP address recently. Once the password
+    /// has been verified, the attempt is marked as a success with
+    /// [`Self::succeed`].
+    ///
+    /// The check and the insert happen while holding locks on both the email
+    /// and the IP address, so concurrent attempts can't all pass the check
+    /// before any of them is recorded.
+    pub async fn start(
+        &self,
+        id: Snowflake,
+        email: &str,
+        ip: &str,
+        user_agent: Option<&str>,
+        now: i64,
+    ) -> Result<(), StartError> {
+        let lowercase = email.to_lowercase();
+        let user_agent = user_agent.map(|ua| ua.chars().take(255).collect::<String>());
+        let mut tx = self.conn.begin().await?;
+
+        // Always in the same order, so that two attempts can't each hold the
+        // lock the other is waiting for
+        sqlx::query!(
+            "SELECT pg_advisory_xact_lock(hashtext('login:email:' || $1))",
+            lowercase
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!(
+            "SELECT pg_advisory_xact_lock(hashtext('login:ip:' || $1))",
+            ip
+        )
+        .execute(&mut tx)
+        .await?;
+
+        // Logging in successfully forgives the account's failures, but not
+        // the IP's, so that an attacker can't reset them with their own
+        // account.
+        let account = sqlx::query!(
+            r#"SELECT COUNT(*) AS "failures!", MAX(attempted_at) AS last_failure FROM login_attempts
+            WHERE email = $1 AND NOT succeeded AND attempted_at > $2 AND attempted_at > COALESCE(
+                (SELECT MAX(attempted_at) FROM login_attempts WHERE email = $1 AND succeeded),
+                $2
+            )"#,
+            lowercase,
+            now - WINDOW
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        let ip_failures = sqlx::query!(
+            r#"SELECT COUNT(*) AS "failures!", MAX(attempted_at) AS last_failure FROM login_attempts
+            WHERE ip = $1 AND NOT succeeded AND attempted_at > $2"#,
+            ip,
+            now - WINDOW
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        let account = Failures {
+            count: account.failures,
+            last: account.last_failure,
+        };
+        let ip_failures = Failures {
+            count: ip_failures.failures,
+            last: ip_failures.last_failure,
+        };
+        let retry_after = ACCOUNT_POLICY
+            .retry_after(account, now)
+            .max(IP_POLICY.retry_after(ip_failures, now));
+        if retry_after > 0 {
+            return Err(StartError::TooManyAttempts { retry_after });
+        }
+
+        sqlx::query!(
+            "INSERT INTO login_attempts (id, email, user_id, ip, user_agent, succeeded, attempted_at)
+            VALUES ($1, $2, (SELECT id FROM users WHERE email = $6), $3, $4, FALSE, $5)",
+            id.into_number(),
+            lowercase,
+            ip,
+            user_agent,
+            now,
+            email
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+
+        sqlx::query!(
+            "DELETE FROM login_attempts WHERE attempted_at < $1",
+            now - RETENTION
+        )
+        .execute(self.conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks an attempt recorded by [`Self::start`] as a success.
+    pub async fn succeed(&self, id: Snowflake) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE login_attempts SET succeeded = TRUE WHERE id = $1",
+            id.into_number()
+        )
+        .execute(self.conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the most recent failed attempts to log in to a user's account,
+    /// newest first.
+    pub async fn get_failed(
+        &self,
+        user_id: Snowflake,
+        limit: i64,
+    ) -> Result<Vec<LoginAttempt>, sqlx::Error> {
+        let attempts = sqlx::query!(
+            "SELECT id, ip, user_agent, attempted_at FROM login_attempts
+            WHERE user_id = $1 AND NOT succeeded ORDER BY attempted_at DESC LIMIT $2",
+            user_id.into_number(),
+            limit
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        #[allow(clippy::cast_sign_loss)]
+        Ok(attempts
+            .into_iter()
+            .map(|attempt| LoginAttempt {
+                id: attempt.id.into(),
+                ip: attempt.ip,
+                user_agent: attempt.user_agent,
+                attempted_at: attempt.attempted_at as u64,
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StartError {
+    #[error("Too many failed attempts, try again in {retry_after}ms")]
+    TooManyAttempts {
+        /// How long until the next attempt can be made, in milliseconds.
+        retry_after: i64,
+    },
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::Clock;
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    struct FakeClock(AtomicI64);
+    impl FakeClock {
+        fn advance(&self, ms: i64) {
+            self.0.fetch_add(ms, Ordering::Relaxed);
+        }
+    }
+    impl Clock for FakeClock {
+        fn now(&self) -> i64 {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    /// Fails to log in as soon as each attempt is allowed, returning how long
+    /// each one had to wait.
+    fn fail_repeatedly(policy: &Policy, clock: &FakeClock, attempts: usize) -> Vec<i64> {
+        let mut failures = Failures::default();
+        (0..attempts)
+            .map(|_| {
+                let wait = policy.retry_after(failures, clock.now());
+                if wait > 0 {
+                    clock.advance(wait - 1);
+                    assert_eq!(policy.retry_after(failures, clock.now()), 1);
+                    clock.advance(1);
+                }
+                assert_eq!(policy.retry_after(failures, clock.now()), 0);
+                failures = Failures {
+                    count: failures.count + 1,
+                    last: Some(clock.now()),
+                };
+                wait
+            })
+            .collect()
+    }
+
+    #[test]
+    fn account_backs_off_then_locks_out() {
+        let clock = FakeClock(AtomicI64::new(1_000_000));
+        let waits = fail_repeatedly(&ACCOUNT_POLICY, &clock, 12);
+        assert_eq!(
+            waits,
+            [0, 0, 0, 0, 1000, 2000, 4000, 8000, 16000, 32000, LOCKOUT, LOCKOUT]
+        );
+    }
+
+    #[test]
+    fn ip_backs_off_then_locks_out() {
+        let clock = FakeClock(AtomicI64::new(1_000_000));
+        let waits = fail_repeatedly(&IP_POLICY, &clock, 52);
+        assert!(waits[..11].iter().all(|&wait| wait == 0));
+        assert_eq!(waits[11], 1000);
+        assert_eq!(waits[20], 512_000);
+        // Capped at the lockout before reaching it
+        assert!(waits[21..].iter().all(|&wait| wait == LOCKOUT));
+    }
+
+    #[test]
+    fn lockout_counts_from_last_failure() {
+        let clock = FakeClock(AtomicI64::new(1_000_000));
+        let failures = Failures {
+            count: ACCOUNT_POLICY.lockout,
+            last: Some(clock.now()),
+        };
+        clock.advance(LOCKOUT / 3);
+        assert_eq!(
+            ACCOUNT_POLICY.retry_after(failures, clock.now()),
+            LOCKOUT - LOCKOUT / 3
+        );
+        clock.advance(LOCKOUT);
+        assert_eq!(ACCOUNT_POLICY.retry_after(failures, clock.now()), 0);
+    }
+}
@@ -8,6 +8,46 @@ pub struct Message {
     pub content: String,
     pub sent_at: u64,
     pub updated_at: u64,
+    /// Whether the message was deleted. Deleted messages are kept as a
+    /// placeholder with no content.
+    pub deleted: bool,
+    /// The reactions on the message, as seen by the user who fetched it.
+    /// Only included in pages of messages, since `me` depends on who asked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reactions: Option<Vec<reaction::Reaction>>,
+}
+
+/// A prior version of an edited message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    pub id: Snowflake,
+    pub message_id: Snowflake,
+    pub content: String,
+    /// When this version of the message was written.
+    pub written_at: u64,
+}
+
+/// Where to start a page of messages from.
+#[derive(Debug, Clone, Copy)]
+pub enum Cursor {
+    /// The most recent messages in the channel.
+    Latest,
+    /// The messages sent just before this message.
+    Before(Snowflake),
+    /// The messages sent just after this message.
+    After(Snowflake),
+    /// The messages sent around this message, including itself.
+    Around(Snowflake),
+}
+
+/// A page of messages, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagePage {
+    pub messages: Vec<Message>,
+    /// Whether there are older messages than the oldest in this page.
+    pub has_more_before: bool,
+    /// Whether there are newer messages than the newest in this page.
+    pub has_more_after: bool,
 }
 
 /// An interface for interacting with the `messages` table of the database.
@@ -30,6 +70,9 @@ impl<'a> MessageTable<'a> {
             .await)
         {
             Err(channel::HasWritePermissionError::NotFound) => return Err(CreateError::NotFound),
+            Err(channel::HasWritePermissionError::PermissionDenied) => {
+                return Err(CreateError::PermissionDenied)
+            }
             Err(channel::HasWritePermissionError::DatabaseError(e)) => {
                 return Err(CreateError::DatabaseError(e))
             }
@@ -54,50 +97,317 @@ impl<'a> MessageTable<'a> {
         }
     }
 
-    /// Get the messages in a channel, ordered by creation time, with a
-    /// limit and offset.
-    #[allow(clippy::cast_sign_loss)]
-    pub async fn get(
+    /// Get a single message by its ID.
+    pub async fn get_by_id(&self, id: Snowflake) -> Result<Option<Message>, GetError> {
+        let message = sqlx::query_as!(
+            MessageRow,
+            "SELECT m.id, m.channel_id, m.author_id, m.content, m.updated_at, m.deleted_at, u.username, u.discrim, u.profile_img_id, u.accent_color, u.pronouns, u.bio FROM messages m LEFT JOIN users u ON m.author_id = u.id WHERE m.id = $1",
+            id.into_number()
+        )
+        .fetch_optional(self.conn)
+        .await?;
+
+        Ok(message.map(Into::into))
+    }
+
+    /// Replace the content of a message, keeping its previous content as a
+    /// revision.
+    ///
+    /// Only the author of a message can edit it, and deleted messages cannot
+    /// be edited.
+    pub async fn edit(
         &self,
+        revision_id: Snowflake,
         channel: Snowflake,
-        limit: i64,
-        offset: i64,
-    ) -> Result<Vec<Message>, GetError> {
-        let messages = sqlx::query!(
-            "SELECT m.id, m.channel_id, m.author_id, m.content, m.updated_at, u.username, u.discrim, u.profile_img_id, u.accent_color, u.pronouns, u.bio FROM messages m LEFT JOIN users u ON m.author_id = u.id WHERE m.channel_id = $1 ORDER BY m.id DESC LIMIT $2 OFFSET $3",
+        message: Snowflake,
+        author: Snowflake,
+        content: &str,
+    ) -> Result<Message, EditError> {
+        let mut tx = self.conn.begin().await?;
+
+        let current = sqlx::query!(
+            "SELECT author_id, content, updated_at FROM messages WHERE id = $1 AND channel_id = $2 AND deleted_at IS NULL FOR UPDATE",
+            message.into_number(),
+            channel.into_number()
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        .ok_or(EditError::NotFound)?;
+        if current.author_id != author.into_number() {
+            return Err(EditError::NotAuthor);
+        }
+
+        sqlx::query!(
+            "INSERT INTO message_revisions (id, message_id, content, written_at) VALUES ($1, $2, $3, $4)",
+            revision_id.into_number(),
+            message.into_number(),
+            current.content,
+            current.updated_at
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE messages SET content = $2, updated_at = $3 WHERE id = $1",
+            message.into_number(),
+            content,
+            time::now()
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_by_id(message)
+            .await
+            .map_err(|GetError::DatabaseError(e)| EditError::DatabaseError(e))?
+            .ok_or(EditError::NotFound)
+    }
+
+    /// Mark a message as deleted. Its content and revisions are kept, but
+    /// are no longer returned alongside the message.
+    pub async fn delete(&self, channel: Snowflake, message: Snowflake) -> Result<(), DeleteError> {
+        let success = sqlx::query!(
+            "UPDATE messages SET deleted_at = $3 WHERE id = $1 AND channel_id = $2 AND deleted_at IS NULL",
+            message.into_number(),
             channel.into_number(),
-            limit,
-            offset
+            time::now()
+        )
+        .execute(self.conn)
+        .await?;
+
+        if success.rows_affected() == 0 {
+            return Err(DeleteError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// Get every prior version of a message, oldest first.
+    pub async fn get_revisions(
+        &self,
+        channel: Snowflake,
+        message: Snowflake,
+    ) -> Result<Vec<Revision>, sqlx::Error> {
+        let revisions = sqlx::query!(
+            "SELECT r.id, r.message_id, r.content, r.written_at FROM message_revisions r INNER JOIN messages m ON m.id = r.message_id WHERE r.message_id = $1 AND m.channel_id = $2 ORDER BY r.id",
+            message.into_number(),
+            channel.into_number()
         )
         .fetch_all(self.conn)
         .await?;
 
-        Ok(messages
+        #[allow(clippy::cast_sign_loss)]
+        Ok(revisions
             .into_iter()
-            .map(|message| Message {
-                id: message.id.into(),
-                channel_id: message.channel_id.into(),
-                author: user::User {
-                    id: message.author_id.into(),
-                    username: message.username,
-                    discrim: message.discrim,
-                    profile_img_id: message.profile_img_id.map(Into::into),
-                    accent_color: message.accent_color,
-                    pronouns: message.pronouns,
-                    bio: message.bio,
-                },
-                content: message.content,
-                sent_at: Snowflake::from_number(message.id as u64).timestamp,
-                updated_at: message.updated_at as u64,
+            .map(|revision| Revision {
+                id: revision.id.into(),
+                message_id: revision.message_id.into(),
+                content: revision.content,
+                written_at: revision.written_at as u64,
             })
             .collect())
     }
+
+    /// Get a page of messages in a channel, newest first, with their
+    /// reactions as seen by `user`.
+    ///
+    /// Snowflakes are time-ordered, so paging by ID gives a stable order
+    /// that does not shift as new messages are sent.
+    pub async fn get(
+        &self,
+        channel: Snowflake,
+        user: Snowflake,
+        cursor: Cursor,
+        limit: i64,
+    ) -> Result<MessagePage, GetError> {
+        let channel = channel.into_number();
+
+        let (messages, has_more_before, has_more_after) = match cursor {
+            Cursor::Latest => {
+                let mut older = self.get_older(channel, None, limit + 1).await?;
+                let has_more_before = truncate(&mut older, limit);
+                (older, has_more_before, false)
+            }
+            Cursor::Before(id) => {
+                let id = id.into_number();
+                let mut older = self.get_older(channel, Some(id), limit + 1).await?;
+                let has_more_before = truncate(&mut older, limit);
+                let has_more_after = self.has_newer(channel, id - 1).await?;
+                (older, has_more_before, has_more_after)
+            }
+            Cursor::After(id) => {
+                let id = id.into_number();
+                let mut newer = self.get_newer(channel, id, limit + 1).await?;
+                let has_more_after = truncate(&mut newer, limit);
+                let has_more_before = self.has_older(channel, id + 1).await?;
+                newer.reverse();
+                (newer, has_more_before, has_more_after)
+            }
+            Cursor::Around(id) => {
+                // The message itself is included in the newer half
+                let id = id.into_number();
+                let older_limit = limit / 2;
+                let newer_limit = limit - older_limit;
+
+                let mut newer = self.get_newer(channel, id - 1, newer_limit + 1).await?;
+                let has_more_after = truncate(&mut newer, newer_limit);
+                let mut older = self.get_older(channel, Some(id), older_limit + 1).await?;
+                let has_more_before = truncate(&mut older, older_limit);
+
+                newer.reverse();
+                newer.append(&mut older);
+                (newer, has_more_before, has_more_after)
+            }
+        };
+
+        let ids: Vec<i64> = messages.iter().map(|message| message.id).collect();
+        let mut reactions = reaction::ReactionTable { conn: self.conn }
+            .get_counts(&ids, user)
+            .await?;
+        let messages = messages
+            .into_iter()
+            .map(|row| {
+                let mut message = Message::from(row);
+                let reactions = reactions.remove(&message.id.into_number());
+                // Deleted messages keep their reactions, but don't show them
+                message.reactions = Some(if message.deleted {
+                    vec![]
+                } else {
+                    reactions.unwrap_or_default()
+                });
+                message
+            })
+            .collect();
+
+        Ok(MessagePage {
+            messages,
+            has_more_before,
+            has_more_after,
+        })
+    }
+
+    /// Get up to `limit` messages sent before `before`, newest first.
+    async fn get_older(
+        &self,
+        channel: i64,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<MessageRow>, sqlx::Error> {
+        sqlx::query_as!(
+            MessageRow,
+            "SELECT m.id, m.channel_id, m.author_id, m.content, m.updated_at, m.deleted_at, u.username, u.discrim, u.profile_img_id, u.accent_color, u.pronouns, u.bio FROM messages m LEFT JOIN users u ON m.author_id = u.id WHERE m.channel_id = $1 AND ($2::bigint IS NULL OR m.id < $2) ORDER BY m.id DESC LIMIT $3",
+            channel,
+            before,
+            limit
+        )
+        .fetch_all(self.conn)
+        .await
+    }
+
+    /// Get up to `limit` messages sent after `after`, oldest first.
+    async fn get_newer(
+        &self,
+        channel: i64,
+        after: i64,
+        limit: i64,
+    ) -> Result<Vec<MessageRow>, sqlx::Error> {
+        sqlx::query_as!(
+            MessageRow,
+            "SELECT m.id, m.channel_id, m.author_id, m.content, m.updated_at, m.deleted_at, u.username, u.discrim, u.profile_img_id, u.accent_color, u.pronouns, u.bio FROM messages m LEFT JOIN users u ON m.author_id = u.id WHERE m.channel_id = $1 AND m.id > $2 ORDER BY m.id ASC LIMIT $3",
+            channel,
+            after,
+            limit
+        )
+        .fetch_all(self.conn)
+        .await
+    }
+
+    /// Whether any message was sent in the channel before `before`.
+    async fn has_older(&self, channel: i64, before: i64) -> Result<bool, sqlx::Error> {
+        let exists = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM messages WHERE channel_id = $1 AND id < $2)",
+            channel,
+            before
+        )
+        .fetch_one(self.conn)
+        .await?;
+
+        Ok(exists == Some(true))
+    }
+
+    /// Whether any message was sent in the channel after `after`.
+    async fn has_newer(&self, channel: i64, after: i64) -> Result<bool, sqlx::Error> {
+        let exists = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM messages WHERE channel_id = $1 AND id > $2)",
+            channel,
+            after
+        )
+        .fetch_one(self.conn)
+        .await?;
+
+        Ok(exists == Some(true))
+    }
+}
+
+/// Cuts `rows` down to `limit` and returns whether any were cut off.
+fn truncate(rows: &mut Vec<MessageRow>, limit: i64) -> bool {
+    let limit = usize::try_from(limit).unwrap_or_default();
+    let cut = rows.len() > limit;
+    rows.truncate(limit);
+    cut
+}
+
+/// A message joined with its author, as selected from the database.
+struct MessageRow {
+    id: i64,
+    channel_id: i64,
+    author_id: i64,
+    content: String,
+    updated_at: i64,
+    deleted_at: Option<i64>,
+    username: String,
+    discrim: i16,
+    profile_img_id: Option<i64>,
+    accent_color: Option<String>,
+    pronouns: Option<String>,
+    bio: Option<String>,
+}
+
+impl From<MessageRow> for Message {
+    #[allow(clippy::cast_sign_loss)]
+    fn from(message: MessageRow) -> Self {
+        Message {
+            id: message.id.into(),
+            channel_id: message.channel_id.into(),
+            author: user::User {
+                id: message.author_id.into(),
+                username: message.username,
+                discrim: message.discrim,
+                profile_img_id: message.profile_img_id.map(Into::into),
+                accent_color: message.accent_color,
+                pronouns: message.pronouns,
+                bio: message.bio,
+            },
+            // Never expose what a deleted message said
+            content: if message.deleted_at.is_some() {
+                String::new()
+            } else {
+                message.content
+            },
+            sent_at: Snowflake::from_number(message.id as u64).timestamp,
+            updated_at: message.updated_at as u64,
+            deleted: message.deleted_at.is_some(),
+            reactions: None,
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum CreateError {
     #[error("The channel does not exist or the user does not have permission to view it")]
     NotFound,
+    #[error("The user does not have permission to send messages in the channel")]
+    PermissionDenied,
     #[error("The entry was not inserted into the database")]
     NotInserted,
     #[error("An error occurred while querying the database")]
@@ -109,3 +419,21 @@ pub enum GetError {
     #[error("An error occurred while querying the database")]
     DatabaseError(#[from] sqlx::Error),
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum EditError {
+    #[error("The message does not exist in the channel or was deleted")]
+    NotFound,
+    #[error("The user is not the author of the message")]
+    NotAuthor,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteError {
+    #[error("The message does not exist in the channel or was already deleted")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
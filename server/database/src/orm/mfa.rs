@@ -0,0 +1,476 @@
+//! Two-factor authentication with an authenticator app.
+//!
+//! A user enrols by being given a new secret, then confirms it with a code
+//! from their app. Only then is it enabled and are they given their
+//! recovery codes, each of which can be used once instead of a code.
+//!
+//! Logging in with 2FA enabled gives an MFA ticket rather than an access
+//! token. The ticket is exchanged for an access token along with a code.
+//!
+//! Each ticket can only be exchanged once, and stops working after
+//! [`TICKET_MAX_FAILURES`] wrong codes. Wrong codes are also counted for the
+//! user across tickets, and slow down or lock out further attempts the same
+//! way failed logins do, as anyone with a ticket already knows the password.
+
+use crate::{
+    orm::login_attempt::{Failures, Policy, WINDOW},
+    prelude::*,
+    totp,
+};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header};
+use sha2::{Digest, Sha256};
+
+/// The audience of MFA tickets, so that they can't be mistaken for any other
+/// JWT signed with the same secret.
+const TICKET_AUDIENCE: &str = "mfa";
+/// How long an MFA ticket can be exchanged for, in seconds. 5 minutes.
+const TICKET_LIFETIME: u64 = 5 * 60;
+/// How many wrong codes can be given with one ticket before it stops
+/// working.
+const TICKET_MAX_FAILURES: i32 = 3;
+/// How many wrong codes a user can give before logging in with 2FA is slowed
+/// down or locked.
+const MFA_POLICY: Policy = Policy {
+    free: 3,
+    lockout: 10,
+};
+/// How many recovery codes a user is given.
+const RECOVERY_CODES: usize = 10;
+/// The characters in a recovery code. No `0`, `1`, `l` or `o`, so they
+/// can't be misread.
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghijkmnpqrstuvwxyz23456789";
+
+/// A secret the user has to add to their authenticator app.
+#[derive(Debug, Clone, Serialize)]
+pub struct Enrolment {
+    /// The secret, base32 encoded, for typing in manually.
+    pub secret: String,
+    /// The `otpauth://` URI, for showing as a QR code.
+    pub uri: String,
+}
+
+/// The claims of an MFA ticket.
+#[derive(Debug, Serialize, Deserialize)]
+struct TicketClaims {
+    sub: Snowflake,
+    aud: String,
+    exp: u64,
+    /// The ID of the ticket in the `mfa_tickets` table.
+    jti: i64,
+}
+
+fn hash(code: &str) -> Vec<u8> {
+    Sha256::digest(code.as_bytes()).to_vec()
+}
+
+/// Strips the formatting from a code the user typed in, such as the dash in
+/// the middle of a recovery code.
+fn normalize(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn generate_recovery_code() -> String {
+    let mut rng = rand::thread_rng();
+    let mut code: String = (0..10)
+        .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+        .collect();
+    code.insert(5, '-');
+    code
+}
+
+/// An interface for interacting with the `user_totp` and `recovery_codes`
+/// tables of the database.
+pub struct MfaTable<'a> {
+    pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
+}
+
+impl<'a> MfaTable<'a> {
+    /// Whether the user has 2FA enabled.
+    pub async fn is_enabled(&self, user: Snowflake) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM user_totp WHERE user_id = $1 AND enabled_at IS NOT NULL) AS "exists!""#,
+            user.into_number()
+        )
+        .fetch_one(self.conn)
+        .await
+    }
+
+    /// Generates a new secret for the user. It isn't used until it is
+    /// confirmed with [`MfaTable::confirm`]. Enrolling again before then
+    /// replaces the secret.
+    pub async fn enrol(&self, user: Snowflake, account: &str) -> Result<Enrolment, EnrolError> {
+        let secret = totp::generate_secret();
+
+        let success = sqlx::query!(
+            "INSERT INTO user_totp (user_id, secret) VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE SET secret = $2, last_used_step = 0
+            WHERE user_totp.enabled_at IS NULL",
+            user.into_number(),
+            totp::encrypt(&secret)
+        )
+        .execute(self.conn)
+        .await?;
+
+        if success.rows_affected() != 1 {
+            return Err(EnrolError::AlreadyEnabled);
+        }
+
+        Ok(Enrolment {
+            secret: totp::base32(&secret),
+            uri: totp::uri(&secret, account),
+        })
+    }
+
+    /// Enables 2FA after the user proves their authenticator app works.
+    /// Returns the user's recovery codes, which are not shown again.
+    pub async fn confirm(&self, user: Snowflake, code: &str) -> Result<Vec<String>, ConfirmError> {
+        let mut tx = self.conn.begin().await?;
+
+        let stored = sqlx::query!(
+            "SELECT secret, enabled_at FROM user_totp WHERE user_id = $1 FOR UPDATE",
+            user.into_number()
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        .ok_or(ConfirmError::NotEnrolled)?;
+
+        if stored.enabled_at.is_some() {
+            return Err(ConfirmError::AlreadyEnabled);
+        }
+
+        let secret = totp::decrypt(&stored.secret).ok_or(ConfirmError::UndecryptableSecret)?;
+        let step = totp::verify(&secret, &normalize(code)).ok_or(ConfirmError::InvalidCode)?;
+
+        #[allow(clippy::cast_possible_wrap)]
+        sqlx::query!(
+            "UPDATE user_totp SET enabled_at = $2, last_used_step = $3 WHERE user_id = $1",
+            user.into_number(),
+            time::now(),
+            step as i64
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let codes = self.replace_recovery_codes(&mut tx, user).await?;
+
+        tx.commit().await?;
+        Ok(codes)
+    }
+
+    /// Replaces all of the user's recovery codes with new ones.
+    async fn replace_recovery_codes(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        user: Snowflake,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM recovery_codes WHERE user_id = $1",
+            user.into_number()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let codes: Vec<String> = (0..RECOVERY_CODES)
+            .map(|_| generate_recovery_code())
+            .collect();
+        let hashes: Vec<Vec<u8>> = codes.iter().map(|code| hash(&normalize(code))).collect();
+        sqlx::query!(
+            "INSERT INTO recovery_codes (user_id, code_hash) SELECT $1, * FROM UNNEST($2::bytea[])",
+            user.into_number(),
+            &hashes
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(codes)
+    }
+
+    /// Checks a code from the user's authenticator app or one of their
+    /// recovery codes. Recovery codes are used up, and codes from the app
+    /// can't be used again.
+    pub async fn verify(&self, user: Snowflake, code: &str) -> Result<(), VerifyError> {
+        let mut tx = self.conn.begin().await?;
+        self.verify_in(&mut tx, user, code).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn verify_in(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        user: Snowflake,
+        code: &str,
+    ) -> Result<(), VerifyError> {
+        let stored = sqlx::query!(
+            "SELECT secret, last_used_step FROM user_totp
+            WHERE user_id = $1 AND enabled_at IS NOT NULL FOR UPDATE",
+            user.into_number()
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(VerifyError::NotEnabled)?;
+
+        let code = normalize(code);
+        if code.len() == totp::DIGITS {
+            let secret = totp::decrypt(&stored.secret).ok_or(VerifyError::UndecryptableSecret)?;
+
+            #[allow(clippy::cast_possible_wrap)]
+            let step = totp::verify(&secret, &code)
+                .map(|step| step as i64)
+                .filter(|step| *step > stored.last_used_step)
+                .ok_or(VerifyError::InvalidCode)?;
+
+            sqlx::query!(
+                "UPDATE user_totp SET last_used_step = $2 WHERE user_id = $1",
+                user.into_number(),
+                step
+            )
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            let consumed = sqlx::query!(
+                "DELETE FROM recovery_codes WHERE user_id = $1 AND code_hash = $2",
+                user.into_number(),
+                hash(&code)
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            if consumed.rows_affected() != 1 {
+                return Err(VerifyError::InvalidCode);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates the ticket given when a user with 2FA enabled logs in, and
+    /// forgets tickets that have expired.
+    pub async fn create_ticket(&self, user: Snowflake, now: i64) -> Result<String, TicketError> {
+        let id: i64 = rand::thread_rng().gen();
+        #[allow(clippy::cast_possible_wrap)]
+        let expires_at = now + TICKET_LIFETIME as i64 * 1000;
+
+        sqlx::query!("DELETE FROM mfa_tickets WHERE expires_at < $1", now)
+            .execute(self.conn)
+            .await?;
+        sqlx::query!(
+            "INSERT INTO mfa_tickets (id, user_id, expires_at) VALUES ($1, $2, $3)",
+            id,
+            user.into_number(),
+            expires_at
+        )
+        .execute(self.conn)
+        .await?;
+
+        let exp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs())
+            + TICKET_LIFETIME;
+        let claims = TicketClaims {
+            sub: user,
+            aud: TICKET_AUDIENCE.to_string(),
+            exp,
+            jti: id,
+        };
+
+        Ok(jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(&access_token::secret()),
+        )?)
+    }
+
+    /// Exchanges an MFA ticket and a code for the ID of the user who logged
+    /// in, using up the ticket.
+    ///
+    /// Wrong codes are recorded while holding a lock on the user, so
+    /// concurrent guesses can't all pass the check before any of them is
+    /// counted.
+    pub async fn redeem(
+        &self,
+        ticket: &str,
+        code: &str,
+        now: i64,
+    ) -> Result<Snowflake, RedeemError> {
+        let mut validation = jsonwebtoken::Validation::default();
+        validation.set_audience(&[TICKET_AUDIENCE]);
+        let claims = jsonwebtoken::decode::<TicketClaims>(
+            ticket,
+            &DecodingKey::from_secret(&access_token::secret()),
+            &validation,
+        )
+        .map_err(|_| RedeemError::InvalidTicket)?
+        .claims;
+        let user = claims.sub;
+
+        let mut tx = self.conn.begin().await?;
+        sqlx::query!(
+            "SELECT pg_advisory_xact_lock(hashtext('mfa:user:' || $1::bigint))",
+            user.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let ticket_failures = sqlx::query_scalar!(
+            "SELECT failures FROM mfa_tickets WHERE id = $1 AND user_id = $2 AND expires_at > $3",
+            claims.jti,
+            user.into_number(),
+            now
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        .ok_or(RedeemError::InvalidTicket)?;
+
+        // 2FA was turned off since the ticket was made if there's no row
+        let failures = sqlx::query!(
+            "SELECT failures, last_failure_at FROM user_totp
+            WHERE user_id = $1 AND enabled_at IS NOT NULL",
+            user.into_number()
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        .ok_or(RedeemError::InvalidTicket)?;
+        let failures = Failures {
+            count: i64::from(failures.failures),
+            last: failures.last_failure_at,
+        };
+        let failures = match failures.last {
+            Some(last) if last > now - WINDOW => failures,
+            _ => Failures::default(),
+        };
+        let retry_after = MFA_POLICY.retry_after(failures, now);
+        if retry_after > 0 {
+            return Err(RedeemError::TooManyAttempts { retry_after });
+        }
+
+        match self.verify_in(&mut tx, user, code).await {
+            Ok(()) => {
+                sqlx::query!("DELETE FROM mfa_tickets WHERE id = $1", claims.jti)
+                    .execute(&mut tx)
+                    .await?;
+                sqlx::query!(
+                    "UPDATE user_totp SET failures = 0, last_failure_at = NULL WHERE user_id = $1",
+                    user.into_number()
+                )
+                .execute(&mut tx)
+                .await?;
+                tx.commit().await?;
+                Ok(user)
+            }
+            Err(VerifyError::InvalidCode) => {
+                #[allow(clippy::cast_possible_truncation)]
+                let count = (failures.count + 1).min(i64::from(i32::MAX)) as i32;
+                sqlx::query!(
+                    "UPDATE user_totp SET failures = $2, last_failure_at = $3 WHERE user_id = $1",
+                    user.into_number(),
+                    count,
+                    now
+                )
+                .execute(&mut tx)
+                .await?;
+                if ticket_failures + 1 >= TICKET_MAX_FAILURES {
+                    sqlx::query!("DELETE FROM mfa_tickets WHERE id = $1", claims.jti)
+                        .execute(&mut tx)
+                        .await?;
+                } else {
+                    sqlx::query!(
+                        "UPDATE mfa_tickets SET failures = failures + 1 WHERE id = $1",
+                        claims.jti
+                    )
+                    .execute(&mut tx)
+                    .await?;
+                }
+                tx.commit().await?;
+                Err(RedeemError::InvalidCode)
+            }
+            Err(VerifyError::NotEnabled) => Err(RedeemError::InvalidTicket),
+            Err(VerifyError::UndecryptableSecret) => Err(RedeemError::UndecryptableSecret),
+            Err(VerifyError::DatabaseError(e)) => Err(RedeemError::DatabaseError(e)),
+        }
+    }
+
+    /// Turns off 2FA, after checking a code like [`MfaTable::verify`].
+    pub async fn disable(&self, user: Snowflake, code: &str) -> Result<(), VerifyError> {
+        let mut tx = self.conn.begin().await?;
+        self.verify_in(&mut tx, user, code).await?;
+
+        sqlx::query!(
+            "DELETE FROM recovery_codes WHERE user_id = $1",
+            user.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM user_totp WHERE user_id = $1",
+            user.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EnrolError {
+    #[error("2FA is already enabled")]
+    AlreadyEnabled,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfirmError {
+    #[error("The user has not enrolled in 2FA")]
+    NotEnrolled,
+    #[error("2FA is already enabled")]
+    AlreadyEnabled,
+    #[error("The code is incorrect")]
+    InvalidCode,
+    #[error("The secret could not be decrypted, has TOTP_KEY changed?")]
+    UndecryptableSecret,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum VerifyError {
+    #[error("2FA is not enabled")]
+    NotEnabled,
+    #[error("The code is incorrect or was already used")]
+    InvalidCode,
+    #[error("The secret could not be decrypted, has TOTP_KEY changed?")]
+    UndecryptableSecret,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TicketError {
+    #[error("Could not encode the JWT")]
+    JwtEncoding(#[from] jsonwebtoken::errors::Error),
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RedeemError {
+    #[error("The ticket is invalid, has expired or was already used")]
+    InvalidTicket,
+    #[error("The code is incorrect or was already used")]
+    InvalidCode,
+    #[error("Too many wrong codes, try again in {retry_after}ms")]
+    TooManyAttempts {
+        /// How long until the next attempt can be made, in milliseconds.
+        retry_after: i64,
+    },
+    #[error("The secret could not be decrypted, has TOTP_KEY changed?")]
+    UndecryptableSecret,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
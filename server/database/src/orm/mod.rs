@@ -1,5 +1,19 @@
 pub mod access_token;
+pub mod ban;
 pub mod channel;
+pub mod channel_overwrite;
+pub mod dm;
 pub mod guild;
+pub mod guild_folder;
+pub mod invite;
+pub mod login_attempt;
 pub mod message;
+pub mod mfa;
+pub mod password_reset;
+pub mod reaction;
+pub mod relationship;
+pub mod role;
 pub mod user;
+
+/// The Postgres error code for a row that breaks a unique constraint.
+pub(crate) const UNIQUE_VIOLATION: &str = "23505";
@@ -0,0 +1,132 @@
+//! Single-use tokens that let a user set a new password without knowing
+//! their current one.
+//!
+//! The token is only ever sent to the user's email address. The database
+//! stores its hash, so a token can't be recovered from the database.
+
+use crate::prelude::*;
+use sha2::{Digest, Sha256};
+
+/// How long a reset token can be used for, in milliseconds. 1 hour.
+const TOKEN_LIFETIME: i64 = 60 * 60 * 1000;
+
+/// An interface for interacting with the `password_resets` table of the
+/// database.
+pub struct PasswordResetTable<'a> {
+    pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
+}
+
+fn hash(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
+
+impl<'a> PasswordResetTable<'a> {
+    /// Creates a new reset token for the user and returns it. Any tokens the
+    /// user was sent previously stop working.
+    pub async fn create(&self, user: Snowflake) -> Result<String, CreateError> {
+        use base64::Engine;
+
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill(&mut bytes);
+        let token = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+        let created_at = time::now();
+
+        let mut tx = self.conn.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM password_resets WHERE user_id = $1",
+            user.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let success = sqlx::query!(
+            "INSERT INTO password_resets (token_hash, user_id, created_at, expires_at) VALUES ($1, $2, $3, $4)",
+            hash(&token),
+            user.into_number(),
+            created_at,
+            created_at + TOKEN_LIFETIME
+        )
+        .execute(&mut tx)
+        .await?;
+
+        if success.rows_affected() != 1 {
+            return Err(CreateError::NotInserted);
+        }
+
+        tx.commit().await?;
+        Ok(token)
+    }
+
+    /// Gets the user a reset token belongs to, without using it up.
+    pub async fn get_user(&self, token: &str) -> Result<Option<Snowflake>, sqlx::Error> {
+        let user_id = sqlx::query_scalar!(
+            "SELECT user_id FROM password_resets WHERE token_hash = $1 AND expires_at > $2",
+            hash(token),
+            time::now()
+        )
+        .fetch_optional(self.conn)
+        .await?;
+
+        Ok(user_id.map(Into::into))
+    }
+
+    /// Uses up a reset token to set the user's password, logging them out
+    /// everywhere. Returns the revoked access tokens.
+    pub async fn consume<'pw, P: Into<password::Password<'pw>>>(
+        &self,
+        token: &str,
+        password: P,
+    ) -> Result<Vec<i64>, ConsumeError> {
+        let mut tx = self.conn.begin().await?;
+
+        let reset = sqlx::query!(
+            "DELETE FROM password_resets WHERE token_hash = $1 RETURNING user_id, expires_at",
+            hash(token)
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        .ok_or(ConsumeError::InvalidToken)?;
+
+        if reset.expires_at <= time::now() {
+            // Still commit so the expired token is cleaned up
+            tx.commit().await?;
+            return Err(ConsumeError::InvalidToken);
+        }
+
+        let phc = password.into().generate();
+        sqlx::query!(
+            "UPDATE users SET phc = $2 WHERE id = $1",
+            reset.user_id,
+            phc
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let revoked = sqlx::query_scalar!(
+            "DELETE FROM access_tokens WHERE user_id = $1 RETURNING token",
+            reset.user_id
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(revoked)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateError {
+    #[error("The entry was not inserted into the database")]
+    NotInserted,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConsumeError {
+    #[error("The reset token does not exist, was already used, or has expired")]
+    InvalidToken,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
@@ -0,0 +1,214 @@
+//! Emoji reactions on messages.
+//!
+//! Each user can react to a message with each emoji once. Reactions are
+//! stored per user, and counted up when messages are fetched.
+
+use std::collections::HashMap;
+
+use crate::prelude::*;
+
+/// An emoji a message can be reacted with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Emoji {
+    /// A standard Unicode emoji, such as `👍`.
+    Unicode(String),
+    /// The ID of a guild's custom emoji.
+    Custom(Snowflake),
+}
+
+impl Emoji {
+    /// Parses an emoji as given in a URL: either the Unicode emoji itself, or
+    /// the ID of a custom emoji.
+    pub fn parse(emoji: &str) -> Option<Self> {
+        if !emoji.is_empty() && emoji.chars().all(|c| c.is_ascii_digit()) {
+            return emoji.parse::<u64>().ok().map(|id| Emoji::Custom(id.into()));
+        }
+        validation::validate_emoji(emoji).then(|| Emoji::Unicode(emoji.to_string()))
+    }
+
+    /// Returns how the emoji is stored in the database.
+    pub fn to_key(&self) -> String {
+        match self {
+            Emoji::Unicode(emoji) => emoji.clone(),
+            Emoji::Custom(id) => id.into_number().to_string(),
+        }
+    }
+
+    /// Reads an emoji stored in the database.
+    pub(crate) fn from_key(key: String) -> Self {
+        match key.parse::<u64>() {
+            Ok(id) => Emoji::Custom(id.into()),
+            Err(_) => Emoji::Unicode(key),
+        }
+    }
+}
+
+/// Everyone who reacted to a message with one emoji.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reaction {
+    pub emoji: Emoji,
+    /// How many users reacted with the emoji.
+    pub count: u64,
+    /// Whether the user who fetched the message reacted with the emoji.
+    pub me: bool,
+}
+
+/// An interface for interacting with the `message_reactions` table of the
+/// database.
+pub struct ReactionTable<'a> {
+    pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
+}
+
+impl<'a> ReactionTable<'a> {
+    /// Reacts to a message. Returns `false` if the user had already reacted
+    /// with the emoji.
+    ///
+    /// A custom emoji must belong to the guild the message was sent in.
+    pub async fn add(
+        &self,
+        message: Snowflake,
+        user: Snowflake,
+        emoji: &Emoji,
+    ) -> Result<bool, AddError> {
+        if let Emoji::Custom(id) = emoji {
+            let in_guild = sqlx::query_scalar!(
+                r#"SELECT EXISTS(
+                    SELECT 1 FROM guild_emojis e
+                    INNER JOIN channels c ON c.guild_id = e.guild_id
+                    INNER JOIN messages m ON m.channel_id = c.id
+                    WHERE m.id = $1 AND e.id = $2
+                ) AS "exists!""#,
+                message.into_number(),
+                id.into_number()
+            )
+            .fetch_one(self.conn)
+            .await?;
+            if !in_guild {
+                return Err(AddError::EmojiNotFound);
+            }
+        }
+
+        let success = sqlx::query!(
+            "INSERT INTO message_reactions (message_id, user_id, emoji, created_at) VALUES ($1, $2, $3, $4)
+            ON CONFLICT DO NOTHING",
+            message.into_number(),
+            user.into_number(),
+            emoji.to_key(),
+            time::now()
+        )
+        .execute(self.conn)
+        .await?;
+
+        Ok(success.rows_affected() == 1)
+    }
+
+    /// Removes a user's reaction from a message.
+    pub async fn remove(
+        &self,
+        message: Snowflake,
+        user: Snowflake,
+        emoji: &Emoji,
+    ) -> Result<(), RemoveError> {
+        let success = sqlx::query!(
+            "DELETE FROM message_reactions WHERE message_id = $1 AND user_id = $2 AND emoji = $3",
+            message.into_number(),
+            user.into_number(),
+            emoji.to_key()
+        )
+        .execute(self.conn)
+        .await?;
+
+        if success.rows_affected() == 0 {
+            return Err(RemoveError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// Gets up to `limit` of the users who reacted to a message with an
+    /// emoji, in order of their IDs, starting after the user `after`.
+    pub async fn get_users(
+        &self,
+        message: Snowflake,
+        emoji: &Emoji,
+        after: Option<Snowflake>,
+        limit: i64,
+    ) -> Result<Vec<user::User>, sqlx::Error> {
+        let users = sqlx::query!(
+            "SELECT u.id, u.username, u.discrim, u.profile_img_id, u.accent_color, u.pronouns, u.bio
+            FROM message_reactions r INNER JOIN users u ON u.id = r.user_id
+            WHERE r.message_id = $1 AND r.emoji = $2 AND ($3::bigint IS NULL OR r.user_id > $3)
+            ORDER BY r.user_id
+            LIMIT $4",
+            message.into_number(),
+            emoji.to_key(),
+            after.map(|id| id.into_number()),
+            limit
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        Ok(users
+            .into_iter()
+            .map(|user| user::User {
+                id: user.id.into(),
+                username: user.username,
+                discrim: user.discrim,
+                profile_img_id: user.profile_img_id.map(Into::into),
+                accent_color: user.accent_color,
+                pronouns: user.pronouns,
+                bio: user.bio,
+            })
+            .collect())
+    }
+
+    /// Counts the reactions on each of `messages`, from the point of view of
+    /// `user`. Each message's reactions are in the order they were first
+    /// added.
+    pub(crate) async fn get_counts(
+        &self,
+        messages: &[i64],
+        user: Snowflake,
+    ) -> Result<HashMap<i64, Vec<Reaction>>, sqlx::Error> {
+        let counts = sqlx::query!(
+            r#"SELECT message_id, emoji, COUNT(*) AS "count!", BOOL_OR(user_id = $2) AS "me!"
+            FROM message_reactions
+            WHERE message_id = ANY($1)
+            GROUP BY message_id, emoji
+            ORDER BY MIN(created_at), emoji"#,
+            messages,
+            user.into_number()
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        let mut reactions: HashMap<i64, Vec<Reaction>> = HashMap::new();
+        for count in counts {
+            reactions
+                .entry(count.message_id)
+                .or_default()
+                .push(Reaction {
+                    emoji: Emoji::from_key(count.emoji),
+                    count: count.count.try_into().unwrap_or_default(),
+                    me: count.me,
+                });
+        }
+        Ok(reactions)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AddError {
+    #[error("The custom emoji does not exist in the message's guild")]
+    EmojiNotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RemoveError {
+    #[error("The user has not reacted to the message with the emoji")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
@@ -0,0 +1,363 @@
+//! Friends, friend requests and blocked users.
+//!
+//! Each user has their own row for how they relate to another user, so a
+//! friend request is an outgoing row for the sender and an incoming row for
+//! the recipient. Blocking someone removes any other relationship between the
+//! two users, and only the user who blocked has a row for it.
+
+use crate::prelude::*;
+
+/// How a user relates to another user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelationshipKind {
+    /// The users are friends.
+    Friend,
+    /// The user sent a friend request to the other user.
+    Outgoing,
+    /// The other user sent a friend request to the user.
+    Incoming,
+    /// The user blocked the other user.
+    Blocked,
+}
+
+impl RelationshipKind {
+    fn into_number(self) -> i16 {
+        match self {
+            Self::Friend => 0,
+            Self::Outgoing => 1,
+            Self::Incoming => 2,
+            Self::Blocked => 3,
+        }
+    }
+
+    fn from_number(kind: i16) -> Self {
+        match kind {
+            0 => Self::Friend,
+            1 => Self::Outgoing,
+            2 => Self::Incoming,
+            _ => Self::Blocked,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Relationship {
+    /// The other user.
+    pub user: user::User,
+    pub kind: RelationshipKind,
+    /// When the relationship became its current kind.
+    pub since: u64,
+}
+
+/// An interface for interacting with the `relationships` table of the
+/// database.
+pub struct RelationshipTable<'a> {
+    pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
+}
+
+impl<'a> RelationshipTable<'a> {
+    /// Gets every relationship the user has with other users.
+    pub async fn get_all(&self, user: Snowflake) -> Result<Vec<Relationship>, sqlx::Error> {
+        self.get_many(user, None).await
+    }
+
+    /// Gets how the user relates to the target, if at all.
+    pub async fn get(
+        &self,
+        user: Snowflake,
+        target: Snowflake,
+    ) -> Result<Option<Relationship>, sqlx::Error> {
+        Ok(self.get_many(user, Some(target)).await?.pop())
+    }
+
+    async fn get_many(
+        &self,
+        user: Snowflake,
+        target: Option<Snowflake>,
+    ) -> Result<Vec<Relationship>, sqlx::Error> {
+        let relationships = sqlx::query!(
+            "SELECT r.kind, r.created_at, u.id, u.username, u.discrim, u.profile_img_id, u.accent_color, u.pronouns, u.bio
+            FROM relationships r INNER JOIN users u ON u.id = r.target_id
+            WHERE r.user_id = $1 AND ($2::bigint IS NULL OR r.target_id = $2)
+            ORDER BY r.created_at DESC",
+            user.into_number(),
+            target.map(|id| id.into_number())
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        #[allow(clippy::cast_sign_loss)]
+        Ok(relationships
+            .into_iter()
+            .map(|relationship| Relationship {
+                user: user::User {
+                    id: relationship.id.into(),
+                    username: relationship.username,
+                    discrim: relationship.discrim,
+                    profile_img_id: relationship.profile_img_id.map(Into::into),
+                    accent_color: relationship.accent_color,
+                    pronouns: relationship.pronouns,
+                    bio: relationship.bio,
+                },
+                kind: RelationshipKind::from_number(relationship.kind),
+                since: relationship.created_at as u64,
+            })
+            .collect())
+    }
+
+    /// Whether the user has blocked, or been blocked by, any of the others.
+    pub async fn any_blocked(
+        &self,
+        user: Snowflake,
+        others: &[Snowflake],
+    ) -> Result<bool, sqlx::Error> {
+        let others: Vec<i64> = others.iter().map(Snowflake::into_number).collect();
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM relationships WHERE kind = $3 AND (
+                    (user_id = $1 AND target_id = ANY($2)) OR (target_id = $1 AND user_id = ANY($2))
+                )
+            ) AS "exists!""#,
+            user.into_number(),
+            &others,
+            RelationshipKind::Blocked.into_number()
+        )
+        .fetch_one(self.conn)
+        .await
+    }
+
+    /// Whether any of the users has blocked another of them.
+    pub async fn any_blocked_between(&self, users: &[Snowflake]) -> Result<bool, sqlx::Error> {
+        let users: Vec<i64> = users.iter().map(Snowflake::into_number).collect();
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM relationships
+                WHERE kind = $2 AND user_id = ANY($1) AND target_id = ANY($1)
+            ) AS "exists!""#,
+            &users,
+            RelationshipKind::Blocked.into_number()
+        )
+        .fetch_one(self.conn)
+        .await
+    }
+
+    /// Locks and returns how the user relates to the target, and how the
+    /// target relates to the user.
+    async fn lock_pair(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        user: Snowflake,
+        target: Snowflake,
+    ) -> Result<(Option<RelationshipKind>, Option<RelationshipKind>), sqlx::Error> {
+        let rows = sqlx::query!(
+            "SELECT user_id, kind FROM relationships
+            WHERE (user_id = $1 AND target_id = $2) OR (user_id = $2 AND target_id = $1)
+            FOR UPDATE",
+            user.into_number(),
+            target.into_number()
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let kind_of = |id: Snowflake| {
+            rows.iter()
+                .find(|row| row.user_id == id.into_number())
+                .map(|row| RelationshipKind::from_number(row.kind))
+        };
+        Ok((kind_of(user), kind_of(target)))
+    }
+
+    /// Sets how the user relates to the target.
+    async fn set(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        user: Snowflake,
+        target: Snowflake,
+        kind: RelationshipKind,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO relationships (user_id, target_id, kind, created_at) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, target_id) DO UPDATE SET kind = $3, created_at = $4",
+            user.into_number(),
+            target.into_number(),
+            kind.into_number(),
+            time::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Removes how the user relates to the target.
+    async fn unset(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        user: Snowflake,
+        target: Snowflake,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM relationships WHERE user_id = $1 AND target_id = $2",
+            user.into_number(),
+            target.into_number()
+        )
+        .execute(&mut *tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Sends a friend request from the user to the target. If the target had
+    /// already sent one to the user, they become friends instead.
+    ///
+    /// Returns how the user relates to the target afterwards.
+    pub async fn request(
+        &self,
+        user: Snowflake,
+        target: Snowflake,
+    ) -> Result<RelationshipKind, RequestError> {
+        if user == target {
+            return Err(RequestError::CannotFriendSelf);
+        }
+
+        let mut tx = self.conn.begin().await?;
+        let kind = match Self::lock_pair(&mut tx, user, target).await? {
+            (Some(RelationshipKind::Blocked), _) | (_, Some(RelationshipKind::Blocked)) => {
+                return Err(RequestError::Blocked)
+            }
+            (Some(RelationshipKind::Friend), _) => return Err(RequestError::AlreadyFriends),
+            (Some(RelationshipKind::Outgoing), _) => return Err(RequestError::AlreadyRequested),
+            (Some(RelationshipKind::Incoming), _) => {
+                Self::set(&mut tx, user, target, RelationshipKind::Friend).await?;
+                Self::set(&mut tx, target, user, RelationshipKind::Friend).await?;
+                RelationshipKind::Friend
+            }
+            (None, _) => {
+                Self::set(&mut tx, user, target, RelationshipKind::Outgoing).await?;
+                Self::set(&mut tx, target, user, RelationshipKind::Incoming).await?;
+                RelationshipKind::Outgoing
+            }
+        };
+        tx.commit().await?;
+
+        Ok(kind)
+    }
+
+    /// Accepts the friend request the target sent to the user.
+    pub async fn accept(&self, user: Snowflake, target: Snowflake) -> Result<(), AcceptError> {
+        let mut tx = self.conn.begin().await?;
+        if Self::lock_pair(&mut tx, user, target).await?.0 != Some(RelationshipKind::Incoming) {
+            return Err(AcceptError::NoRequest);
+        }
+
+        Self::set(&mut tx, user, target, RelationshipKind::Friend).await?;
+        Self::set(&mut tx, target, user, RelationshipKind::Friend).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Declines the friend request the target sent to the user.
+    pub async fn decline(&self, user: Snowflake, target: Snowflake) -> Result<(), DeclineError> {
+        let mut tx = self.conn.begin().await?;
+        if Self::lock_pair(&mut tx, user, target).await?.0 != Some(RelationshipKind::Incoming) {
+            return Err(DeclineError::NoRequest);
+        }
+
+        Self::unset(&mut tx, user, target).await?;
+        Self::unset(&mut tx, target, user).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Removes a friend, cancels or declines a friend request, or unblocks
+    /// the target, depending on how the user relates to them.
+    pub async fn remove(&self, user: Snowflake, target: Snowflake) -> Result<(), RemoveError> {
+        let mut tx = self.conn.begin().await?;
+        match Self::lock_pair(&mut tx, user, target).await? {
+            (None, _) => return Err(RemoveError::NotFound),
+            (Some(RelationshipKind::Blocked), _) => Self::unset(&mut tx, user, target).await?,
+            (Some(_), _) => {
+                Self::unset(&mut tx, user, target).await?;
+                Self::unset(&mut tx, target, user).await?;
+            }
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Blocks the target, removing any friendship or friend request between
+    /// the two users.
+    pub async fn block(&self, user: Snowflake, target: Snowflake) -> Result<(), BlockError> {
+        if user == target {
+            return Err(BlockError::CannotBlockSelf);
+        }
+
+        let target_exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1) AS "exists!""#,
+            target.into_number()
+        )
+        .fetch_one(self.conn)
+        .await?;
+        if !target_exists {
+            return Err(BlockError::UserNotFound);
+        }
+
+        let mut tx = self.conn.begin().await?;
+        let (_, theirs) = Self::lock_pair(&mut tx, user, target).await?;
+        // The target's own block of the user stays
+        if theirs.map_or(false, |kind| kind != RelationshipKind::Blocked) {
+            Self::unset(&mut tx, target, user).await?;
+        }
+        Self::set(&mut tx, user, target, RelationshipKind::Blocked).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RequestError {
+    #[error("Users cannot send a friend request to themselves")]
+    CannotFriendSelf,
+    #[error("One of the users has blocked the other")]
+    Blocked,
+    #[error("The users are already friends")]
+    AlreadyFriends,
+    #[error("The user already sent a friend request to the target")]
+    AlreadyRequested,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AcceptError {
+    #[error("The target has not sent a friend request to the user")]
+    NoRequest,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeclineError {
+    #[error("The target has not sent a friend request to the user")]
+    NoRequest,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RemoveError {
+    #[error("The user has no relationship with the target")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BlockError {
+    #[error("Users cannot block themselves")]
+    CannotBlockSelf,
+    #[error("The target does not exist")]
+    UserNotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
@@ -0,0 +1,461 @@
+//! Roles and the permissions they grant.
+//!
+//! Every guild has a default role that shares its ID with the guild. Every
+//! member implicitly holds it, and it always sits at the bottom of the
+//! hierarchy with an `order` of 0. Other roles rank above each other by their
+//! `order`, with higher orders ranking above lower ones. A member can only
+//! manage roles ranked below their own highest role.
+
+use crate::prelude::*;
+
+bitflags::bitflags! {
+    /// A set of permissions, stored in the `permission_mask` of a role.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Permissions: i64 {
+        /// Can see channels and read the messages in them.
+        const VIEW_CHANNELS = 1 << 0;
+        /// Can send messages in channels.
+        const SEND_MESSAGES = 1 << 1;
+        /// Can delete other members' messages.
+        const MANAGE_MESSAGES = 1 << 2;
+        /// Can create, edit and delete channels.
+        const MANAGE_CHANNELS = 1 << 3;
+        /// Can create, edit, delete and assign roles below their highest role.
+        const MANAGE_ROLES = 1 << 4;
+        /// Can edit the guild itself.
+        const MANAGE_GUILD = 1 << 5;
+        /// Has every permission.
+        const ADMINISTRATOR = 1 << 6;
+        /// Can create invites to the guild.
+        const CREATE_INVITES = 1 << 7;
+        /// Can remove members ranked below their highest role from the guild.
+        const KICK_MEMBERS = 1 << 8;
+        /// Can ban users from the guild, and see and lift its bans. Members
+        /// can only be banned by those ranked above them.
+        const BAN_MEMBERS = 1 << 9;
+        /// Can change the nicknames of members ranked below their highest
+        /// role.
+        const MANAGE_NICKNAMES = 1 << 10;
+    }
+}
+
+impl Permissions {
+    /// The permissions the default role of a new guild grants.
+    pub const DEFAULT: Self = Self::VIEW_CHANNELS
+        .union(Self::SEND_MESSAGES)
+        .union(Self::CREATE_INVITES);
+}
+
+impl Serialize for Permissions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.bits())
+    }
+}
+impl<'de> Deserialize<'de> for Permissions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Unknown bits are dropped so that clients built against a newer
+        // permission set don't break older servers.
+        i64::deserialize(deserializer).map(Self::from_bits_truncate)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Role {
+    pub id: Snowflake,
+    pub guild_id: Snowflake,
+    pub name: String,
+    /// The hex color code of the role, without the leading `#`.
+    pub color: String,
+    pub permissions: Permissions,
+    /// The position of the role in the hierarchy. Higher ranks above lower.
+    pub order: i32,
+}
+
+/// The permissions a member holds in a guild, computed from their roles.
+#[derive(Debug, Clone, Copy)]
+pub struct MemberPermissions {
+    pub permissions: Permissions,
+    /// The `order` of the member's highest role. The owner of the guild
+    /// outranks every role.
+    pub rank: i32,
+}
+
+impl MemberPermissions {
+    /// Computes a member's permissions from the masks of their roles.
+    pub(crate) fn compute(is_owner: bool, role_mask: i64, rank: i32) -> Self {
+        if is_owner {
+            return Self {
+                permissions: Permissions::all(),
+                rank: i32::MAX,
+            };
+        }
+
+        let mut permissions = Permissions::from_bits_truncate(role_mask);
+        if permissions.contains(Permissions::ADMINISTRATOR) {
+            permissions = Permissions::all();
+        }
+
+        Self { permissions, rank }
+    }
+
+    /// Whether the member holds all of the given permissions.
+    pub fn contains(&self, permissions: Permissions) -> bool {
+        self.permissions.contains(permissions)
+    }
+
+    /// Whether the member ranks above a role and so can manage it.
+    pub fn outranks(&self, order: i32) -> bool {
+        self.rank > order
+    }
+}
+
+/// An interface for interacting with the `roles` table of the database.
+pub struct RoleTable<'a> {
+    pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
+}
+
+impl<'a> RoleTable<'a> {
+    /// Get every role in a guild, from highest to lowest.
+    pub async fn get_all(&self, guild: Snowflake) -> Result<Vec<Role>, sqlx::Error> {
+        let roles = sqlx::query_as!(
+            RoleRow,
+            r#"SELECT id, guild_id, name, color, permission_mask, "order" FROM roles WHERE guild_id = $1 ORDER BY "order" DESC, id"#,
+            guild.into_number()
+        )
+        .fetch_all(self.conn)
+        .await?;
+
+        Ok(roles.into_iter().map(Into::into).collect())
+    }
+
+    /// Get a role in a guild.
+    pub async fn get(
+        &self,
+        guild: Snowflake,
+        role: Snowflake,
+    ) -> Result<Option<Role>, sqlx::Error> {
+        let role = sqlx::query_as!(
+            RoleRow,
+            r#"SELECT id, guild_id, name, color, permission_mask, "order" FROM roles WHERE id = $1 AND guild_id = $2"#,
+            role.into_number(),
+            guild.into_number()
+        )
+        .fetch_optional(self.conn)
+        .await?;
+
+        Ok(role.map(Into::into))
+    }
+
+    /// Create a new role, placed directly above the default role.
+    pub async fn create(
+        &self,
+        id: Snowflake,
+        guild: Snowflake,
+        name: &str,
+        color: &str,
+        permissions: Permissions,
+    ) -> Result<Role, CreateError> {
+        let mut tx = self.conn.begin().await?;
+
+        // Make room above the default role
+        sqlx::query!(
+            r#"UPDATE roles SET "order" = "order" + 1 WHERE guild_id = $1 AND id != $1"#,
+            guild.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let success = sqlx::query!(
+            r#"INSERT INTO roles (id, guild_id, name, color, permission_mask, "order") VALUES ($1, $2, $3, $4, $5, 1)"#,
+            id.into_number(),
+            guild.into_number(),
+            name,
+            color,
+            permissions.bits()
+        )
+        .execute(&mut tx)
+        .await?;
+
+        if success.rows_affected() != 1 {
+            return Err(CreateError::NotInserted);
+        }
+        tx.commit().await?;
+
+        Ok(Role {
+            id,
+            guild_id: guild,
+            name: name.to_string(),
+            color: color.to_string(),
+            permissions,
+            order: 1,
+        })
+    }
+
+    /// Edit a role. Fields that are `None` are left unchanged.
+    pub async fn edit(
+        &self,
+        guild: Snowflake,
+        role: Snowflake,
+        name: Option<&str>,
+        color: Option<&str>,
+        permissions: Option<Permissions>,
+    ) -> Result<Role, EditError> {
+        let role = sqlx::query_as!(
+            RoleRow,
+            r#"
+            UPDATE roles SET
+                name = COALESCE($3, name),
+                color = COALESCE($4, color),
+                permission_mask = COALESCE($5, permission_mask)
+            WHERE id = $1 AND guild_id = $2
+            RETURNING id, guild_id, name, color, permission_mask, "order"
+            "#,
+            role.into_number(),
+            guild.into_number(),
+            name,
+            color,
+            permissions.map(|p| p.bits())
+        )
+        .fetch_optional(self.conn)
+        .await?;
+
+        role.map(Into::into).ok_or(EditError::NotFound)
+    }
+
+    /// Delete a role, removing it from every member that has it and every
+    /// channel it has an overwrite on.
+    pub async fn delete(&self, guild: Snowflake, role: Snowflake) -> Result<(), DeleteError> {
+        if guild == role {
+            return Err(DeleteError::DefaultRole);
+        }
+
+        let mut tx = self.conn.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM guild_member_roles WHERE role_id = $1 AND role_id IN (SELECT id FROM roles WHERE guild_id = $2)",
+            role.into_number(),
+            guild.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM channel_overwrites WHERE target_id = $1 AND kind = 0 AND channel_id IN (SELECT id FROM channels WHERE guild_id = $2)",
+            role.into_number(),
+            guild.into_number()
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let order = sqlx::query_scalar!(
+            r#"DELETE FROM roles WHERE id = $1 AND guild_id = $2 RETURNING "order""#,
+            role.into_number(),
+            guild.into_number()
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        let Some(order) = order else {
+            return Err(DeleteError::NotFound);
+        };
+
+        // Close the gap left in the hierarchy
+        sqlx::query!(
+            r#"UPDATE roles SET "order" = "order" - 1 WHERE guild_id = $1 AND "order" > $2"#,
+            guild.into_number(),
+            order
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Rewrite the hierarchy of a guild's roles.
+    ///
+    /// `roles` must contain every role in the guild except the default role,
+    /// ordered from highest to lowest.
+    pub async fn reorder(&self, guild: Snowflake, roles: &[Snowflake]) -> Result<(), ReorderError> {
+        let mut tx = self.conn.begin().await?;
+
+        let existing = sqlx::query_scalar!(
+            "SELECT id FROM roles WHERE guild_id = $1 AND id != $1 FOR UPDATE",
+            guild.into_number()
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        let mut expected: Vec<i64> = roles.iter().map(Snowflake::into_number).collect();
+        let mut actual = existing;
+        expected.sort_unstable();
+        actual.sort_unstable();
+        if expected != actual || expected.windows(2).any(|w| w[0] == w[1]) {
+            return Err(ReorderError::Mismatch);
+        }
+
+        // The first role listed gets the highest order, and the last is
+        // placed directly above the default role.
+        let ids: Vec<i64> = roles.iter().map(Snowflake::into_number).collect();
+        sqlx::query!(
+            r#"
+            UPDATE roles SET "order" = (cardinality($2::bigint[]) - new.idx + 1)::integer
+            FROM UNNEST($2::bigint[]) WITH ORDINALITY AS new(id, idx)
+            WHERE roles.id = new.id AND roles.guild_id = $1
+            "#,
+            guild.into_number(),
+            &ids
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Give a member of a guild a role. Does nothing if they already have it.
+    pub async fn assign(
+        &self,
+        id: Snowflake,
+        guild: Snowflake,
+        role: Snowflake,
+        user: Snowflake,
+    ) -> Result<(), AssignError> {
+        if guild == role {
+            return Err(AssignError::RoleNotFound);
+        }
+
+        let role_exists = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM roles WHERE id = $1 AND guild_id = $2)",
+            role.into_number(),
+            guild.into_number()
+        )
+        .fetch_one(self.conn)
+        .await?;
+        if role_exists != Some(true) {
+            return Err(AssignError::RoleNotFound);
+        }
+
+        let member = sqlx::query_scalar!(
+            "SELECT id FROM guild_members WHERE guild_id = $1 AND user_id = $2",
+            guild.into_number(),
+            user.into_number()
+        )
+        .fetch_optional(self.conn)
+        .await?;
+        let Some(member) = member else {
+            return Err(AssignError::MemberNotFound);
+        };
+
+        sqlx::query!(
+            "INSERT INTO guild_member_roles (id, guild_member_id, role_id) VALUES ($1, $2, $3) ON CONFLICT (guild_member_id, role_id) DO NOTHING",
+            id.into_number(),
+            member,
+            role.into_number()
+        )
+        .execute(self.conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Take a role away from a member of a guild.
+    pub async fn unassign(
+        &self,
+        guild: Snowflake,
+        role: Snowflake,
+        user: Snowflake,
+    ) -> Result<(), UnassignError> {
+        let success = sqlx::query!(
+            "DELETE FROM guild_member_roles WHERE role_id = $1 AND guild_member_id = (SELECT id FROM guild_members WHERE guild_id = $2 AND user_id = $3)",
+            role.into_number(),
+            guild.into_number(),
+            user.into_number()
+        )
+        .execute(self.conn)
+        .await?;
+
+        if success.rows_affected() == 0 {
+            Err(UnassignError::NotAssigned)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A role as selected from the database.
+struct RoleRow {
+    id: i64,
+    guild_id: i64,
+    name: String,
+    color: String,
+    permission_mask: i64,
+    order: i32,
+}
+
+impl From<RoleRow> for Role {
+    fn from(role: RoleRow) -> Self {
+        Role {
+            id: role.id.into(),
+            guild_id: role.guild_id.into(),
+            name: role.name,
+            color: role.color,
+            permissions: Permissions::from_bits_truncate(role.permission_mask),
+            order: role.order,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateError {
+    #[error("The entry was not inserted into the database")]
+    NotInserted,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EditError {
+    #[error("The role does not exist in this guild")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeleteError {
+    #[error("The default role of a guild cannot be deleted")]
+    DefaultRole,
+    #[error("The role does not exist in this guild")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReorderError {
+    #[error("The roles given do not match the roles in the guild")]
+    Mismatch,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AssignError {
+    #[error("The role does not exist in this guild or is the default role")]
+    RoleNotFound,
+    #[error("The user is not a member of this guild")]
+    MemberNotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UnassignError {
+    #[error("The member does not have this role")]
+    NotAssigned,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
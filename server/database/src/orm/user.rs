@@ -1,6 +1,28 @@
 use crate::prelude::*;
+use jsonwebtoken::{DecodingKey, EncodingKey, Header};
 use std::collections::HashSet;
 
+/// The audience of email verification JWTs, so that they can't be mistaken
+/// for any other JWT signed with the same secret.
+const VERIFY_EMAIL_AUDIENCE: &str = "verify-email";
+/// How long an email verification link works for, in seconds. 1 day.
+const VERIFY_EMAIL_LIFETIME: u64 = 24 * 60 * 60;
+/// The unique index that stops two users having the same tag.
+const TAG_INDEX: &str = "users_username_discrim_key";
+/// How many discriminators are tried when someone else takes the one picked
+/// before it is saved.
+const DISCRIM_ATTEMPTS: usize = 5;
+
+/// Whether a query failed because another user already has the tag.
+fn is_tag_taken(e: &sqlx::Error) -> bool {
+    matches!(
+        e,
+        sqlx::Error::Database(e)
+            if e.code().as_deref() == Some(super::UNIQUE_VIOLATION)
+                && e.constraint() == Some(TAG_INDEX)
+    )
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: Snowflake,
@@ -14,6 +36,58 @@ pub struct User {
     pub bio: Option<String>,
 }
 
+/// The account of the logged in user, which includes details only they can
+/// see.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    #[serde(flatten)]
+    pub user: User,
+    pub email: String,
+    /// Whether the user has clicked the link sent to their email address.
+    pub email_verified: bool,
+    /// Whether logging in needs a code from an authenticator app.
+    pub mfa_enabled: bool,
+}
+
+/// The claims of a signed email verification link.
+///
+/// The email address is included so that the link stops working if the
+/// user's email address changes.
+#[derive(Debug, Serialize, Deserialize)]
+struct VerifyEmailClaims {
+    sub: Snowflake,
+    email: String,
+    aud: String,
+    exp: u64,
+}
+
+/// Creates the signed token for a link that verifies the user's email
+/// address.
+///
+/// # Errors
+///
+/// Returns an error if the JWT could not be encoded.
+pub fn email_verification_token(
+    id: Snowflake,
+    email: &str,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let claims = VerifyEmailClaims {
+        sub: id,
+        email: email.to_string(),
+        aud: VERIFY_EMAIL_AUDIENCE.to_string(),
+        exp: now + VERIFY_EMAIL_LIFETIME,
+    };
+
+    jsonwebtoken::encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(&access_token::secret()),
+    )
+}
+
 /// An interface for interacting with the `users` table of the database.
 pub struct UserTable<'a> {
     pub(crate) conn: &'a sqlx::Pool<sqlx::Postgres>,
@@ -60,6 +134,40 @@ impl<'a> UserTable<'a> {
             _ => {}
         }
 
+        let phc = password.into().generate();
+
+        for _ in 0..DISCRIM_ATTEMPTS {
+            let Some(discrim) = self.pick_discrim(username).await? else {
+                return Err(NewUserError::AllDiscriminatorsUsed);
+            };
+
+            let result = sqlx::query!(
+                "INSERT INTO users (id, username, discrim, phc, email) VALUES ($1, $2, $3, $4, $5)",
+                id.into_number(),
+                username,
+                discrim,
+                &phc,
+                email
+            )
+            .execute(self.conn)
+            .await;
+
+            return match result {
+                Ok(success) if success.rows_affected() == 1 => {
+                    self.get(id).await?.ok_or(NewUserError::UserNotFound)
+                }
+                Ok(_) => Err(NewUserError::NotInserted),
+                // Someone else took the tag after it was picked
+                Err(e) if is_tag_taken(&e) => continue,
+                Err(e) => Err(e.into()),
+            };
+        }
+        Err(NewUserError::AllDiscriminatorsUsed)
+    }
+
+    /// Picks a random discriminator not already used by someone with the
+    /// username, or `None` if they are all taken.
+    async fn pick_discrim(&self, username: &str) -> Result<Option<i16>, sqlx::Error> {
         // Get every user with the same username and their discriminator
         // so we can generate a new one not already used
         let existing_discrims =
@@ -72,36 +180,205 @@ impl<'a> UserTable<'a> {
         for discrim in existing_discrims {
             discrims.remove(&discrim.discrim);
         }
-        if discrims.is_empty() {
-            return Err(NewUserError::AllDiscriminatorsUsed);
-        }
         // Pick a random one from whats available
-        let discrim = discrims
-            .iter()
-            .choose(&mut rand::thread_rng())
-            .unwrap_or(&0);
+        Ok(discrims.into_iter().choose(&mut rand::thread_rng()))
+    }
 
-        let phc = password.into().generate();
+    /// Gets a user along with their private account details.
+    pub async fn get_account(&self, id: Snowflake) -> Result<Option<Account>, sqlx::Error> {
+        let account = sqlx::query!(
+            r#"SELECT email, email_verified_at,
+            EXISTS(SELECT 1 FROM user_totp WHERE user_id = id AND enabled_at IS NOT NULL) AS "mfa_enabled!"
+            FROM users WHERE id = $1"#,
+            id.into_number()
+        )
+        .fetch_optional(self.conn)
+        .await?;
+        let Some(account) = account else {
+            return Ok(None);
+        };
+
+        Ok(self.get(id).await?.map(|user| Account {
+            user,
+            email: account.email,
+            email_verified: account.email_verified_at.is_some(),
+            mfa_enabled: account.mfa_enabled,
+        }))
+    }
+
+    /// Whether the user has verified their email address.
+    pub async fn is_email_verified(&self, id: Snowflake) -> Result<bool, sqlx::Error> {
+        let verified = sqlx::query_scalar!(
+            r#"SELECT email_verified_at IS NOT NULL AS "verified!" FROM users WHERE id = $1"#,
+            id.into_number()
+        )
+        .fetch_optional(self.conn)
+        .await?;
+
+        Ok(verified.unwrap_or(false))
+    }
+
+    /// Marks a user's email address as verified using the token from
+    /// [`email_verification_token`]. Returns the user's ID.
+    pub async fn verify_email(&self, token: &str) -> Result<Snowflake, VerifyEmailError> {
+        let mut validation = jsonwebtoken::Validation::default();
+        validation.set_audience(&[VERIFY_EMAIL_AUDIENCE]);
+        let claims = jsonwebtoken::decode::<VerifyEmailClaims>(
+            token,
+            &DecodingKey::from_secret(&access_token::secret()),
+            &validation,
+        )
+        .map_err(|_| VerifyEmailError::InvalidToken)?
+        .claims;
+
+        let verified_at = sqlx::query_scalar!(
+            "SELECT email_verified_at FROM users WHERE id = $1 AND email = $2",
+            claims.sub.into_number(),
+            claims.email
+        )
+        .fetch_optional(self.conn)
+        .await?
+        .ok_or(VerifyEmailError::InvalidToken)?;
+        if verified_at.is_some() {
+            return Err(VerifyEmailError::AlreadyVerified);
+        }
+
+        sqlx::query!(
+            "UPDATE users SET email_verified_at = $2 WHERE id = $1 AND email_verified_at IS NULL",
+            claims.sub.into_number(),
+            time::now()
+        )
+        .execute(self.conn)
+        .await?;
+
+        Ok(claims.sub)
+    }
+
+    /// Edits a user's profile. Fields that are `None` are left unchanged, and
+    /// fields that are empty strings are removed.
+    ///
+    /// Changing the username gives the user a new discriminator, as their
+    /// current one may already be used by someone with the new username.
+    pub async fn edit(
+        &self,
+        id: Snowflake,
+        username: Option<&str>,
+        accent_color: Option<&str>,
+        pronouns: Option<&str>,
+        bio: Option<&str>,
+    ) -> Result<User, EditError> {
+        let current = self.get(id).await?.ok_or(EditError::NotFound)?;
+
+        for _ in 0..DISCRIM_ATTEMPTS {
+            let (username, discrim) = match username {
+                Some(username) if username != current.username => {
+                    let Some(discrim) = self.pick_discrim(username).await? else {
+                        return Err(EditError::AllDiscriminatorsUsed);
+                    };
+                    (username, discrim)
+                }
+                _ => (current.username.as_str(), current.discrim),
+            };
 
+            let result = sqlx::query!(
+                r#"
+                UPDATE users SET
+                    username = $2,
+                    discrim = $3,
+                    accent_color = CASE WHEN $4::text IS NULL THEN accent_color ELSE NULLIF($4, '') END,
+                    pronouns = CASE WHEN $5::text IS NULL THEN pronouns ELSE NULLIF($5, '') END,
+                    bio = CASE WHEN $6::text IS NULL THEN bio ELSE NULLIF($6, '') END
+                WHERE id = $1
+                "#,
+                id.into_number(),
+                username,
+                discrim,
+                accent_color,
+                pronouns,
+                bio
+            )
+            .execute(self.conn)
+            .await;
+
+            return match result {
+                Ok(success) if success.rows_affected() == 1 => {
+                    self.get(id).await?.ok_or(EditError::NotFound)
+                }
+                Ok(_) => Err(EditError::NotFound),
+                // Someone else took the tag after it was picked
+                Err(e) if is_tag_taken(&e) => continue,
+                Err(e) => Err(e.into()),
+            };
+        }
+        Err(EditError::AllDiscriminatorsUsed)
+    }
+
+    /// Gets the user with the email address.
+    pub async fn get_by_email(&self, email: &str) -> Result<Option<User>, sqlx::Error> {
+        let id = sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", email)
+            .fetch_optional(self.conn)
+            .await?;
+
+        match id {
+            Some(id) => self.get(id.into()).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Gets the user with the username and discriminator, i.e. `username#0001`.
+    pub async fn get_by_tag(
+        &self,
+        username: &str,
+        discrim: i16,
+    ) -> Result<Option<User>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            "SELECT id FROM users WHERE username = $1 AND discrim = $2",
+            username,
+            discrim
+        )
+        .fetch_optional(self.conn)
+        .await?;
+
+        match id {
+            Some(id) => self.get(id.into()).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Changes a user's password, given they know their current one.
+    pub async fn change_password<'pw, P: Into<password::Password<'pw>>>(
+        &self,
+        id: Snowflake,
+        current: P,
+        new: P,
+    ) -> Result<(), ChangePasswordError> {
+        let phc = sqlx::query_scalar!("SELECT phc FROM users WHERE id = $1", id.into_number())
+            .fetch_optional(self.conn)
+            .await?
+            .ok_or(ChangePasswordError::NotFound)?;
+
+        if !current.into().verify(&phc) {
+            return Err(ChangePasswordError::InvalidCredentials);
+        }
+
+        let phc = new.into().generate();
         let success = sqlx::query!(
-            "INSERT INTO users (id, username, discrim, phc, email) VALUES ($1, $2, $3, $4, $5)",
+            "UPDATE users SET phc = $2 WHERE id = $1",
             id.into_number(),
-            username,
-            discrim,
-            phc,
-            email
+            phc
         )
         .execute(self.conn)
         .await?;
 
-        if success.rows_affected() == 1 {
-            self.get(id).await?.ok_or(NewUserError::UserNotFound)
-        } else {
-            Err(NewUserError::NotInserted)
+        if success.rows_affected() != 1 {
+            return Err(ChangePasswordError::NotFound);
         }
+        Ok(())
     }
 
     /// Logs a user in with their email and password and returns their ID.
+    ///
+    /// If the password hash is outdated, it is replaced with a new one.
     pub async fn login<'pw, P: Into<password::Password<'pw>>>(
         &self,
         email: &str,
@@ -123,15 +400,27 @@ impl<'a> UserTable<'a> {
             .ok_or(LoginError::InvalidCredentials)?
         };
 
-        // TODO: Update password if changed (due to encryption method changing)
+        let password = password.into();
+        if !password.verify(&user.phc) {
+            return Err(LoginError::InvalidCredentials);
+        }
 
-        if password.into().verify(&user.phc) {
-            self.get(user.id.into())
-                .await?
-                .ok_or(LoginError::UserNotFound)
-        } else {
-            Err(LoginError::InvalidCredentials)
+        // Only now is the plaintext known, so upgrade outdated hashes
+        if password::Password::needs_rehash(&user.phc) {
+            debug!("Rehashing the password of {}", user.id);
+            sqlx::query!(
+                "UPDATE users SET phc = $3 WHERE id = $1 AND phc = $2",
+                user.id,
+                user.phc,
+                password.generate()
+            )
+            .execute(self.conn)
+            .await?;
         }
+
+        self.get(user.id.into())
+            .await?
+            .ok_or(LoginError::UserNotFound)
     }
 }
 
@@ -158,3 +447,33 @@ pub enum LoginError {
     #[error("An error occurred while querying the database")]
     DatabaseError(#[from] sqlx::Error),
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum EditError {
+    #[error("The user does not exist")]
+    NotFound,
+    #[error("All the username + discriminator combos are already taken")]
+    AllDiscriminatorsUsed,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChangePasswordError {
+    #[error("The current password was not correct")]
+    InvalidCredentials,
+    #[error("The user does not exist")]
+    NotFound,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum VerifyEmailError {
+    #[error("The verification token is invalid, has expired, or is for an old email address")]
+    InvalidToken,
+    #[error("The email address has already been verified")]
+    AlreadyVerified,
+    #[error("An error occurred while querying the database")]
+    DatabaseError(#[from] sqlx::Error),
+}
@@ -0,0 +1,241 @@
+#![allow(clippy::module_name_repetitions)]
+mod strength;
+
+use crate::prelude::*;
+use argon2::PasswordVerifier;
+use serde::Serialize;
+
+/// The lowest [`InvalidPassword::score`] a password can have.
+pub const MIN_SCORE: u8 = 3;
+
+/// Returns the current pepper secret and its key ID.
+///
+/// `PEPPER_ID` is up to 8 bytes and is stored in every hash, so that
+/// `PEPPER` can be changed without invalidating existing hashes. Hashes
+/// made before it was set have no key ID.
+fn current_pepper() -> (Vec<u8>, String) {
+    let id = std::env::var("PEPPER_ID").unwrap_or_default();
+    let secret = std::env::var("PEPPER").expect("PEPPER not set");
+    (decode_pepper(&secret), id)
+}
+
+/// Returns the pepper secret with the key ID a hash was made with.
+///
+/// Peppers that have been rotated out are read from `OLD_PEPPERS`, a comma
+/// separated list of `id:base64` pairs. An empty ID is the pepper from
+/// before `PEPPER_ID` was set.
+fn pepper(key_id: &[u8]) -> Option<Vec<u8>> {
+    let (secret, id) = current_pepper();
+    if id.as_bytes() == key_id {
+        return Some(secret);
+    }
+
+    std::env::var("OLD_PEPPERS")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|pair| pair.trim().split_once(':'))
+        .find(|(id, _)| id.as_bytes() == key_id)
+        .map(|(_, secret)| decode_pepper(secret))
+}
+
+fn decode_pepper(secret: &str) -> Vec<u8> {
+    use base64::Engine;
+
+    base64::engine::general_purpose::STANDARD
+        .decode(secret)
+        .expect("PEPPER is not valid base64")
+}
+
+/// Returns the argon2 parameters new hashes are made with.
+///
+/// `ARGON2_MEMORY` is the memory cost in KiB, `ARGON2_ITERATIONS` the
+/// number of passes and `ARGON2_PARALLELISM` the number of lanes.
+fn params() -> argon2::Params {
+    let cost = |name: &str, default: u32| {
+        std::env::var(name).map_or(default, |cost| {
+            cost.parse()
+                .unwrap_or_else(|_| panic!("{name} is not a number"))
+        })
+    };
+    let (_, id) = current_pepper();
+
+    argon2::ParamsBuilder::new()
+        .m_cost(cost("ARGON2_MEMORY", 15000))
+        .t_cost(cost("ARGON2_ITERATIONS", 2))
+        .p_cost(cost("ARGON2_PARALLELISM", 1))
+        .keyid(argon2::KeyId::new(id.as_bytes()).expect("PEPPER_ID is longer than 8 bytes"))
+        .build()
+        .expect("Invalid argon2 parameters")
+}
+
+/// A struct for password generation and validation.
+///
+/// A lot of the code in this struct comes from
+/// [this article](https://www.lpalmieri.com/posts/password-authentication-in-rust/#3-3-6-argon2)
+pub struct Password<'pw> {
+    password: &'pw str,
+}
+impl<'pw> Password<'pw> {
+    pub fn new(password: &'pw str) -> Self {
+        Self { password }
+    }
+
+    /// Validates that a password is strong enough to be used by the user
+    /// with the username and email address. Returns its score from 0 to 4.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`InvalidPassword`] if the password is not valid.
+    pub fn validate(&self, username: &str, email: &str) -> Result<u8, InvalidPassword> {
+        let invalid = |rule, score| Err(InvalidPassword { rule, score });
+
+        if self.password.len() > 64 {
+            // Not worth estimating the strength of
+            return invalid(PasswordError::TooLong, 0);
+        }
+
+        let email_name = email.split('@').next().unwrap_or_default();
+        let estimate = strength::estimate(self.password, &[username, email_name]);
+        let score = estimate.score();
+
+        if self.password.len() < 8 {
+            return invalid(PasswordError::TooShort, score);
+        }
+        if strength::is_common(self.password) {
+            return invalid(PasswordError::TooCommon, score);
+        }
+
+        // A few characters changed is still too similar
+        let password = self.password.to_lowercase();
+        let is_similar = |input: &str| {
+            let input = input.to_lowercase();
+            input.chars().count() >= 3
+                && strength::edit_distance(&password, &input) <= password.chars().count() / 4
+        };
+        if is_similar(username) {
+            return invalid(PasswordError::TooSimilarToUsername, score);
+        }
+        if is_similar(email_name) {
+            return invalid(PasswordError::TooSimilarToEmail, score);
+        }
+
+        if score < MIN_SCORE {
+            // Blame the username or email if they're why it's weak
+            let rule = if estimate.patterns.contains(&strength::Pattern::UserInput(0)) {
+                PasswordError::TooSimilarToUsername
+            } else if estimate.patterns.contains(&strength::Pattern::UserInput(1)) {
+                PasswordError::TooSimilarToEmail
+            } else {
+                PasswordError::TooWeak
+            };
+            return invalid(rule, score);
+        }
+
+        Ok(score)
+    }
+
+    /// Generates a new hash for the given password.
+    pub fn generate(&self) -> String {
+        use argon2::PasswordHasher;
+
+        let (secret, _) = current_pepper();
+        let hasher = argon2::Argon2::new_with_secret(
+            &secret,
+            argon2::Algorithm::Argon2id,
+            argon2::Version::V0x13,
+            params(),
+        )
+        .expect("Why is your pepper so long????");
+
+        let salt = password_hash::SaltString::generate(&mut rand::thread_rng());
+        hasher
+            .hash_password(self.password.as_bytes(), &salt)
+            .expect("Failed to hash password")
+            .to_string()
+    }
+
+    /// Verifies this password matches a PHC string.
+    ///
+    /// The hash is checked with the parameters and pepper it was made with,
+    /// which may not be the current ones. Use [`Password::needs_rehash`] to
+    /// find out if it should be updated.
+    pub fn verify(&self, phc: &str) -> bool {
+        let Ok(expected_hash) = argon2::PasswordHash::new(phc) else {
+            return false;
+        };
+        let Ok(params) = argon2::Params::try_from(&expected_hash) else {
+            return false;
+        };
+        let Some(secret) = pepper(params.keyid()) else {
+            warn!("No pepper with the key ID of a password hash, is it in OLD_PEPPERS?");
+            return false;
+        };
+
+        argon2::Argon2::new_with_secret(
+            &secret,
+            argon2::Algorithm::Argon2id,
+            argon2::Version::V0x13,
+            params,
+        )
+        .expect("dam that secret is long")
+        .verify_password(self.password.as_bytes(), &expected_hash)
+        .is_ok()
+    }
+
+    /// Whether a PHC string was made with weaker parameters or an older
+    /// pepper than new hashes are, and should be generated again.
+    pub fn needs_rehash(phc: &str) -> bool {
+        let Ok(hash) = argon2::PasswordHash::new(phc) else {
+            return true;
+        };
+        let Ok(stored) = argon2::Params::try_from(&hash) else {
+            return true;
+        };
+        let current = params();
+
+        hash.algorithm != argon2::Algorithm::Argon2id.ident()
+            || hash.version != Some(argon2::Version::V0x13.into())
+            || stored.m_cost() < current.m_cost()
+            || stored.t_cost() < current.t_cost()
+            || stored.p_cost() < current.p_cost()
+            || stored.keyid() != current.keyid()
+    }
+}
+
+impl<'pw> From<&'pw str> for Password<'pw> {
+    fn from(password: &'pw str) -> Self {
+        Self::new(password)
+    }
+}
+impl<'pw> From<&'pw String> for Password<'pw> {
+    fn from(password: &'pw String) -> Self {
+        Self::new(password)
+    }
+}
+
+/// Why a password is not valid, and how strong it is.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct InvalidPassword {
+    /// The rule the password broke.
+    pub rule: PasswordError,
+    /// How hard the password is to guess, from 0 (trivial) to 4 (very hard).
+    pub score: u8,
+}
+
+/// An error that can occur when validating a password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordError {
+    /// The password being validated was too short.
+    TooShort,
+    /// The password being validated was too long.
+    TooLong,
+    /// The password being validated was too common.
+    TooCommon,
+    /// The password being validated was too similar to the username.
+    TooSimilarToUsername,
+    /// The password being validated was too similar to the email.
+    TooSimilarToEmail,
+    /// The password being validated was too weak.
+    TooWeak,
+}
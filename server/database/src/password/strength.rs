@@ -0,0 +1,330 @@
+//! Estimating how hard a password is to guess.
+//!
+//! This works like a much smaller [zxcvbn](https://github.com/dropbox/zxcvbn):
+//! the password is split into the pieces an attacker would guess separately,
+//! such as common passwords, keyboard runs, sequences and repeats, and the
+//! cheapest way to guess every piece is what the password is worth.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+/// The 10,000 most common passwords, most common first. Taken from the
+/// frequency lists of the `zxcvbn` crate.
+const COMMON_PASSWORDS: &str = include_str!("common-passwords.txt");
+
+/// Rows of a QWERTY keyboard, for spotting runs like `asdf`.
+const KEYBOARD_ROWS: [&str; 4] = [
+    "`1234567890-=",
+    "qwertyuiop[]\\",
+    "asdfghjkl;'",
+    "zxcvbnm,./",
+];
+
+/// Common substitutions of letters for numbers and symbols.
+const LEET: [(char, char); 10] = [
+    ('4', 'a'),
+    ('@', 'a'),
+    ('3', 'e'),
+    ('1', 'i'),
+    ('!', 'i'),
+    ('0', 'o'),
+    ('5', 's'),
+    ('$', 's'),
+    ('7', 't'),
+    ('+', 't'),
+];
+
+/// The rank of every common password, starting at 1.
+fn common_passwords() -> &'static HashMap<&'static str, usize> {
+    static RANKS: OnceLock<HashMap<&'static str, usize>> = OnceLock::new();
+    RANKS.get_or_init(|| {
+        COMMON_PASSWORDS
+            .lines()
+            .enumerate()
+            .map(|(rank, password)| (password, rank + 1))
+            .collect()
+    })
+}
+
+/// Whether the password is one of the common passwords, ignoring case,
+/// substitutions and any digits or symbols tacked on the end.
+pub(super) fn is_common(password: &str) -> bool {
+    let password = password.to_lowercase();
+    let trimmed = password.trim_end_matches(|c: char| !c.is_alphabetic());
+
+    let common = common_passwords();
+    common.contains_key(password.as_str())
+        || common.contains_key(unleet(&password).as_str())
+        || (trimmed.len() >= 4
+            && (common.contains_key(trimmed) || common.contains_key(unleet(trimmed).as_str())))
+}
+
+fn unleet(password: &str) -> String {
+    password
+        .chars()
+        .map(|c| {
+            LEET.iter()
+                .find(|(from, _)| *from == c)
+                .map_or(c, |(_, to)| *to)
+        })
+        .collect()
+}
+
+/// What a piece of the password was guessed as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Pattern {
+    BruteForce,
+    Dictionary,
+    /// One of the user inputs, by its index.
+    UserInput(usize),
+    Sequence,
+    Keyboard,
+    Repeat,
+    Year,
+}
+
+/// How hard a password is to guess.
+#[derive(Debug, Clone)]
+pub(super) struct Estimate {
+    /// The base 2 logarithm of how many guesses the password takes.
+    pub bits: f64,
+    /// Every piece the password was split into.
+    pub patterns: Vec<Pattern>,
+}
+impl Estimate {
+    /// A score from 0 (trivial to guess) to 4 (very hard to guess), on the
+    /// same scale as zxcvbn.
+    pub fn score(&self) -> u8 {
+        let guesses_log10 = self.bits * std::f64::consts::LOG10_2;
+        match guesses_log10 {
+            g if g < 3.0 => 0,
+            g if g < 6.0 => 1,
+            g if g < 8.0 => 2,
+            g if g < 10.0 => 3,
+            _ => 4,
+        }
+    }
+}
+
+/// Estimates how hard a password is to guess. `user_inputs` are words an
+/// attacker would try first, like the user's username.
+pub(super) fn estimate(password: &str, user_inputs: &[&str]) -> Estimate {
+    let chars: Vec<char> = password.chars().collect();
+    let user_inputs: Vec<String> = user_inputs
+        .iter()
+        .filter(|input| input.chars().count() >= 3)
+        .map(|input| unleet(&input.to_lowercase()))
+        .collect();
+    let cardinality = cardinality(&chars).log2();
+
+    // The cheapest way to guess the first `i` characters, and the pieces
+    // that make it up.
+    let mut best: Vec<(f64, Vec<Pattern>)> = vec![(0.0, vec![])];
+    for end in 1..=chars.len() {
+        let mut cheapest: Option<(f64, Vec<Pattern>)> = None;
+        for start in 0..end {
+            let (bits, pattern) = piece(&chars[start..end], cardinality, &user_inputs);
+            // Every extra piece is one more choice of where it starts.
+            let (before, patterns) = &best[start];
+            let bits = before + bits + if start == 0 { 0.0 } else { 1.0 };
+
+            if cheapest
+                .as_ref()
+                .map_or(true, |(cheapest, _)| bits < *cheapest)
+            {
+                let mut patterns = patterns.clone();
+                patterns.push(pattern);
+                cheapest = Some((bits, patterns));
+            }
+        }
+        best.push(cheapest.unwrap_or_default());
+    }
+
+    let (bits, patterns) = best.pop().unwrap_or_default();
+    Estimate { bits, patterns }
+}
+
+/// How many characters an attacker has to try for each position.
+fn cardinality(chars: &[char]) -> f64 {
+    let mut cardinality = 0.0;
+    if chars.iter().any(char::is_ascii_lowercase) {
+        cardinality += 26.0;
+    }
+    if chars.iter().any(char::is_ascii_uppercase) {
+        cardinality += 26.0;
+    }
+    if chars.iter().any(char::is_ascii_digit) {
+        cardinality += 10.0;
+    }
+    if chars.iter().any(char::is_ascii_punctuation) || chars.contains(&' ') {
+        cardinality += 33.0;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        cardinality += 100.0;
+    }
+    f64::max(cardinality, 1.0)
+}
+
+/// The cheapest way to guess a piece of the password, in bits.
+#[allow(clippy::cast_precision_loss)]
+fn piece(chars: &[char], cardinality: f64, user_inputs: &[String]) -> (f64, Pattern) {
+    let mut cheapest = (chars.len() as f64 * cardinality, Pattern::BruteForce);
+    let mut consider = |bits: f64, pattern: Pattern| {
+        if bits < cheapest.0 {
+            cheapest = (bits, pattern);
+        }
+    };
+
+    if let Some((bits, pattern)) = dictionary(chars, user_inputs) {
+        consider(bits, pattern);
+    }
+    if let Some(bits) = sequence(chars) {
+        consider(bits, Pattern::Sequence);
+    }
+    if let Some(bits) = keyboard(chars) {
+        consider(bits, Pattern::Keyboard);
+    }
+    if let Some(bits) = year(chars) {
+        consider(bits, Pattern::Year);
+    }
+    if let Some(bits) = repeat(chars, cardinality, user_inputs) {
+        consider(bits, Pattern::Repeat);
+    }
+
+    cheapest
+}
+
+/// Matches common passwords and user inputs, forwards or backwards, with
+/// any capitalisation and substitutions.
+#[allow(clippy::cast_precision_loss)]
+fn dictionary(chars: &[char], user_inputs: &[String]) -> Option<(f64, Pattern)> {
+    if chars.len() < 3 {
+        return None;
+    }
+
+    let word: String = chars.iter().collect::<String>().to_lowercase();
+    let unleeted = unleet(&word);
+    let substitutions = word
+        .chars()
+        .zip(unleeted.chars())
+        .filter(|(a, b)| a != b)
+        .count();
+
+    // Each capital letter could have been anywhere, but capitalising the
+    // first letter or every letter are the first things tried.
+    let uppers = chars.iter().filter(|c| c.is_uppercase()).count();
+    let lowers = chars.iter().filter(|c| c.is_lowercase()).count();
+    let case_bits = if uppers == 0 || lowers == 0 || (uppers == 1 && chars[0].is_uppercase()) {
+        if uppers == 0 {
+            0.0
+        } else {
+            1.0
+        }
+    } else {
+        uppers.min(lowers) as f64 + 1.0
+    };
+    let extra_bits = case_bits + substitutions as f64;
+
+    let reversed: String = unleeted.chars().rev().collect();
+    let mut cheapest: Option<(f64, Pattern)> = None;
+    for (candidate, reverse_bits) in [(&unleeted, 0.0), (&reversed, 1.0)] {
+        if let Some(index) = user_inputs.iter().position(|input| input == candidate) {
+            return Some((extra_bits + reverse_bits, Pattern::UserInput(index)));
+        }
+        if let Some(rank) = common_passwords().get(candidate.as_str()) {
+            let bits = (*rank as f64).log2() + extra_bits + reverse_bits;
+            if cheapest.map_or(true, |(cheapest, _)| bits < cheapest) {
+                cheapest = Some((bits, Pattern::Dictionary));
+            }
+        }
+    }
+    cheapest
+}
+
+/// Matches runs like `abcd`, `9876` or `acegi`.
+#[allow(clippy::cast_precision_loss)]
+fn sequence(chars: &[char]) -> Option<f64> {
+    if chars.len() < 3 {
+        return None;
+    }
+
+    let step = chars[1] as i64 - chars[0] as i64;
+    if step == 0 || step.abs() > 5 {
+        return None;
+    }
+    if !chars.windows(2).all(|w| w[1] as i64 - w[0] as i64 == step) {
+        return None;
+    }
+
+    // Sequences starting at an obvious character are tried first.
+    let start_bits = if matches!(chars[0], 'a' | 'A' | 'z' | 'Z' | '0' | '1' | '9') {
+        1.0
+    } else if chars[0].is_ascii_digit() {
+        10f64.log2()
+    } else {
+        26f64.log2()
+    };
+    let direction_bits = if step > 0 { 0.0 } else { 1.0 };
+    Some(start_bits + (chars.len() as f64).log2() + direction_bits + (step.abs() as f64).log2())
+}
+
+/// Matches runs of keys next to each other on a row of the keyboard, like
+/// `qwerty` or `lkjh`.
+#[allow(clippy::cast_precision_loss)]
+fn keyboard(chars: &[char]) -> Option<f64> {
+    if chars.len() < 4 {
+        return None;
+    }
+
+    let word: String = chars.iter().collect::<String>().to_lowercase();
+    let reversed: String = word.chars().rev().collect();
+    for (candidate, direction_bits) in [(&word, 0.0), (&reversed, 1.0)] {
+        if KEYBOARD_ROWS
+            .iter()
+            .any(|row| row.contains(candidate.as_str()))
+        {
+            let start_bits =
+                (KEYBOARD_ROWS.iter().map(|row| row.len()).sum::<usize>() as f64).log2();
+            return Some(start_bits + (chars.len() as f64).log2() + direction_bits);
+        }
+    }
+    None
+}
+
+/// Matches recent years, like `1998` or `2023`.
+fn year(chars: &[char]) -> Option<f64> {
+    let year: String = chars.iter().collect();
+    if chars.len() != 4 || !chars.iter().all(char::is_ascii_digit) {
+        return None;
+    }
+    match year.parse::<u16>() {
+        Ok(1900..=2099) => Some(200f64.log2()),
+        _ => None,
+    }
+}
+
+/// Matches the same characters repeated, like `aaaa` or `abcabc`.
+#[allow(clippy::cast_precision_loss)]
+fn repeat(chars: &[char], cardinality: f64, user_inputs: &[String]) -> Option<f64> {
+    (1..=chars.len() / 2)
+        .filter(|unit| chars.len() % unit == 0)
+        .find(|unit| chars.chunks(*unit).all(|chunk| chunk == &chars[..*unit]))
+        .map(|unit| {
+            let (unit_bits, _) = piece(&chars[..unit], cardinality, user_inputs);
+            unit_bits + ((chars.len() / unit) as f64).log2()
+        })
+}
+
+/// The minimum number of single character edits to turn `a` into `b`.
+pub(super) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -1,5 +1,8 @@
 pub use crate::{
-    orm::{access_token, channel, guild, message, user},
+    orm::{
+        access_token, ban, channel, channel_overwrite, dm, guild, guild_folder, invite,
+        login_attempt, message, mfa, password_reset, reaction, relationship, role, user,
+    },
     password,
     snowflake::{self, Snowflake},
     time, validation, DbPool,
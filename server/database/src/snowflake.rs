@@ -108,6 +108,16 @@ impl Snowflake {
         }
     }
 
+    /// Creates the lowest [`Snowflake`] that could be generated at a
+    /// timestamp, for finding everything created since then.
+    pub fn from_timestamp(timestamp: u64) -> Self {
+        Self {
+            timestamp,
+            machine_id: 0,
+            increment: 0,
+        }
+    }
+
     /// Returns the snowflake as an i64.
     pub fn into_number(&self) -> i64 {
         // These casts are safe because the values are guaranteed to be within
@@ -11,3 +11,18 @@ pub fn now() -> i64 {
 pub fn into_systime(timestamp: u64) -> std::time::SystemTime {
     std::time::UNIX_EPOCH + std::time::Duration::from_millis(timestamp + DB_EPOCH as u64)
 }
+
+/// A source of the current time, so that code depending on it can be given a
+/// fake clock.
+pub trait Clock: Send + Sync {
+    /// Returns the current time in milliseconds since the database epoch.
+    fn now(&self) -> i64;
+}
+
+/// The real time, as returned by [`now`].
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        now()
+    }
+}
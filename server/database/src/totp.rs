@@ -0,0 +1,223 @@
+//! Time-based one-time passwords ([RFC 6238](https://www.rfc-editor.org/rfc/rfc6238)),
+//! as used by authenticator apps.
+//!
+//! Secrets are encrypted with the `TOTP_KEY` before they're stored, so that
+//! a leaked database can't be used to generate codes.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use hmac::{Hmac, Mac};
+
+/// How long each code is valid for, in seconds.
+pub const STEP: u64 = 30;
+/// How many digits are in each code.
+pub const DIGITS: usize = 6;
+/// How many steps either side of the current one are accepted, to allow for
+/// clocks being slightly off.
+const SKEW: u64 = 1;
+/// How many bytes are in a secret. 160 bits, as recommended for SHA-1.
+const SECRET_LENGTH: usize = 20;
+/// How many bytes are in an AES-GCM nonce.
+const NONCE_LENGTH: usize = 12;
+
+/// Returns the key secrets are encrypted with.
+fn key() -> Aes256Gcm {
+    use base64::Engine;
+
+    // Read the key from the env and parse from b64 to [u8]
+    let key = std::env::var("TOTP_KEY").expect("TOTP_KEY not set");
+    let key = base64::engine::general_purpose::STANDARD
+        .decode(key)
+        .expect("TOTP_KEY is not valid base64");
+    Aes256Gcm::new_from_slice(&key).expect("TOTP_KEY must be 32 bytes")
+}
+
+/// Generates a new random secret.
+pub fn generate_secret() -> Vec<u8> {
+    use rand::Rng;
+
+    let mut secret = vec![0u8; SECRET_LENGTH];
+    rand::thread_rng().fill(&mut secret[..]);
+    secret
+}
+
+/// Encrypts a secret to be stored. The nonce is prepended to the output.
+pub fn encrypt(secret: &[u8]) -> Vec<u8> {
+    use rand::Rng;
+
+    let nonce: [u8; NONCE_LENGTH] = rand::thread_rng().gen();
+    let mut encrypted = nonce.to_vec();
+    encrypted.extend(
+        key()
+            .encrypt(Nonce::from_slice(&nonce), secret)
+            .expect("Failed to encrypt TOTP secret"),
+    );
+    encrypted
+}
+
+/// Decrypts a secret made with [`encrypt`]. Returns `None` if it was
+/// encrypted with a different key or has been tampered with.
+pub fn decrypt(encrypted: &[u8]) -> Option<Vec<u8>> {
+    if encrypted.len() < NONCE_LENGTH {
+        return None;
+    }
+    let (nonce, ciphertext) = encrypted.split_at(NONCE_LENGTH);
+    key().decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+}
+
+/// The code for a time step, as in [RFC 4226](https://www.rfc-editor.org/rfc/rfc4226).
+#[allow(clippy::cast_possible_truncation)]
+fn code(secret: &[u8], step: u64) -> String {
+    let mut mac =
+        <Hmac<sha1::Sha1> as Mac>::new_from_slice(secret).expect("HMAC takes keys of any size");
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    let offset = (hash[hash.len() - 1] & 0xf) as usize;
+    let truncated = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    format!("{:0DIGITS$}", truncated % 10u32.pow(DIGITS as u32))
+}
+
+/// The current time step.
+fn current_step() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+        / STEP
+}
+
+/// Checks a code against a secret. Returns the time step it was for, so
+/// that the same code can be refused if it's used again.
+pub fn verify(secret: &[u8], code_to_check: &str) -> Option<u64> {
+    if code_to_check.len() != DIGITS || !code_to_check.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    verify_at(secret, code_to_check, current_step())
+}
+
+/// Checks a code against a secret as if `now` was the current time step.
+fn verify_at(secret: &[u8], code_to_check: &str, now: u64) -> Option<u64> {
+    (now.saturating_sub(SKEW)..=now + SKEW).find(|step| code(secret, *step) == code_to_check)
+}
+
+/// The `otpauth://` URI authenticator apps can scan as a QR code.
+pub fn uri(secret: &[u8], account: &str) -> String {
+    let encode = |s: &str| -> String {
+        s.bytes()
+            .map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    (b as char).to_string()
+                }
+                _ => format!("%{b:02X}"),
+            })
+            .collect()
+    };
+    format!(
+        "otpauth://totp/Campfire:{}?secret={}&issuer=Campfire&algorithm=SHA1&digits={DIGITS}&period={STEP}",
+        encode(account),
+        base32(secret)
+    )
+}
+
+/// Encodes bytes as unpadded base32, which is how secrets are shown to
+/// users.
+pub fn base32(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+    let mut encoded = String::with_capacity((bytes.len() * 8 + 4) / 5);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[usize::from((buffer >> bits) & 0x1f)] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)] as char);
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The SHA-1 secret from RFC 6238 appendix B.
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    /// Decodes unpadded base32, the way authenticator apps read secrets.
+    fn decode_base32(encoded: &str) -> Vec<u8> {
+        const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+        let mut decoded = Vec::new();
+        let mut buffer = 0u16;
+        let mut bits = 0;
+        for c in encoded.bytes() {
+            let value = ALPHABET.iter().position(|a| *a == c).unwrap();
+            buffer = (buffer << 5) | u16::try_from(value).unwrap();
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                decoded.push(u8::try_from((buffer >> bits) & 0xff).unwrap());
+            }
+        }
+        decoded
+    }
+
+    #[test]
+    fn matches_rfc_6238_vectors() {
+        // The RFC's codes are 8 digits; ours are the last 6 of them
+        let vectors = [
+            (59, "287082"),
+            (1_111_111_109, "081804"),
+            (1_111_111_111, "050471"),
+            (1_234_567_890, "005924"),
+            (2_000_000_000, "279037"),
+            (20_000_000_000, "353130"),
+        ];
+        for (time, expected) in vectors {
+            assert_eq!(code(RFC_SECRET, time / STEP), expected, "at {time}");
+        }
+    }
+
+    #[test]
+    fn accepts_one_step_either_side() {
+        let now = 1_234_567_890 / STEP;
+
+        for step in [now - 1, now, now + 1] {
+            assert_eq!(
+                verify_at(RFC_SECRET, &code(RFC_SECRET, step), now),
+                Some(step)
+            );
+        }
+        for step in [now - 2, now + 2] {
+            assert_eq!(verify_at(RFC_SECRET, &code(RFC_SECRET, step), now), None);
+        }
+    }
+
+    #[test]
+    fn base32_round_trips() {
+        assert_eq!(base32(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32(RFC_SECRET), "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+
+        for len in 0..=SECRET_LENGTH {
+            let secret: Vec<u8> = (0..len)
+                .map(|i| u8::try_from(i * 37 % 256).unwrap())
+                .collect();
+            assert_eq!(decode_base32(&base32(&secret)), secret);
+        }
+        let secret = generate_secret();
+        assert_eq!(decode_base32(&base32(&secret)), secret);
+    }
+}
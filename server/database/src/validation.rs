@@ -13,7 +13,8 @@ pub fn validate_username(username: &str) -> bool {
 
 /// Validates an email conforms to a standard / *could* exist.
 ///
-/// Does not check if it actually exists or send out a verification email.
+/// Does not check if it actually exists. That is done by sending a
+/// verification email, see [`crate::user::email_verification_token`].
 pub fn validate_email(email: &str) -> bool {
     if email.len() < 3 || email.len() > 320 {
         return false;
@@ -25,3 +26,30 @@ pub fn validate_email(email: &str) -> bool {
 
     true
 }
+
+/// Validates a color is a 6-digit hex code, without the leading `#`.
+pub fn validate_color(color: &str) -> bool {
+    color.len() == 6 && color.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Validates a string looks like a single Unicode emoji.
+///
+/// There are too many emoji and sequences of them to check against a list,
+/// so this only rejects text that can't be one: anything empty, too long,
+/// or containing letters, whitespace or control characters. Every emoji
+/// has at least one character outside ASCII, which also keeps them apart
+/// from the IDs of custom emoji.
+pub fn validate_emoji(emoji: &str) -> bool {
+    if emoji.is_empty() || emoji.len() > 64 {
+        return false;
+    }
+
+    if emoji
+        .chars()
+        .any(|c| c.is_ascii_alphabetic() || c.is_whitespace() || c.is_control())
+    {
+        return false;
+    }
+
+    !emoji.is_ascii()
+}
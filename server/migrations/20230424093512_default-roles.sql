@@ -0,0 +1,10 @@
+-- Every guild gets a default role that all of its members implicitly hold.
+-- It shares its ID with the guild so it can be found without a lookup, and
+-- grants permission to view channels and send messages (0b11).
+INSERT INTO roles (id, guild_id, name, color, permission_mask, "order")
+SELECT id, id, 'everyone', '000000', 3, 0 FROM guilds
+ON CONFLICT (id) DO NOTHING;
+
+-- A member can only be given each role once.
+ALTER TABLE guild_member_roles
+    ADD CONSTRAINT guild_member_roles_member_role_key UNIQUE (guild_member_id, role_id);
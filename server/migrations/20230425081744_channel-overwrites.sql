@@ -0,0 +1,15 @@
+-- Allows or denies permissions for a single role or member on a single
+-- channel, on top of the permissions they have in the guild.
+CREATE TABLE IF NOT EXISTS channel_overwrites (
+    channel_id bigint NOT NULL,
+    -- A role ID if `kind` is 0, or a user ID if `kind` is 1
+    target_id bigint NOT NULL,
+    kind smallint NOT NULL,
+
+    allow bigint NOT NULL DEFAULT 0,
+    deny bigint NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (channel_id, target_id),
+    FOREIGN KEY (channel_id) REFERENCES channels (id),
+    CHECK (kind IN (0, 1))
+);
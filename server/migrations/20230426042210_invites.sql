@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS invites (
+    code varchar(16) NOT NULL,
+    guild_id bigint NOT NULL,
+    -- The user who created the invite. References users.id
+    inviter_id bigint NOT NULL,
+    created_at bigint NOT NULL,
+
+    -- Nullable because the invite may never expire
+    expires_at bigint DEFAULT NULL,
+    -- Nullable because the invite may be used any amount of times
+    max_uses integer DEFAULT NULL,
+    uses integer NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (code),
+    FOREIGN KEY (guild_id) REFERENCES guilds (id),
+    FOREIGN KEY (inviter_id) REFERENCES users (id)
+);
+
+-- A user can only be a member of each guild once.
+ALTER TABLE guild_members
+    ADD CONSTRAINT guild_members_guild_user_key UNIQUE (guild_id, user_id);
+
+-- Let everyone create invites by default (1 << 7).
+UPDATE roles SET permission_mask = permission_mask | 128 WHERE id = guild_id;
@@ -0,0 +1,17 @@
+-- Deleted messages are kept so that a placeholder can be shown in their
+-- place instead of a gap. Null while the message is not deleted.
+ALTER TABLE messages ADD COLUMN deleted_at bigint DEFAULT NULL;
+
+-- Every prior version of an edited message.
+CREATE TABLE IF NOT EXISTS message_revisions (
+    id bigint NOT NULL,
+    message_id bigint NOT NULL,
+
+    content varchar(16000) NOT NULL,
+    -- When this version was written, i.e. the message's `updated_at` at the
+    -- time it was edited
+    written_at bigint NOT NULL,
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (message_id) REFERENCES messages (id)
+);
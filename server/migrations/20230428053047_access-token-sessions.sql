@@ -0,0 +1,14 @@
+-- Give every access token a public ID so that sessions can be listed and
+-- revoked without exposing the token itself. Existing tokens get an ID in
+-- the snowflake format built from when they were created.
+ALTER TABLE access_tokens ADD COLUMN id bigint;
+UPDATE access_tokens SET id = (created_at << 21) | (token & 2097151);
+ALTER TABLE access_tokens ALTER COLUMN id SET NOT NULL;
+ALTER TABLE access_tokens ADD CONSTRAINT access_tokens_id_key UNIQUE (id);
+
+-- The `User-Agent` of the client that logged in, if it sent one.
+ALTER TABLE access_tokens ADD COLUMN user_agent varchar(255) DEFAULT NULL;
+
+-- When the token was last used, for expiring idle sessions.
+ALTER TABLE access_tokens ADD COLUMN last_used_at bigint NOT NULL DEFAULT 0;
+UPDATE access_tokens SET last_used_at = created_at;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS password_resets (
+    -- The SHA-256 hash of the token sent to the user, so that a leaked
+    -- database can't be used to reset anyone's password.
+    token_hash bytea NOT NULL,
+    user_id bigint NOT NULL,
+    created_at bigint NOT NULL,
+    expires_at bigint NOT NULL,
+
+    PRIMARY KEY (token_hash),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
@@ -0,0 +1,3 @@
+-- When the user clicked the link sent to their email address, or NULL if
+-- they haven't yet.
+ALTER TABLE users ADD COLUMN email_verified_at bigint DEFAULT NULL;
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS user_totp (
+    user_id bigint NOT NULL,
+    -- The TOTP secret, encrypted with TOTP_KEY and prefixed with the nonce.
+    secret bytea NOT NULL,
+    -- NULL until the user confirms their authenticator app works, so a
+    -- half finished setup doesn't lock them out.
+    enabled_at bigint DEFAULT NULL,
+    -- The time step of the last code used, so a code can't be used twice.
+    last_used_step bigint NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (user_id),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
+
+CREATE TABLE IF NOT EXISTS recovery_codes (
+    user_id bigint NOT NULL,
+    -- The SHA-256 hash of the code, like password reset tokens.
+    code_hash bytea NOT NULL,
+
+    PRIMARY KEY (user_id, code_hash),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS login_attempts (
+    id bigint NOT NULL,
+    -- The email address that was tried, which may not belong to anyone.
+    email varchar(255) NOT NULL,
+    -- The account the email address belonged to at the time, if any.
+    user_id bigint DEFAULT NULL,
+    ip varchar(45) NOT NULL,
+    user_agent varchar(255) DEFAULT NULL,
+    succeeded boolean NOT NULL,
+    attempted_at bigint NOT NULL,
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
+
+CREATE INDEX login_attempts_email ON login_attempts (email, attempted_at);
+CREATE INDEX login_attempts_ip ON login_attempts (ip, attempted_at);
+CREATE INDEX login_attempts_user_id ON login_attempts (user_id, attempted_at);
@@ -0,0 +1,33 @@
+-- DMs are channels that don't belong to a guild.
+ALTER TABLE channels ALTER COLUMN guild_id DROP NOT NULL;
+
+CREATE TABLE IF NOT EXISTS dm_channels (
+    channel_id bigint NOT NULL,
+    -- A DM between two users if `kind` is 0, or a group DM if `kind` is 1
+    kind smallint NOT NULL,
+    -- The user who can remove members from a group DM. NULL for DMs.
+    owner_id bigint DEFAULT NULL,
+
+    -- The two users in a DM, lowest ID first, so that each pair of users
+    -- only has one DM. NULL for group DMs.
+    low_user_id bigint DEFAULT NULL,
+    high_user_id bigint DEFAULT NULL,
+
+    PRIMARY KEY (channel_id),
+    FOREIGN KEY (channel_id) REFERENCES channels (id),
+    FOREIGN KEY (owner_id) REFERENCES users (id),
+    UNIQUE (low_user_id, high_user_id),
+    CHECK (kind IN (0, 1))
+);
+
+CREATE TABLE IF NOT EXISTS dm_members (
+    channel_id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    joined_at bigint NOT NULL,
+
+    PRIMARY KEY (channel_id, user_id),
+    FOREIGN KEY (channel_id) REFERENCES dm_channels (channel_id),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
+
+CREATE INDEX IF NOT EXISTS dm_members_user_id_idx ON dm_members (user_id);
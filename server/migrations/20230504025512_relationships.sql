@@ -0,0 +1,20 @@
+-- How a user relates to another. Each side of a relationship has its own
+-- row, except that only the user who blocked someone has a row for it.
+CREATE TABLE IF NOT EXISTS relationships (
+    user_id bigint NOT NULL,
+    target_id bigint NOT NULL,
+    -- 0: friends, 1: `user_id` sent a friend request to `target_id`,
+    -- 2: `target_id` sent a friend request to `user_id`,
+    -- 3: `user_id` blocked `target_id`
+    kind smallint NOT NULL,
+    created_at bigint NOT NULL,
+
+    PRIMARY KEY (user_id, target_id),
+    FOREIGN KEY (user_id) REFERENCES users (id),
+    FOREIGN KEY (target_id) REFERENCES users (id),
+    CHECK (kind IN (0, 1, 2, 3)),
+    CHECK (user_id != target_id)
+);
+
+-- Friend requests are sent by username and discriminator.
+CREATE INDEX IF NOT EXISTS users_username_discrim_idx ON users (username, discrim);
@@ -0,0 +1,19 @@
+-- Users who can't rejoin a guild until they are unbanned or the ban expires.
+CREATE TABLE IF NOT EXISTS guild_bans (
+    guild_id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    -- The member who banned the user
+    moderator_id bigint NOT NULL,
+    reason varchar(512) DEFAULT NULL,
+    created_at bigint NOT NULL,
+    -- Nullable because the ban may be permanent
+    expires_at bigint DEFAULT NULL,
+
+    PRIMARY KEY (guild_id, user_id),
+    FOREIGN KEY (guild_id) REFERENCES guilds (id),
+    FOREIGN KEY (user_id) REFERENCES users (id),
+    FOREIGN KEY (moderator_id) REFERENCES users (id)
+);
+
+-- Purging a banned user's messages selects them by author.
+CREATE INDEX IF NOT EXISTS messages_author_id_idx ON messages (author_id);
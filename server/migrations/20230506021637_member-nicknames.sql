@@ -0,0 +1,6 @@
+-- What a member is called in a guild, instead of their username.
+ALTER TABLE guild_members ADD COLUMN IF NOT EXISTS nickname varchar(32) DEFAULT NULL;
+
+-- Members are listed in the order they joined, and searched by username.
+CREATE INDEX IF NOT EXISTS guild_members_guild_id_id_idx ON guild_members (guild_id, id);
+CREATE INDEX IF NOT EXISTS users_lower_username_idx ON users (lower(username) text_pattern_ops);
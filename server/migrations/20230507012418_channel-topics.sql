@@ -0,0 +1,2 @@
+-- What a channel is for, shown beside its name.
+ALTER TABLE channels ADD COLUMN IF NOT EXISTS topic varchar(1024) DEFAULT NULL;
@@ -0,0 +1,19 @@
+-- 0: a text channel, 1: a category that groups other channels
+ALTER TABLE channels ADD COLUMN IF NOT EXISTS kind smallint NOT NULL DEFAULT 0;
+ALTER TABLE channels ADD CONSTRAINT channels_kind_check CHECK (kind IN (0, 1));
+
+-- The category a channel is in, or NULL if it isn't in one. Categories
+-- can't be put in other categories.
+ALTER TABLE channels ADD COLUMN IF NOT EXISTS parent_id bigint DEFAULT NULL;
+ALTER TABLE channels
+    ADD CONSTRAINT channels_parent_id_fkey FOREIGN KEY (parent_id) REFERENCES channels (id);
+
+-- Categories each user has collapsed in their sidebar.
+CREATE TABLE IF NOT EXISTS collapsed_categories (
+    user_id bigint NOT NULL,
+    channel_id bigint NOT NULL,
+
+    PRIMARY KEY (user_id, channel_id),
+    FOREIGN KEY (user_id) REFERENCES users (id),
+    FOREIGN KEY (channel_id) REFERENCES channels (id)
+);
@@ -0,0 +1,19 @@
+-- Folders that group guilds in a user's sidebar. A folder sits where the
+-- highest of its guilds would be.
+CREATE TABLE IF NOT EXISTS guild_folders (
+    id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    name varchar(32) NOT NULL,
+    -- Hex colour code without the leading `#`, or NULL for the default
+    color varchar(6) DEFAULT NULL,
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
+
+CREATE INDEX IF NOT EXISTS guild_folders_user_id_idx ON guild_folders (user_id);
+
+-- The folder the member put this guild in, if any.
+ALTER TABLE guild_members ADD COLUMN IF NOT EXISTS folder_id bigint DEFAULT NULL;
+ALTER TABLE guild_members
+    ADD CONSTRAINT guild_members_folder_id_fkey FOREIGN KEY (folder_id) REFERENCES guild_folders (id);
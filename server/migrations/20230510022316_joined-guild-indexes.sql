@@ -0,0 +1,4 @@
+-- Indexes for loading a user's sidebar: their memberships in order, then the
+-- channels of every guild they are in.
+CREATE INDEX IF NOT EXISTS guild_members_user_id_order_idx ON guild_members (user_id, "order");
+CREATE INDEX IF NOT EXISTS channels_guild_id_order_idx ON channels (guild_id, "order");
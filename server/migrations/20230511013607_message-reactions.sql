@@ -0,0 +1,13 @@
+-- Emoji reactions on messages. Each user can react to a message with each
+-- emoji once.
+CREATE TABLE IF NOT EXISTS message_reactions (
+    message_id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    -- The Unicode emoji itself, or the ID of a custom emoji in decimal
+    emoji varchar(64) NOT NULL,
+    created_at bigint NOT NULL,
+
+    PRIMARY KEY (message_id, emoji, user_id),
+    FOREIGN KEY (message_id) REFERENCES messages (id),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
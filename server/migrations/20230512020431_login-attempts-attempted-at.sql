@@ -0,0 +1,2 @@
+-- For forgetting old login attempts, which happens on every login.
+CREATE INDEX IF NOT EXISTS login_attempts_attempted_at_idx ON login_attempts (attempted_at);
@@ -0,0 +1,21 @@
+-- Tags (`username#discrim`) must belong to one user. Give a new discriminator
+-- to anyone who shares a tag with an older account before enforcing it.
+DO $$
+DECLARE
+    dup RECORD;
+BEGIN
+    FOR dup IN
+        SELECT u.id, u.username FROM users u WHERE EXISTS (
+            SELECT 1 FROM users o WHERE o.username = u.username AND o.discrim = u.discrim AND o.id < u.id
+        )
+    LOOP
+        UPDATE users SET discrim = (
+            SELECT d FROM generate_series(0, 9999) d
+            WHERE NOT EXISTS (SELECT 1 FROM users o WHERE o.username = dup.username AND o.discrim = d)
+            ORDER BY random() LIMIT 1
+        ) WHERE id = dup.id;
+    END LOOP;
+END $$;
+
+DROP INDEX IF EXISTS users_username_discrim_idx;
+CREATE UNIQUE INDEX IF NOT EXISTS users_username_discrim_key ON users (username, discrim);
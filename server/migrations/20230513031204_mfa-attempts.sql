@@ -0,0 +1,20 @@
+-- Wrong codes given when logging in with 2FA, to slow down anyone guessing
+-- them. Reset once a right code is given.
+ALTER TABLE user_totp ADD COLUMN IF NOT EXISTS failures integer NOT NULL DEFAULT 0;
+ALTER TABLE user_totp ADD COLUMN IF NOT EXISTS last_failure_at bigint DEFAULT NULL;
+
+-- MFA tickets that can still be exchanged for an access token. A ticket is
+-- removed once it is used, or after too many wrong codes.
+CREATE TABLE IF NOT EXISTS mfa_tickets (
+    id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    -- Wrong codes given with this ticket.
+    failures integer NOT NULL DEFAULT 0,
+    expires_at bigint NOT NULL,
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
+
+-- For forgetting expired tickets, which happens whenever one is made.
+CREATE INDEX IF NOT EXISTS mfa_tickets_expires_at_idx ON mfa_tickets (expires_at);
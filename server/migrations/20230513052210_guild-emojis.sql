@@ -0,0 +1,12 @@
+-- A guild's custom emoji, which its members can react to messages in the
+-- guild with.
+CREATE TABLE IF NOT EXISTS guild_emojis (
+    id bigint NOT NULL,
+    guild_id bigint NOT NULL,
+    name varchar(32) NOT NULL,
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (guild_id) REFERENCES guilds (id)
+);
+
+CREATE INDEX IF NOT EXISTS guild_emojis_guild_id_idx ON guild_emojis (guild_id);
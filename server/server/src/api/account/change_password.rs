@@ -0,0 +1,83 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The user's current password
+    current_password: String,
+    /// The password to change to
+    new_password: String,
+}
+
+/// The current password is not correct
+pub const INVALID_CREDENTIALS: &str = "InvalidCredentials";
+
+/// Changes the logged in user's password, logging out every other session.
+pub async fn change_password(
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let account = match db.user().get_account(session.user_id).await {
+        Ok(Some(account)) => account,
+        Ok(None) => {
+            warn!(
+                "User {} not found even with a valid session",
+                session.user_id
+            );
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let new_password = password::Password::new(&req.new_password);
+    if let Err(response) =
+        super::validate_password(&new_password, &account.user.username, &account.email)
+    {
+        return response;
+    }
+
+    match db
+        .user()
+        .change_password(
+            session.user_id,
+            password::Password::new(&req.current_password),
+            new_password,
+        )
+        .await
+    {
+        Ok(()) => {}
+        Err(user::ChangePasswordError::InvalidCredentials) => {
+            return err!(UNAUTHORIZED => INVALID_CREDENTIALS);
+        }
+        Err(user::ChangePasswordError::NotFound) => {
+            warn!(
+                "User {} not found even with a valid session",
+                session.user_id
+            );
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(user::ChangePasswordError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let revoked = match db
+        .access_token()
+        .revoke_all_except(session.user_id, session.token)
+        .await
+    {
+        Ok(revoked) => revoked,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    gateway.revoke(&revoked).await;
+
+    ok!(())
+}
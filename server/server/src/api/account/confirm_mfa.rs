@@ -0,0 +1,39 @@
+use super::INVALID_MFA_CODE;
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// A code from the authenticator app
+    code: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Response {
+    /// Codes that can each be used once instead of a code from the
+    /// authenticator app. These are not shown again.
+    recovery_codes: Vec<String>,
+}
+
+/// The user hasn't enrolled in 2FA
+const MFA_NOT_ENROLLED: &str = "MfaNotEnrolled";
+/// 2FA is already enabled
+const MFA_ALREADY_ENABLED: &str = "MfaAlreadyEnabled";
+
+/// Enables 2FA for the logged in user once they've shown their authenticator
+/// app gives the right codes.
+pub async fn confirm_mfa(req: Json<Params>, session: Session, db: Data<DbPool>) -> impl Responder {
+    match db.mfa().confirm(session.user_id, &req.code).await {
+        Ok(recovery_codes) => ok!(Response { recovery_codes }),
+        Err(mfa::ConfirmError::NotEnrolled) => err!(MFA_NOT_ENROLLED),
+        Err(mfa::ConfirmError::AlreadyEnabled) => err!(MFA_ALREADY_ENABLED),
+        Err(mfa::ConfirmError::InvalidCode) => err!(INVALID_MFA_CODE),
+        Err(mfa::ConfirmError::UndecryptableSecret) => {
+            error!("TOTP secret of {} could not be decrypted", session.user_id);
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+        Err(mfa::ConfirmError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+    }
+}
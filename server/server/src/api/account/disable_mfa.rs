@@ -0,0 +1,26 @@
+use super::{INVALID_MFA_CODE, MFA_NOT_ENABLED};
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// A code from the authenticator app, or a recovery code
+    code: String,
+}
+
+/// Turns off 2FA for the logged in user. A code is needed so that a stolen
+/// session can't turn it off.
+pub async fn disable_mfa(req: Json<Params>, session: Session, db: Data<DbPool>) -> impl Responder {
+    match db.mfa().disable(session.user_id, &req.code).await {
+        Ok(()) => ok!(()),
+        Err(mfa::VerifyError::NotEnabled) => err!(MFA_NOT_ENABLED),
+        Err(mfa::VerifyError::InvalidCode) => err!(UNAUTHORIZED => INVALID_MFA_CODE),
+        Err(mfa::VerifyError::UndecryptableSecret) => {
+            error!("TOTP secret of {} could not be decrypted", session.user_id);
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+        Err(mfa::VerifyError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::prelude::*;
+
+/// Every field is optional. An empty string removes the field, except for the
+/// username which cannot be removed.
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The new username. Changing it also changes the discriminator
+    #[serde(default)]
+    username: Option<String>,
+    /// The new hex color code of the profile, without the leading `#`
+    #[serde(default)]
+    accent_color: Option<String>,
+    /// The new pronouns, up to 255 characters
+    #[serde(default)]
+    pronouns: Option<String>,
+    /// The new bio, up to 255 characters
+    #[serde(default)]
+    bio: Option<String>,
+}
+
+/// The username is invalid
+pub const INVALID_USERNAME: &str = "InvalidUsername";
+/// The username and all discriminators are already taken
+pub const USERNAME_TAKEN: &str = "UsernameTaken";
+/// The accent color was not a 6-digit hex code
+pub const INVALID_COLOR: &str = "InvalidColor";
+/// The pronouns were longer than 255 characters
+pub const PRONOUNS_TOO_LONG: &str = "PronounsTooLong";
+/// The bio was longer than 255 characters
+pub const BIO_TOO_LONG: &str = "BioTooLong";
+
+/// Edits the logged in user's profile.
+pub async fn edit_me(req: Json<Params>, session: Session, db: Data<DbPool>) -> impl Responder {
+    if let Some(username) = &req.username {
+        if !validation::validate_username(username) {
+            return err!(INVALID_USERNAME);
+        }
+    }
+    if let Some(color) = &req.accent_color {
+        if !color.is_empty() && !validation::validate_color(color) {
+            return err!(INVALID_COLOR);
+        }
+    }
+    if let Some(pronouns) = &req.pronouns {
+        if pronouns.chars().count() > 255 {
+            return err!(PRONOUNS_TOO_LONG 255);
+        }
+    }
+    if let Some(bio) = &req.bio {
+        if bio.chars().count() > 255 {
+            return err!(BIO_TOO_LONG 255);
+        }
+    }
+
+    let accent_color = req.accent_color.as_deref().map(str::to_lowercase);
+    let user = match db
+        .user()
+        .edit(
+            session.user_id,
+            req.username.as_deref(),
+            accent_color.as_deref(),
+            req.pronouns.as_deref(),
+            req.bio.as_deref(),
+        )
+        .await
+    {
+        Ok(user) => user,
+        Err(user::EditError::AllDiscriminatorsUsed) => return err!(USERNAME_TAKEN),
+        Err(user::EditError::NotFound) => {
+            warn!(
+                "User {} not found even with a valid session",
+                session.user_id
+            );
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(user::EditError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(user)
+}
@@ -0,0 +1,32 @@
+use crate::prelude::*;
+
+/// 2FA is already enabled, and has to be disabled before enrolling again
+const MFA_ALREADY_ENABLED: &str = "MfaAlreadyEnabled";
+
+/// Starts setting up 2FA by giving the logged in user a new secret to add to
+/// their authenticator app. 2FA isn't enabled until the secret is confirmed.
+pub async fn enrol_mfa(session: Session, db: Data<DbPool>) -> impl Responder {
+    let account = match db.user().get_account(session.user_id).await {
+        Ok(Some(account)) => account,
+        Ok(None) => {
+            warn!(
+                "User {} not found even with a valid session",
+                session.user_id
+            );
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    match db.mfa().enrol(session.user_id, &account.email).await {
+        Ok(enrolment) => ok!(enrolment),
+        Err(mfa::EnrolError::AlreadyEnabled) => err!(MFA_ALREADY_ENABLED),
+        Err(mfa::EnrolError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+    }
+}
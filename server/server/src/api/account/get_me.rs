@@ -0,0 +1,19 @@
+use crate::prelude::*;
+
+/// Gets the logged in user's account.
+pub async fn get_me(session: Session, db: Data<DbPool>) -> impl Responder {
+    match db.user().get_account(session.user_id).await {
+        Ok(Some(account)) => ok!(account),
+        Ok(None) => {
+            warn!(
+                "User {} not found even with a valid session",
+                session.user_id
+            );
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+        Err(e) => {
+            error!("Database error: {}", e);
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use crate::prelude::*;
+
+/// How many failed logins are listed.
+const LIMIT: i64 = 50;
+
+/// Lists the most recent failed attempts to log in to the user's account, so
+/// they can tell if someone is trying to guess their password.
+pub async fn list_failed_logins(session: Session, db: Data<DbPool>) -> impl Responder {
+    match db.login_attempt().get_failed(session.user_id, LIMIT).await {
+        Ok(attempts) => ok!(attempts),
+        Err(e) => {
+            error!("Database error: {}", e);
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use crate::prelude::*;
+
+/// Lists every session the user is logged in with.
+pub async fn list_sessions(session: Session, db: Data<DbPool>) -> impl Responder {
+    let sessions = match db
+        .access_token()
+        .get_all(session.user_id, session.token)
+        .await
+    {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(sessions)
+}
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use actix_web::http::header;
 
 // Note that we can't use the username because multiple users can have the
 // same username but different discriminators, and remembering your discrim
@@ -10,16 +11,61 @@ pub struct Params {
 }
 
 #[derive(Serialize, Debug)]
-pub struct Response {
-    access_token: String,
-    user: user::User,
+#[serde(untagged)]
+pub enum Response {
+    LoggedIn {
+        access_token: String,
+        user: user::User,
+    },
+    /// The user has 2FA enabled, so the ticket has to be exchanged for an
+    /// access token at `/account/login/mfa` along with a code.
+    MfaRequired { mfa_ticket: String },
 }
 
 /// The email or password is invalid
 const INVALID_CREDENTIALS: &str = "InvalidCredentials";
 
-pub async fn login(req: Json<Params>, db: Data<DbPool>) -> impl Responder {
-    let user = match db.user().login(&req.email, &req.password).await {
+pub async fn login(
+    req: Json<Params>,
+    http_req: HttpRequest,
+    token_sfgen: Data<Mutex<AccessTokenSnowflakeGen>>,
+    attempt_sfgen: Data<Mutex<LoginAttemptSnowflakeGen>>,
+    clock: Data<dyn time::Clock>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let now = clock.now();
+    let ip = ip::from_request(&http_req);
+    let attempt_id = { attempt_sfgen.lock().await.generate() };
+    let user_agent = http_req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|ua| ua.to_str().ok());
+    // Recorded as a failure before the password is checked, so that
+    // concurrent guesses count against each other
+    match db
+        .login_attempt()
+        .start(attempt_id, &req.email, &ip, user_agent, now)
+        .await
+    {
+        Ok(()) => {}
+        Err(login_attempt::StartError::TooManyAttempts { retry_after }) => {
+            return super::too_many_attempts(retry_after);
+        }
+        Err(login_attempt::StartError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    }
+
+    let result = db.user().login(&req.email, &req.password).await;
+    if result.is_ok() {
+        if let Err(e) = db.login_attempt().succeed(attempt_id).await {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    }
+
+    let user = match result {
         Ok(user) => user,
         Err(user::LoginError::InvalidCredentials) => {
             return err!(UNAUTHORIZED => INVALID_CREDENTIALS)
@@ -37,23 +83,33 @@ pub async fn login(req: Json<Params>, db: Data<DbPool>) -> impl Responder {
         }
     };
 
-    let jwt = match db.access_token().create(user.id).await {
-        Ok(jwt) => jwt,
-        Err(access_token::CreateError::JwtEncoding(e)) => {
-            error!("JWT encoding error: {}", e);
-            return err!(INTERNAL_SERVER_ERROR => ISE);
+    match db.mfa().is_enabled(user.id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return match db.mfa().create_ticket(user.id, now).await {
+                Ok(mfa_ticket) => ok!(Response::MfaRequired { mfa_ticket }),
+                Err(mfa::TicketError::JwtEncoding(e)) => {
+                    error!("JWT encoding error: {}", e);
+                    err!(INTERNAL_SERVER_ERROR => ISE)
+                }
+                Err(mfa::TicketError::DatabaseError(e)) => {
+                    error!("Database error: {}", e);
+                    err!(INTERNAL_SERVER_ERROR => ISE)
+                }
+            };
         }
-        Err(access_token::CreateError::NotInserted) => {
-            error!("Access token not inserted");
-            return err!(INTERNAL_SERVER_ERROR => ISE);
-        }
-        Err(access_token::CreateError::DatabaseError(e)) => {
+        Err(e) => {
             error!("Database error: {}", e);
             return err!(INTERNAL_SERVER_ERROR => ISE);
         }
+    }
+
+    let jwt = match super::create_session(&db, &token_sfgen, &http_req, user.id).await {
+        Ok(jwt) => jwt,
+        Err(response) => return response,
     };
 
-    ok!(Response {
+    ok!(Response::LoggedIn {
         access_token: jwt,
         user
     })
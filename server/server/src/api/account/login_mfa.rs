@@ -0,0 +1,68 @@
+use super::INVALID_MFA_CODE;
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The ticket given by `/account/login`
+    ticket: String,
+    /// A code from the authenticator app, or a recovery code
+    code: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Response {
+    access_token: String,
+    user: user::User,
+}
+
+/// The ticket is invalid, has expired, was already used or had too many wrong
+/// codes given with it, so the user has to log in again
+const INVALID_MFA_TICKET: &str = "InvalidMfaTicket";
+
+/// Finishes logging in a user with 2FA enabled.
+pub async fn login_mfa(
+    req: Json<Params>,
+    http_req: HttpRequest,
+    token_sfgen: Data<Mutex<AccessTokenSnowflakeGen>>,
+    clock: Data<dyn time::Clock>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let user_id = match db.mfa().redeem(&req.ticket, &req.code, clock.now()).await {
+        Ok(user_id) => user_id,
+        Err(mfa::RedeemError::InvalidTicket) => return err!(UNAUTHORIZED => INVALID_MFA_TICKET),
+        Err(mfa::RedeemError::InvalidCode) => return err!(UNAUTHORIZED => INVALID_MFA_CODE),
+        Err(mfa::RedeemError::TooManyAttempts { retry_after }) => {
+            return super::too_many_attempts(retry_after)
+        }
+        Err(mfa::RedeemError::UndecryptableSecret) => {
+            error!("TOTP secret could not be decrypted while redeeming an MFA ticket");
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(mfa::RedeemError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let user = match db.user().get(user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            warn!("User {} not found even with a valid MFA ticket", user_id);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let jwt = match super::create_session(&db, &token_sfgen, &http_req, user.id).await {
+        Ok(jwt) => jwt,
+        Err(response) => return response,
+    };
+
+    ok!(Response {
+        access_token: jwt,
+        user
+    })
+}
@@ -0,0 +1,18 @@
+use crate::prelude::*;
+
+/// Revokes the access token the request was made with.
+pub async fn logout(session: Session, db: Data<DbPool>, gateway: Data<Gateway>) -> impl Responder {
+    match db.access_token().revoke(session.token).await {
+        // The token could have been revoked by another request since the
+        // middleware checked it, which is as good as logging out.
+        Ok(()) | Err(access_token::RevokeError::NotFound) => {}
+        Err(access_token::RevokeError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    gateway.revoke(&[session.token]).await;
+
+    ok!(())
+}
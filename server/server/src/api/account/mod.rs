@@ -1,2 +1,132 @@
+pub mod change_password;
+pub mod confirm_mfa;
+pub mod disable_mfa;
+pub mod edit_me;
+pub mod enrol_mfa;
+pub mod get_me;
+pub mod list_failed_logins;
+pub mod list_sessions;
 pub mod login;
+pub mod login_mfa;
+pub mod logout;
 pub mod register;
+pub mod request_password_reset;
+pub mod resend_verification;
+pub mod reset_password;
+pub mod revoke_other_sessions;
+pub mod revoke_session;
+pub mod verify_email;
+
+use crate::prelude::*;
+use actix_web::http::header;
+
+/// The password is too weak
+pub const PASSWORD_TOO_WEAK: &str = "PasswordTooWeak";
+/// The password is too long
+pub const PASSWORD_TOO_LONG: &str = "PasswordTooLong";
+/// The password is too short
+pub const PASSWORD_TOO_SHORT: &str = "PasswordTooShort";
+/// The password is too common
+pub const PASSWORD_TOO_COMMON: &str = "PasswordTooCommon";
+/// The password is too similar to the username
+pub const PASSWORD_LIKE_USERNAME: &str = "PasswordLikeUsername";
+/// The password is too similar to the email address
+pub const PASSWORD_LIKE_EMAIL: &str = "PasswordLikeEmail";
+
+/// The code from the authenticator app or the recovery code is incorrect
+pub const INVALID_MFA_CODE: &str = "InvalidMfaCode";
+/// 2FA is not enabled
+pub const MFA_NOT_ENABLED: &str = "MfaNotEnabled";
+/// There have been too many failed attempts to log in to the account or from
+/// the IP address, or too many wrong 2FA codes. The data is how many seconds
+/// to wait before trying again
+pub const TOO_MANY_ATTEMPTS: &str = "TooManyAttempts";
+
+/// The response to an attempt to log in that was made too soon after failed
+/// ones. `retry_after` is in milliseconds.
+fn too_many_attempts(retry_after: i64) -> HttpResponse {
+    // Round up so that retrying straight away always works
+    let retry_after = (retry_after + 999) / 1000;
+    let mut response = err!(TOO_MANY_REQUESTS => TOO_MANY_ATTEMPTS map! { retry_after });
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, header::HeaderValue::from(retry_after));
+    response
+}
+
+/// Creates a new session for the user, returning its access token or the
+/// error response to send.
+async fn create_session(
+    db: &DbPool,
+    token_sfgen: &Mutex<AccessTokenSnowflakeGen>,
+    http_req: &HttpRequest,
+    user: Snowflake,
+) -> Result<String, HttpResponse> {
+    let token_id = { token_sfgen.lock().await.generate() };
+    let user_agent = http_req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|ua| ua.to_str().ok());
+    match db.access_token().create(token_id, user, user_agent).await {
+        Ok(jwt) => Ok(jwt),
+        Err(access_token::CreateError::JwtEncoding(e)) => {
+            error!("JWT encoding error: {}", e);
+            Err(err!(INTERNAL_SERVER_ERROR => ISE))
+        }
+        Err(access_token::CreateError::NotInserted) => {
+            error!("Access token not inserted");
+            Err(err!(INTERNAL_SERVER_ERROR => ISE))
+        }
+        Err(access_token::CreateError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            Err(err!(INTERNAL_SERVER_ERROR => ISE))
+        }
+    }
+}
+
+/// Checks a new password is strong enough, returning the error response to
+/// send if it isn't. The error data is the rule that failed and the
+/// password's strength score.
+fn validate_password(
+    password: &password::Password,
+    username: &str,
+    email: &str,
+) -> Result<(), HttpResponse> {
+    match password.validate(username, email) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(match e.rule {
+            password::PasswordError::TooShort => err!(PASSWORD_TOO_SHORT e),
+            password::PasswordError::TooLong => err!(PASSWORD_TOO_LONG e),
+            password::PasswordError::TooCommon => err!(PASSWORD_TOO_COMMON e),
+            password::PasswordError::TooSimilarToUsername => err!(PASSWORD_LIKE_USERNAME e),
+            password::PasswordError::TooSimilarToEmail => err!(PASSWORD_LIKE_EMAIL e),
+            password::PasswordError::TooWeak => err!(PASSWORD_TOO_WEAK e),
+        }),
+    }
+}
+
+/// Emails the user a link to verify their email address in the background.
+fn send_verification_email(mailer: Data<dyn Mailer>, user: &user::User, email: String) {
+    let token = match user::email_verification_token(user.id, &email) {
+        Ok(token) => token,
+        Err(e) => {
+            error!("JWT encoding error: {}", e);
+            return;
+        }
+    };
+
+    let verification = mail::Mail {
+        to: email,
+        subject: "Verify your email address".to_string(),
+        body: format!(
+            "Hi {},\n\nFollow this link within the next day to verify your email address:\n\n{}\n\nIf you didn't make an account, you can ignore this email.",
+            user.username,
+            mail::web_url(&format!("/verify-email?token={token}"))
+        ),
+    };
+    actix_web::rt::spawn(async move {
+        if let Err(e) = mail::send(mailer, verification).await {
+            error!("Could not send verification email: {}", e);
+        }
+    });
+}
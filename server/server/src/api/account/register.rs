@@ -24,22 +24,13 @@ const USERNAME_TAKEN: &str = "UsernameTaken";
 const INVALID_EMAIL: &str = "InvalidEmail";
 /// The email address is already taken
 const EMAIL_TAKEN: &str = "EmailTaken";
-/// The password is too weak
-const PASSWORD_TOO_WEAK: &str = "PasswordTooWeak";
-/// The password is too long
-const PASSWORD_TOO_LONG: &str = "PasswordTooLong";
-/// The password is too short
-const PASSWORD_TOO_SHORT: &str = "PasswordTooShort";
-/// The password is too common
-const PASSWORD_TOO_COMMON: &str = "PasswordTooCommon";
-/// The password is too similar to the username
-const PASSWORD_LIKE_USERNAME: &str = "PasswordLikeUsername";
-/// The password is too similar to the email address
-const PASSWORD_LIKE_EMAIL: &str = "PasswordLikeEmail";
 
 pub async fn register(
     req: Json<Params>,
+    http_req: HttpRequest,
     user_sfgen: Data<Mutex<UserSnowflakeGen>>,
+    token_sfgen: Data<Mutex<AccessTokenSnowflakeGen>>,
+    mailer: Data<dyn Mailer>,
     db: Data<DbPool>,
 ) -> impl Responder {
     if !validation::validate_username(&req.username) {
@@ -52,16 +43,9 @@ pub async fn register(
 
     // Validate the password
     let password = password::Password::new(&req.password);
-    if let Err(e) = password.validate(&req.username, &req.email) {
-        return match e {
-            password::PasswordError::TooShort => err!(PASSWORD_TOO_SHORT),
-            password::PasswordError::TooLong => err!(PASSWORD_TOO_LONG),
-            password::PasswordError::TooCommon => err!(PASSWORD_TOO_COMMON),
-            password::PasswordError::TooSimilarToUsername => err!(PASSWORD_LIKE_USERNAME),
-            password::PasswordError::TooSimilarToEmail => err!(PASSWORD_LIKE_EMAIL),
-            password::PasswordError::TooWeak => err!(PASSWORD_TOO_WEAK),
-        };
-    };
+    if let Err(response) = super::validate_password(&password, &req.username, &req.email) {
+        return response;
+    }
 
     let user_id = { user_sfgen.lock().await.generate() };
     let user = match db
@@ -95,21 +79,13 @@ pub async fn register(
         }
     };
 
-    let jwt = match db.access_token().create(user.id).await {
+    let jwt = match super::create_session(&db, &token_sfgen, &http_req, user.id).await {
         Ok(jwt) => jwt,
-        Err(access_token::CreateError::JwtEncoding(_)) => {
-            return err!(INTERNAL_SERVER_ERROR => ISE);
-        }
-        Err(access_token::CreateError::NotInserted) => {
-            error!("Access token not inserted");
-            return err!(INTERNAL_SERVER_ERROR => ISE);
-        }
-        Err(access_token::CreateError::DatabaseError(e)) => {
-            error!("Database error: {}", e);
-            return err!(INTERNAL_SERVER_ERROR => ISE);
-        }
+        Err(response) => return response,
     };
 
+    super::send_verification_email(mailer, &user, req.into_inner().email);
+
     ok!(Response {
         access_token: jwt,
         user,
@@ -0,0 +1,135 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The email address of the account to reset the password of
+    email: String,
+}
+
+/// How many reset emails can be sent to one account, however many IP
+/// addresses ask for them. 3 an hour.
+const RESET_EMAILS: rate_limit::Limit = rate_limit::Limit {
+    capacity: 3,
+    period: 60 * 60 * 1000,
+};
+
+/// Emails a password reset link to the account with the email address.
+///
+/// Always succeeds, so that it can't be used to find out whether someone
+/// has an account or has asked for too many emails.
+pub async fn request_password_reset(
+    req: Json<Params>,
+    mailer: Data<dyn Mailer>,
+    store: Data<dyn rate_limit::Store>,
+    clock: Data<dyn time::Clock>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let user = match db.user().get_by_email(&req.email).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return ok!(()),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    if !may_send(&**store, user.id, clock.now()) {
+        return ok!(());
+    }
+
+    let token = match db.password_reset().create(user.id).await {
+        Ok(token) => token,
+        Err(password_reset::CreateError::NotInserted) => {
+            error!("Password reset not inserted");
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(password_reset::CreateError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let mail = reset_mail(&user, req.into_inner().email, &token);
+    // Send in the background so the response time doesn't give away whether
+    // the account exists
+    actix_web::rt::spawn(async move {
+        if let Err(e) = mail::send(mailer, mail).await {
+            error!("Could not send password reset email: {}", e);
+        }
+    });
+
+    ok!(())
+}
+
+/// Takes one of the account's reset emails, returning whether there was one
+/// left.
+fn may_send(store: &dyn rate_limit::Store, user_id: Snowflake, now: i64) -> bool {
+    store
+        .take(&format!("password_reset:{user_id}"), RESET_EMAILS, now)
+        .allowed
+}
+
+/// Writes the email with the link to reset a user's password.
+fn reset_mail(user: &user::User, to: String, token: &str) -> mail::Mail {
+    mail::Mail {
+        to,
+        subject: "Reset your password".to_string(),
+        body: format!(
+            "Hi {},\n\nSomeone asked to reset the password of your account. If it was you, follow this link within the next hour to choose a new password:\n\n{}\n\nIf it wasn't you, you can ignore this email.",
+            user.username,
+            mail::web_url(&format!("/reset-password?token={token}"))
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn user() -> user::User {
+        user::User {
+            id: Snowflake::from_number(1 << 22),
+            username: "alice".to_string(),
+            discrim: 1,
+            profile_img_id: None,
+            accent_color: None,
+            pronouns: None,
+            bio: None,
+        }
+    }
+
+    #[actix_web::test]
+    async fn sends_link_with_token() {
+        let mailer = Arc::new(mail::MemoryMailer::default());
+        let mail = reset_mail(&user(), "alice@example.com".to_string(), "abc123");
+        mail::send(Data::from(mailer.clone() as Arc<dyn Mailer>), mail)
+            .await
+            .unwrap();
+
+        let sent = mailer.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "alice@example.com");
+        assert!(sent[0].body.contains("Hi alice"));
+        assert!(sent[0].body.contains("/reset-password?token=abc123"));
+    }
+
+    #[test]
+    fn limits_emails_per_account() {
+        let store = rate_limit::MemoryStore::default();
+        let alice = user().id;
+        let bob = Snowflake::from_number(2 << 22);
+
+        for _ in 0..3 {
+            assert!(may_send(&store, alice, 0));
+        }
+        assert!(!may_send(&store, alice, 0));
+        // Other accounts have their own limit
+        assert!(may_send(&store, bob, 0));
+
+        // One email comes back every 20 minutes
+        assert!(!may_send(&store, alice, 20 * 60 * 1000 - 1));
+        assert!(may_send(&store, alice, 20 * 60 * 1000));
+        assert!(!may_send(&store, alice, 20 * 60 * 1000));
+    }
+}
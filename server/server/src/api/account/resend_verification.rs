@@ -0,0 +1,33 @@
+use crate::prelude::*;
+
+/// The email address has already been verified
+pub const ALREADY_VERIFIED: &str = "AlreadyVerified";
+
+/// Sends the logged in user another email verification link.
+pub async fn resend_verification(
+    session: Session,
+    mailer: Data<dyn Mailer>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let account = match db.user().get_account(session.user_id).await {
+        Ok(Some(account)) => account,
+        Ok(None) => {
+            warn!(
+                "User {} not found even with a valid session",
+                session.user_id
+            );
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if account.email_verified {
+        return err!(ALREADY_VERIFIED);
+    }
+
+    super::send_verification_email(mailer, &account.user, account.email);
+
+    ok!(())
+}
@@ -0,0 +1,56 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The token from the password reset email
+    token: String,
+    /// The password to change to
+    password: String,
+}
+
+/// The reset token does not exist, was already used, or has expired
+pub const INVALID_RESET_TOKEN: &str = "InvalidResetToken";
+
+/// Sets a new password using a password reset token, logging out every
+/// session.
+pub async fn reset_password(
+    req: Json<Params>,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let user_id = match db.password_reset().get_user(&req.token).await {
+        Ok(Some(user_id)) => user_id,
+        Ok(None) => return err!(INVALID_RESET_TOKEN),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    let account = match db.user().get_account(user_id).await {
+        Ok(Some(account)) => account,
+        Ok(None) => return err!(INVALID_RESET_TOKEN),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let password = password::Password::new(&req.password);
+    if let Err(response) =
+        super::validate_password(&password, &account.user.username, &account.email)
+    {
+        return response;
+    }
+
+    let revoked = match db.password_reset().consume(&req.token, password).await {
+        Ok(revoked) => revoked,
+        Err(password_reset::ConsumeError::InvalidToken) => return err!(INVALID_RESET_TOKEN),
+        Err(password_reset::ConsumeError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    gateway.revoke(&revoked).await;
+
+    ok!(())
+}
@@ -0,0 +1,24 @@
+use crate::prelude::*;
+
+/// Logs out of every session except the one the request was made with.
+pub async fn revoke_other_sessions(
+    session: Session,
+    db: Data<DbPool>,
+    gateway: Data<Gateway>,
+) -> impl Responder {
+    let revoked = match db
+        .access_token()
+        .revoke_all_except(session.user_id, session.token)
+        .await
+    {
+        Ok(revoked) => revoked,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    gateway.revoke(&revoked).await;
+
+    ok!(())
+}
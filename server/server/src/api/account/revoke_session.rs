@@ -0,0 +1,38 @@
+use crate::prelude::*;
+
+/// The session does not exist or belongs to another user
+pub const SESSION_NOT_FOUND: &str = "SessionNotFound";
+
+/// Logs out of one of the user's sessions.
+pub async fn revoke_session(
+    session_id: web::Path<Snowflake>,
+    session: Session,
+    db: Data<DbPool>,
+    gateway: Data<Gateway>,
+) -> impl Responder {
+    let token = match db
+        .access_token()
+        .get_token(session.user_id, *session_id)
+        .await
+    {
+        Ok(Some(token)) => token,
+        Ok(None) => return err!(SESSION_NOT_FOUND),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    match db.access_token().revoke(token).await {
+        Ok(()) => {}
+        Err(access_token::RevokeError::NotFound) => return err!(SESSION_NOT_FOUND),
+        Err(access_token::RevokeError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    gateway.revoke(&[token]).await;
+
+    ok!(())
+}
@@ -0,0 +1,26 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The token from the verification email
+    token: String,
+}
+
+/// The verification token is invalid, has expired, or is for an email
+/// address the user no longer uses
+pub const INVALID_VERIFICATION_TOKEN: &str = "InvalidVerificationToken";
+/// The email address has already been verified
+pub const ALREADY_VERIFIED: &str = "AlreadyVerified";
+
+/// Verifies the email address the link was sent to.
+pub async fn verify_email(req: Json<Params>, db: Data<DbPool>) -> impl Responder {
+    match db.user().verify_email(&req.token).await {
+        Ok(_) => ok!(()),
+        Err(user::VerifyEmailError::InvalidToken) => err!(INVALID_VERIFICATION_TOKEN),
+        Err(user::VerifyEmailError::AlreadyVerified) => err!(ALREADY_VERIFIED),
+        Err(user::VerifyEmailError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// Why the user was banned, up to 512 characters
+    #[serde(default)]
+    reason: Option<String>,
+    /// How many seconds the ban lasts for, up to a year. Permanent if not
+    /// given
+    #[serde(default)]
+    duration: Option<i64>,
+    /// How many days of the user's messages in the guild to delete, up to 7
+    #[serde(default)]
+    delete_message_days: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Response {
+    /// How many of the user's messages were deleted
+    deleted_messages: usize,
+}
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The user being banned does not exist
+pub const USER_NOT_FOUND: &str = "UserNotFound";
+/// The user does not have permission to ban members, or the member is not
+/// ranked below their highest role
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+/// Users cannot ban themselves
+pub const CANNOT_BAN_SELF: &str = "CannotBanSelf";
+/// The reason was longer than 512 characters
+pub const REASON_TOO_LONG: &str = "ReasonTooLong";
+/// `duration` was not between 1 second and a year
+pub const INVALID_DURATION: &str = "InvalidDuration";
+/// `delete_message_days` was more than 7
+pub const INVALID_DELETE_MESSAGE_DAYS: &str = "InvalidDeleteMessageDays";
+
+/// The longest a ban can last for, in seconds.
+const MAX_DURATION: i64 = 365 * 24 * 60 * 60;
+
+/// Bans a user from a guild, removing them from it if they are a member.
+/// They won't be able to join again until they are unbanned or the ban
+/// expires.
+pub async fn create(
+    path: web::Path<(Snowflake, Snowflake)>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, user_id) = path.into_inner();
+
+    if req
+        .reason
+        .as_ref()
+        .map_or(false, |reason| reason.len() > 512)
+    {
+        return err!(REASON_TOO_LONG 512);
+    }
+    if let Some(duration) = req.duration {
+        if !(1..=MAX_DURATION).contains(&duration) {
+            return err!(INVALID_DURATION map!{ min => 1, max => MAX_DURATION });
+        }
+    }
+    if req.delete_message_days > ban::MAX_PURGE_DAYS {
+        return err!(INVALID_DELETE_MESSAGE_DAYS map!{ min => 0, max => ban::MAX_PURGE_DAYS });
+    }
+    if user_id == session.user_id {
+        return err!(CANNOT_BAN_SELF);
+    }
+
+    let perms = match db.guild().get_permissions(guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::BAN_MEMBERS) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    // Like kicking, members can only be banned by those ranked above them.
+    // Users who aren't members can be banned by anyone who can ban.
+    match db.guild().get_permissions(guild_id, user_id).await {
+        Ok(target) if !perms.outranks(target.rank) => {
+            return err!(UNAUTHORIZED => PERMISSION_DENIED)
+        }
+        Ok(_) | Err(guild::GetPermissionsError::NotInGuild) => {}
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let banned = match db
+        .ban()
+        .ban(
+            guild_id,
+            user_id,
+            session.user_id,
+            req.reason.as_deref(),
+            req.duration.map(|duration| duration * 1000),
+            req.delete_message_days,
+        )
+        .await
+    {
+        Ok(banned) => banned,
+        Err(ban::BanError::UserNotFound) => return err!(USER_NOT_FOUND),
+        Err(ban::BanError::IsOwner) => return err!(UNAUTHORIZED => PERMISSION_DENIED),
+        Err(ban::BanError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    if banned.was_member {
+        gateway
+            .dispatch(&[user_id], &gateway::Event::GuildDelete { guild_id })
+            .await;
+        let event = gateway::Event::GuildMemberRemove { guild_id, user_id };
+        gateway.dispatch_to_guild(&db, guild_id, &event).await;
+    }
+    let mut deleted = std::collections::BTreeMap::<_, Vec<_>>::new();
+    for &(channel_id, message_id) in &banned.deleted_messages {
+        deleted.entry(channel_id).or_default().push(message_id);
+    }
+    for (channel_id, message_ids) in deleted {
+        let event = gateway::Event::MessageDeleteBulk {
+            channel_id,
+            message_ids,
+        };
+        gateway.dispatch_to_channel(&db, channel_id, &event).await;
+    }
+
+    ok!(Response {
+        deleted_messages: banned.deleted_messages.len(),
+    })
+}
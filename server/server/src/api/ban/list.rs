@@ -0,0 +1,35 @@
+use crate::prelude::*;
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The user does not have permission to ban members
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Returns every ban in a guild that hasn't expired, newest first.
+pub async fn list(
+    guild_id: web::Path<Snowflake>,
+    session: Session,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let perms = match db.guild().get_permissions(*guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::BAN_MEMBERS) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    let bans = match db.ban().get_all(*guild_id).await {
+        Ok(bans) => bans,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(bans)
+}
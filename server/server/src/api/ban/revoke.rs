@@ -0,0 +1,40 @@
+use crate::prelude::*;
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The user does not have permission to ban members
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+/// The user is not banned from the guild
+pub const BAN_NOT_FOUND: &str = "BanNotFound";
+
+/// Lifts a user's ban from a guild, letting them join it again.
+pub async fn revoke(
+    path: web::Path<(Snowflake, Snowflake)>,
+    session: Session,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, user_id) = path.into_inner();
+
+    let perms = match db.guild().get_permissions(guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::BAN_MEMBERS) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    match db.ban().unban(guild_id, user_id).await {
+        Ok(()) => {}
+        Err(ban::UnbanError::NotFound) => return err!(BAN_NOT_FOUND),
+        Err(ban::UnbanError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(())
+}
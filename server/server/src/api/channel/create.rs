@@ -2,51 +2,55 @@ use crate::prelude::*;
 
 #[derive(Deserialize, Debug)]
 pub struct Params {
-    /// Name of the channel, between 2 and 60 characters
+    /// Name of the channel, between 2 and 32 characters, or 1 and 32 for a
+    /// category
     name: String,
-    /// The channel to place this channel before
+    /// Whether to create a text channel or a category. Defaults to a text
+    /// channel
+    #[serde(default = "default_kind")]
+    kind: channel::ChannelKind,
+    /// The category to create the channel in
+    #[serde(default)]
+    parent_id: Option<Snowflake>,
+    /// The channel to place this channel before. The channel is created in
+    /// the same category as it
     #[serde(default)]
     place_before: Option<Snowflake>,
 }
 
+fn default_kind() -> channel::ChannelKind {
+    channel::ChannelKind::Text
+}
+
 #[derive(Serialize, Debug)]
 pub struct Response {
     /// The ID of the created channel
     id: Snowflake,
 }
 
-/// The name was shorter than 2 character
-pub const NAME_TOO_SHORT: &str = "NameTooShort";
-/// The name was longer than 60 characters
-pub const NAME_TOO_LONG: &str = "NameTooLong";
-/// The name did not conform to the expected style
-pub const NAME_INVALID: &str = "NameInvalid";
 /// The guild was not found or the user is not in the guild
 pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
 /// The user does not have permission to create a channel
 pub const PERMISSION_DENIED: &str = "PermissionDenied";
 /// The `place_before` channel does not exist
 pub const PLACE_BEFORE_NOT_FOUND: &str = "PlaceBeforeNotFound";
+/// The `place_before` channel is not in the `parent_id` category
+pub const PLACE_BEFORE_NOT_IN_PARENT: &str = "PlaceBeforeNotInParent";
+/// The `parent_id` channel does not exist or is not a category
+pub const PARENT_NOT_FOUND: &str = "ParentNotFound";
+/// Categories cannot be put in other categories
+pub const CATEGORY_NESTED: &str = "CategoryNested";
 
 pub async fn create(
     guild_id: web::Path<Snowflake>,
     req: Json<Params>,
     session: Session,
     channel_sfgen: Data<Mutex<ChannelSnowflakeGen>>,
+    gateway: Data<Gateway>,
     db: Data<DbPool>,
 ) -> impl Responder {
-    if req.name.len() < 2 {
-        return err!(NAME_TOO_SHORT 2);
-    }
-    if req.name.len() > 32 {
-        return err!(NAME_TOO_LONG 32);
-    }
-
-    let matches_regex = regex::Regex::new(r"^[a-z][a-z0-9-]+[a-z0-9]$")
-        .unwrap()
-        .is_match(&req.name);
-    if !matches_regex {
-        return err!(NAME_INVALID "^[a-z][a-z0-9-]+[a-z0-9]$");
+    if let Err(response) = super::check_name(req.kind, &req.name) {
+        return response;
     }
 
     // Check the user has permission to create a channel
@@ -60,21 +64,33 @@ pub async fn create(
             return err!(INTERNAL_SERVER_ERROR => ISE);
         }
     };
-    if !perms {
+    if !perms.contains(role::Permissions::MANAGE_CHANNELS) {
         return err!(UNAUTHORIZED => PERMISSION_DENIED);
     }
 
     // Add a new channel to the guild
     let channel_id = { channel_sfgen.lock().await.generate() };
-    match db
+    let channel = match db
         .channel()
-        .create(channel_id, *guild_id, &req.name, req.place_before)
+        .create(
+            channel_id,
+            *guild_id,
+            &req.name,
+            req.kind,
+            req.parent_id,
+            req.place_before,
+        )
         .await
     {
-        Ok(()) => {}
+        Ok(channel) => channel,
         Err(channel::CreateError::NextChannelDoesNotExist) => {
             return err!(PLACE_BEFORE_NOT_FOUND);
         }
+        Err(channel::CreateError::NextChannelNotInParent) => {
+            return err!(PLACE_BEFORE_NOT_IN_PARENT);
+        }
+        Err(channel::CreateError::ParentNotFound) => return err!(PARENT_NOT_FOUND),
+        Err(channel::CreateError::CategoryNested) => return err!(CATEGORY_NESTED),
         Err(channel::CreateError::NotInserted) => {
             warn!("Channel not inserted into database");
             return err!(INTERNAL_SERVER_ERROR => ISE);
@@ -85,5 +101,11 @@ pub async fn create(
         }
     };
 
+    let event = gateway::Event::ChannelCreate {
+        guild_id: *guild_id,
+        channel,
+    };
+    gateway.dispatch_to_guild(&db, *guild_id, &event).await;
+
     ok!(Response { id: channel_id })
 }
@@ -0,0 +1,53 @@
+use crate::prelude::*;
+
+/// The channel was not found in the guild, or the user can't see it
+pub const CHANNEL_NOT_FOUND: &str = "ChannelNotFound";
+/// The user does not have permission to manage the channel
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+/// The channel is the last one in the guild
+pub const LAST_CHANNEL: &str = "LastChannel";
+
+/// Deletes a channel along with every message in it. A guild's last channel
+/// can't be deleted.
+pub async fn delete(
+    path: web::Path<(Snowflake, Snowflake)>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, channel_id) = path.into_inner();
+
+    let perms = match db
+        .channel()
+        .get_permissions(guild_id, channel_id, session.user_id)
+        .await
+    {
+        Ok(perms) if perms.contains(role::Permissions::VIEW_CHANNELS) => perms,
+        Ok(_) | Err(channel::GetPermissionsError::NotFound) => return err!(CHANNEL_NOT_FOUND),
+        Err(channel::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_CHANNELS) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    match db.channel().delete(guild_id, channel_id).await {
+        Ok(()) => {}
+        Err(channel::DeleteError::NotFound) => return err!(CHANNEL_NOT_FOUND),
+        Err(channel::DeleteError::LastChannel) => return err!(LAST_CHANNEL),
+        Err(channel::DeleteError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let event = gateway::Event::ChannelDelete {
+        guild_id,
+        channel_id,
+    };
+    gateway.dispatch_to_guild(&db, guild_id, &event).await;
+
+    ok!(())
+}
@@ -0,0 +1,64 @@
+use crate::prelude::*;
+
+/// The channel was not found or the user did not have permission to view it.
+pub const CHANNEL_NOT_FOUND: &str = "ChannelNotFound";
+/// The channel has no overwrite for the role or member
+pub const OVERWRITE_NOT_FOUND: &str = "OverwriteNotFound";
+/// The user does not have permission to manage the channel
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Removes the permission overwrite for a role or member.
+pub async fn delete_overwrite(
+    path: web::Path<(Snowflake, Snowflake, Snowflake)>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, channel_id, target_id) = path.into_inner();
+
+    // Check the user has permission to manage this channel
+    let perms = match db
+        .channel()
+        .get_permissions(guild_id, channel_id, session.user_id)
+        .await
+    {
+        Ok(perms) if perms.contains(role::Permissions::VIEW_CHANNELS) => perms,
+        Ok(_) | Err(channel::GetPermissionsError::NotFound) => return err!(CHANNEL_NOT_FOUND),
+        Err(channel::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_CHANNELS) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    // Who could see the channel before the change
+    let before = match db.channel().get_recipient_ids(channel_id).await {
+        Ok(recipients) => recipients,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    match db.channel_overwrite().delete(channel_id, target_id).await {
+        Ok(()) => {}
+        Err(channel_overwrite::DeleteError::NotFound) => return err!(OVERWRITE_NOT_FOUND),
+        Err(channel_overwrite::DeleteError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let event = gateway::Event::ChannelOverwriteDelete {
+        guild_id,
+        channel_id,
+        target_id,
+    };
+    gateway
+        .dispatch_access_change(&db, guild_id, channel_id, &before, &event)
+        .await;
+
+    ok!(())
+}
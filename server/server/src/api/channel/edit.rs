@@ -0,0 +1,96 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The new name of the channel, between 2 and 32 characters, or 1 and 32
+    /// for a category
+    #[serde(default)]
+    name: Option<String>,
+    /// The new topic of the channel, up to 1024 characters. An empty topic
+    /// clears it
+    #[serde(default)]
+    topic: Option<String>,
+}
+
+/// The topic was longer than 1024 characters
+pub const TOPIC_TOO_LONG: &str = "TopicTooLong";
+/// The channel was not found in the guild, or the user can't see it
+pub const CHANNEL_NOT_FOUND: &str = "ChannelNotFound";
+/// The user does not have permission to manage the channel
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Renames a channel or changes its topic.
+pub async fn edit(
+    path: web::Path<(Snowflake, Snowflake)>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, channel_id) = path.into_inner();
+
+    if req
+        .topic
+        .as_ref()
+        .map_or(false, |topic| topic.chars().count() > 1024)
+    {
+        return err!(TOPIC_TOO_LONG 1024);
+    }
+
+    let perms = match db
+        .channel()
+        .get_permissions(guild_id, channel_id, session.user_id)
+        .await
+    {
+        Ok(perms) if perms.contains(role::Permissions::VIEW_CHANNELS) => perms,
+        Ok(_) | Err(channel::GetPermissionsError::NotFound) => return err!(CHANNEL_NOT_FOUND),
+        Err(channel::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_CHANNELS) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    // Categories are named differently to text channels
+    if let Some(name) = &req.name {
+        let kind = match db.channel().get(guild_id, channel_id).await {
+            Ok(Some(channel)) => channel.kind,
+            Ok(None) => return err!(CHANNEL_NOT_FOUND),
+            Err(e) => {
+                error!("Database error: {}", e);
+                return err!(INTERNAL_SERVER_ERROR => ISE);
+            }
+        };
+        if let Err(response) = super::check_name(kind, name) {
+            return response;
+        }
+    }
+
+    let channel = match db
+        .channel()
+        .edit(
+            guild_id,
+            channel_id,
+            req.name.as_deref(),
+            req.topic.as_deref(),
+        )
+        .await
+    {
+        Ok(channel) => channel,
+        Err(channel::EditError::NotFound) => return err!(CHANNEL_NOT_FOUND),
+        Err(channel::EditError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let event = gateway::Event::ChannelUpdate {
+        guild_id,
+        channel: channel.clone(),
+    };
+    gateway.dispatch_to_channel(&db, channel_id, &event).await;
+
+    ok!(channel)
+}
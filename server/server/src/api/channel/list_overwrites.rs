@@ -0,0 +1,37 @@
+use crate::prelude::*;
+
+/// The channel was not found or the user did not have permission to view it.
+pub const CHANNEL_NOT_FOUND: &str = "ChannelNotFound";
+
+/// Returns every permission overwrite on a channel.
+pub async fn list_overwrites(
+    path: web::Path<(Snowflake, Snowflake)>,
+    session: Session,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, channel_id) = path.into_inner();
+
+    // Check the user can see this channel
+    match db
+        .channel()
+        .get_permissions(guild_id, channel_id, session.user_id)
+        .await
+    {
+        Ok(perms) if perms.contains(role::Permissions::VIEW_CHANNELS) => {}
+        Ok(_) | Err(channel::GetPermissionsError::NotFound) => return err!(CHANNEL_NOT_FOUND),
+        Err(channel::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let overwrites = match db.channel_overwrite().get_all(channel_id).await {
+        Ok(overwrites) => overwrites,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(overwrites)
+}
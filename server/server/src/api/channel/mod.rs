@@ -1 +1,48 @@
 pub mod create;
+pub mod delete;
+pub mod delete_overwrite;
+pub mod edit;
+pub mod list_overwrites;
+pub mod relocate;
+pub mod reorder;
+pub mod set_collapsed;
+pub mod upsert_overwrite;
+
+use crate::prelude::*;
+
+/// The name was shorter than 2 character
+pub const NAME_TOO_SHORT: &str = "NameTooShort";
+/// The name was longer than 32 characters
+pub const NAME_TOO_LONG: &str = "NameTooLong";
+/// The name did not conform to the expected style
+pub const NAME_INVALID: &str = "NameInvalid";
+
+/// Checks a channel name is between 2 and 32 characters of lowercase
+/// letters, numbers and dashes, returning the error response to send if not.
+/// Category names can be any 1 to 32 characters.
+fn check_name(kind: channel::ChannelKind, name: &str) -> Result<(), HttpResponse> {
+    if kind == channel::ChannelKind::Category {
+        if name.trim().is_empty() {
+            return Err(err!(NAME_TOO_SHORT 1));
+        }
+        if name.chars().count() > 32 {
+            return Err(err!(NAME_TOO_LONG 32));
+        }
+        return Ok(());
+    }
+
+    if name.len() < 2 {
+        return Err(err!(NAME_TOO_SHORT 2));
+    }
+    if name.len() > 32 {
+        return Err(err!(NAME_TOO_LONG 32));
+    }
+
+    let matches_regex = regex::Regex::new(r"^[a-z][a-z0-9-]+[a-z0-9]$")
+        .unwrap()
+        .is_match(name);
+    if !matches_regex {
+        return Err(err!(NAME_INVALID "^[a-z][a-z0-9-]+[a-z0-9]$"));
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The category to move the channel into. Moves it out of its category
+    /// if not given
+    #[serde(default)]
+    parent_id: Option<Snowflake>,
+    /// The channel to place this channel before, which must be in the same
+    /// category. Placed at the bottom of the category if not given
+    #[serde(default)]
+    place_before: Option<Snowflake>,
+}
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The channel was not found in the guild
+pub const CHANNEL_NOT_FOUND: &str = "ChannelNotFound";
+/// The user does not have permission to manage channels
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+/// The `parent_id` channel does not exist or is not a category
+pub const PARENT_NOT_FOUND: &str = "ParentNotFound";
+/// The `place_before` channel does not exist or is not in the `parent_id`
+/// category
+pub const PLACE_BEFORE_NOT_IN_PARENT: &str = "PlaceBeforeNotInParent";
+/// Categories cannot be put in other categories
+pub const CATEGORY_NESTED: &str = "CategoryNested";
+
+/// Moves a channel into or out of a category, or within the one it's in.
+pub async fn relocate(
+    path: web::Path<(Snowflake, Snowflake)>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, channel_id) = path.into_inner();
+
+    let perms = match db.guild().get_permissions(guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_CHANNELS) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    match db
+        .channel()
+        .move_channel(guild_id, channel_id, req.parent_id, req.place_before)
+        .await
+    {
+        Ok(()) => {}
+        Err(channel::MoveError::NotFound) => return err!(CHANNEL_NOT_FOUND),
+        Err(channel::MoveError::ParentNotFound) => return err!(PARENT_NOT_FOUND),
+        Err(channel::MoveError::NextChannelNotInParent) => return err!(PLACE_BEFORE_NOT_IN_PARENT),
+        Err(channel::MoveError::CategoryNested) => return err!(CATEGORY_NESTED),
+        Err(channel::MoveError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    match db.channel().get(guild_id, channel_id).await {
+        Ok(Some(channel)) => {
+            let event = gateway::Event::ChannelUpdate { guild_id, channel };
+            gateway.dispatch_to_channel(&db, channel_id, &event).await;
+        }
+        Ok(None) => warn!("Channel {} not found after moving it", channel_id),
+        Err(e) => error!("Database error: {}", e),
+    }
+    gateway.dispatch_channel_order(&db, guild_id).await;
+
+    ok!(())
+}
@@ -0,0 +1,54 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// Every channel in the guild the user can view, from top to bottom.
+    /// Channels they can't view keep their places
+    channels: Vec<Snowflake>,
+}
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The channels given were not exactly the channels in the guild the user
+/// can view
+pub const CHANNELS_MISMATCH: &str = "ChannelsMismatch";
+/// The user does not have permission to manage channels
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Moves every channel the user can view in a guild at once.
+pub async fn reorder(
+    guild_id: web::Path<Snowflake>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let perms = match db.guild().get_permissions(*guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_CHANNELS) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    match db
+        .channel()
+        .reorder(*guild_id, session.user_id, &req.channels)
+        .await
+    {
+        Ok(()) => {}
+        Err(channel::ReorderError::Mismatch) => return err!(CHANNELS_MISMATCH),
+        Err(channel::ReorderError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    gateway.dispatch_channel_order(&db, *guild_id).await;
+
+    ok!(())
+}
@@ -0,0 +1,58 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// Whether the category should be collapsed
+    collapsed: bool,
+}
+
+/// The category was not found in the guild, or the user can't see it
+pub const CATEGORY_NOT_FOUND: &str = "CategoryNotFound";
+
+/// Collapses or expands a category in the user's sidebar. Only affects the
+/// user who collapsed it.
+pub async fn set_collapsed(
+    path: web::Path<(Snowflake, Snowflake)>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, channel_id) = path.into_inner();
+
+    match db
+        .channel()
+        .get_permissions(guild_id, channel_id, session.user_id)
+        .await
+    {
+        Ok(perms) if perms.contains(role::Permissions::VIEW_CHANNELS) => {}
+        Ok(_) | Err(channel::GetPermissionsError::NotFound) => return err!(CATEGORY_NOT_FOUND),
+        Err(channel::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    match db
+        .channel()
+        .set_collapsed(guild_id, channel_id, session.user_id, req.collapsed)
+        .await
+    {
+        Ok(()) => {}
+        Err(channel::SetCollapsedError::NotFound) => return err!(CATEGORY_NOT_FOUND),
+        Err(channel::SetCollapsedError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    // Keep the user's other sessions in sync
+    let event = gateway::Event::CategoryCollapse {
+        guild_id,
+        channel_id,
+        collapsed: req.collapsed,
+    };
+    gateway.dispatch(&[session.user_id], &event).await;
+
+    ok!(())
+}
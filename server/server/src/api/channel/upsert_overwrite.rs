@@ -0,0 +1,90 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// Whether the target is a role or a member
+    kind: channel_overwrite::OverwriteKind,
+    /// The permissions to grant in this channel
+    #[serde(default = "role::Permissions::empty")]
+    allow: role::Permissions,
+    /// The permissions to take away in this channel
+    #[serde(default = "role::Permissions::empty")]
+    deny: role::Permissions,
+}
+
+/// The channel was not found or the user did not have permission to view it.
+pub const CHANNEL_NOT_FOUND: &str = "ChannelNotFound";
+/// The role or member the overwrite is for is not in the guild
+pub const TARGET_NOT_FOUND: &str = "TargetNotFound";
+/// A permission was both allowed and denied, or `ADMINISTRATOR` was included
+pub const INVALID_PERMISSIONS: &str = "InvalidPermissions";
+/// The user does not have permission to manage the channel, or tried to
+/// allow or deny a permission they do not have in it themselves
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Creates or replaces the permission overwrite for a role or member.
+pub async fn upsert_overwrite(
+    path: web::Path<(Snowflake, Snowflake, Snowflake)>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, channel_id, target_id) = path.into_inner();
+
+    if req.allow.intersects(req.deny)
+        || (req.allow | req.deny).contains(role::Permissions::ADMINISTRATOR)
+    {
+        return err!(INVALID_PERMISSIONS);
+    }
+
+    // Check the user has permission to manage this channel
+    let perms = match db
+        .channel()
+        .get_permissions(guild_id, channel_id, session.user_id)
+        .await
+    {
+        Ok(perms) if perms.contains(role::Permissions::VIEW_CHANNELS) => perms,
+        Ok(_) | Err(channel::GetPermissionsError::NotFound) => return err!(CHANNEL_NOT_FOUND),
+        Err(channel::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_CHANNELS) || !perms.contains(req.allow | req.deny)
+    {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    // Who could see the channel before the change
+    let before = match db.channel().get_recipient_ids(channel_id).await {
+        Ok(recipients) => recipients,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let overwrite = match db
+        .channel_overwrite()
+        .upsert(channel_id, target_id, req.kind, req.allow, req.deny)
+        .await
+    {
+        Ok(overwrite) => overwrite,
+        Err(channel_overwrite::UpsertError::TargetNotFound) => return err!(TARGET_NOT_FOUND),
+        Err(channel_overwrite::UpsertError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let event = gateway::Event::ChannelOverwriteUpdate {
+        guild_id,
+        overwrite: overwrite.clone(),
+    };
+    gateway
+        .dispatch_access_change(&db, guild_id, channel_id, &before, &event)
+        .await;
+
+    ok!(overwrite)
+}
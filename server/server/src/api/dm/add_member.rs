@@ -0,0 +1,71 @@
+use crate::prelude::*;
+
+/// The DM was not found or the user is not a member of it
+pub const DM_NOT_FOUND: &str = "DmNotFound";
+/// Members can only be added to group DMs
+pub const NOT_GROUP_DM: &str = "NotGroupDm";
+/// The user being added does not exist
+pub const USER_NOT_FOUND: &str = "UserNotFound";
+/// The user being added is already in the group DM
+pub const ALREADY_MEMBER: &str = "AlreadyMember";
+/// The group DM already has 10 members
+pub const TOO_MANY_MEMBERS: &str = "TooManyMembers";
+/// A member of the group DM has blocked the user being added, or been
+/// blocked by them
+pub const BLOCKED: &str = "Blocked";
+
+/// Adds a user to a group DM. Any member can add others.
+pub async fn add_member(
+    path: web::Path<(Snowflake, Snowflake)>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (channel_id, user_id) = path.into_inner();
+
+    match db
+        .dm()
+        .add_member(channel_id, session.user_id, user_id)
+        .await
+    {
+        Ok(()) => {}
+        Err(dm::AddMemberError::NotFound) => return err!(DM_NOT_FOUND),
+        Err(dm::AddMemberError::NotGroup) => return err!(NOT_GROUP_DM),
+        Err(dm::AddMemberError::UserNotFound) => return err!(USER_NOT_FOUND),
+        Err(dm::AddMemberError::Blocked) => return err!(FORBIDDEN => BLOCKED),
+        Err(dm::AddMemberError::AlreadyMember) => return err!(ALREADY_MEMBER),
+        Err(dm::AddMemberError::TooManyMembers) => {
+            return err!(TOO_MANY_MEMBERS dm::MAX_GROUP_MEMBERS);
+        }
+        Err(dm::AddMemberError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    // The new member is sent the whole DM, and everyone else just the member
+    match db.dm().get(channel_id, user_id).await {
+        Ok(Some(channel)) => {
+            let others: Vec<Snowflake> = channel
+                .members
+                .iter()
+                .map(|member| member.id)
+                .filter(|id| *id != user_id)
+                .collect();
+            if let Some(user) = channel.members.iter().find(|member| member.id == user_id) {
+                let event = gateway::Event::DmMemberAdd {
+                    channel_id,
+                    user: user.clone(),
+                };
+                gateway.dispatch(&others, &event).await;
+            }
+            gateway
+                .dispatch(&[user_id], &gateway::Event::DmCreate(channel))
+                .await;
+        }
+        Ok(None) => warn!("Group DM not found after adding member"),
+        Err(e) => error!("Database error: {}", e),
+    }
+
+    ok!(())
+}
@@ -0,0 +1,75 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The users to add to the group DM, not including the user creating it
+    recipient_ids: Vec<Snowflake>,
+    /// Name of the group DM, up to 100 characters
+    #[serde(default)]
+    name: Option<String>,
+}
+
+/// The name was longer than 100 characters
+pub const NAME_TOO_LONG: &str = "NameTooLong";
+/// The group DM would have more than 10 members
+pub const TOO_MANY_MEMBERS: &str = "TooManyMembers";
+/// One of the recipients does not exist
+pub const USER_NOT_FOUND: &str = "UserNotFound";
+/// One of the members, including the user, has blocked another of them
+pub const BLOCKED: &str = "Blocked";
+
+/// Creates a group DM owned by the user.
+pub async fn create_group(
+    req: Json<Params>,
+    session: Session,
+    channel_sfgen: Data<Mutex<ChannelSnowflakeGen>>,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let name = req.name.as_deref().map(str::trim).unwrap_or_default();
+    if name.chars().count() > 100 {
+        return err!(NAME_TOO_LONG 100);
+    }
+
+    let channel_id = { channel_sfgen.lock().await.generate() };
+    match db
+        .dm()
+        .create_group(
+            channel_id,
+            session.user_id,
+            &req.recipient_ids,
+            Some(name).filter(|name| !name.is_empty()),
+        )
+        .await
+    {
+        Ok(()) => {}
+        Err(dm::CreateGroupError::TooManyMembers) => {
+            return err!(TOO_MANY_MEMBERS dm::MAX_GROUP_MEMBERS);
+        }
+        Err(dm::CreateGroupError::UserNotFound) => return err!(USER_NOT_FOUND),
+        Err(dm::CreateGroupError::Blocked) => return err!(FORBIDDEN => BLOCKED),
+        Err(dm::CreateGroupError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let channel = match db.dm().get(channel_id, session.user_id).await {
+        Ok(Some(channel)) => channel,
+        Ok(None) => {
+            warn!("Group DM not found after creation");
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let members: Vec<Snowflake> = channel.members.iter().map(|member| member.id).collect();
+    gateway
+        .dispatch(&members, &gateway::Event::DmCreate(channel.clone()))
+        .await;
+
+    ok!(channel)
+}
@@ -0,0 +1,12 @@
+use crate::prelude::*;
+
+/// Returns every DM and group DM the user is in, most recently active first.
+pub async fn list(session: Session, db: Data<DbPool>) -> impl Responder {
+    match db.dm().get_all(session.user_id).await {
+        Ok(channels) => ok!(channels),
+        Err(e) => {
+            error!("Database error: {}", e);
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod add_member;
+pub mod create_group;
+pub mod list;
+pub mod open;
+pub mod remove_member;
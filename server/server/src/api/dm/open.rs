@@ -0,0 +1,59 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The user to open a DM with
+    recipient_id: Snowflake,
+}
+
+/// The recipient is the user opening the DM
+pub const CANNOT_DM_SELF: &str = "CannotDmSelf";
+/// The recipient does not exist
+pub const USER_NOT_FOUND: &str = "UserNotFound";
+/// The user has blocked the recipient, or been blocked by them
+pub const BLOCKED: &str = "Blocked";
+
+/// Opens the DM between the user and the recipient, creating it if they
+/// have never had one.
+pub async fn open(
+    req: Json<Params>,
+    session: Session,
+    channel_sfgen: Data<Mutex<ChannelSnowflakeGen>>,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let id = { channel_sfgen.lock().await.generate() };
+    let (channel_id, created) = match db.dm().open(id, session.user_id, req.recipient_id).await {
+        Ok(opened) => opened,
+        Err(dm::OpenError::CannotDmSelf) => return err!(CANNOT_DM_SELF),
+        Err(dm::OpenError::RecipientNotFound) => return err!(USER_NOT_FOUND),
+        Err(dm::OpenError::Blocked) => return err!(FORBIDDEN => BLOCKED),
+        Err(dm::OpenError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let channel = match db.dm().get(channel_id, session.user_id).await {
+        Ok(Some(channel)) => channel,
+        Ok(None) => {
+            warn!("DM not found after opening");
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    if created {
+        gateway
+            .dispatch(
+                &[session.user_id, req.recipient_id],
+                &gateway::Event::DmCreate(channel.clone()),
+            )
+            .await;
+    }
+
+    ok!(channel)
+}
@@ -0,0 +1,55 @@
+use crate::prelude::*;
+
+/// The DM was not found or the user is not a member of it
+pub const DM_NOT_FOUND: &str = "DmNotFound";
+/// Members can only be removed from group DMs
+pub const NOT_GROUP_DM: &str = "NotGroupDm";
+/// Only the owner of the group DM can remove other members
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+/// The user being removed is not in the group DM
+pub const MEMBER_NOT_FOUND: &str = "MemberNotFound";
+
+/// Removes a user from a group DM, or leaves it if the user is removing
+/// themselves.
+pub async fn remove_member(
+    path: web::Path<(Snowflake, Snowflake)>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (channel_id, user_id) = path.into_inner();
+
+    let owner_id = match db
+        .dm()
+        .remove_member(channel_id, session.user_id, user_id)
+        .await
+    {
+        Ok(owner_id) => owner_id,
+        Err(dm::RemoveMemberError::NotFound) => return err!(DM_NOT_FOUND),
+        Err(dm::RemoveMemberError::NotGroup) => return err!(NOT_GROUP_DM),
+        Err(dm::RemoveMemberError::PermissionDenied) => {
+            return err!(UNAUTHORIZED => PERMISSION_DENIED);
+        }
+        Err(dm::RemoveMemberError::NotMember) => return err!(MEMBER_NOT_FOUND),
+        Err(dm::RemoveMemberError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    // Tell the removed user too, so they can stop showing the DM
+    match db.dm().get_member_ids(channel_id).await {
+        Ok(mut members) => {
+            members.push(user_id);
+            let event = gateway::Event::DmMemberRemove {
+                channel_id,
+                user_id,
+                owner_id,
+            };
+            gateway.dispatch(&members, &event).await;
+        }
+        Err(e) => error!("Database error: {}", e),
+    }
+
+    ok!(())
+}
@@ -12,24 +12,23 @@ pub struct Response {
     channel_id: Snowflake,
 }
 
-/// The name was shorter than 2 character
-pub const NAME_TOO_SHORT: &str = "NameTooShort";
-/// The name was longer than 60 characters
-pub const NAME_TOO_LONG: &str = "NameTooLong";
-
 pub async fn create(
     req: Json<Params>,
     session: Session,
     guild_sfgen: Data<Mutex<GuildSnowflakeGen>>,
     guild_member_sfgen: Data<Mutex<GuildMemberSnowflakeGen>>,
     channel_sfgen: Data<Mutex<ChannelSnowflakeGen>>,
+    gateway: Data<Gateway>,
     db: Data<DbPool>,
 ) -> impl Responder {
-    if req.name.len() < 2 {
-        return err!(NAME_TOO_SHORT 2);
+    if let Err(response) =
+        verification::check(&db, session.user_id, verification::Action::CreateGuild).await
+    {
+        return response;
     }
-    if req.name.len() > 60 {
-        return err!(NAME_TOO_LONG 60);
+
+    if let Err(response) = super::check_name(&req.name) {
+        return response;
     }
 
     let guild_id = { guild_sfgen.lock().await.generate() };
@@ -57,6 +56,10 @@ pub async fn create(
         .await
     {
         Ok(()) => {}
+        Err(guild::JoinError::AlreadyMember | guild::JoinError::Banned) => {
+            warn!("Guild owner already a member of, or banned from, a new guild");
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
         Err(guild::JoinError::NotInserted) => {
             warn!("Guild member not inserted into database");
             return err!(INTERNAL_SERVER_ERROR => ISE);
@@ -69,14 +72,26 @@ pub async fn create(
 
     // Add a new channel to the guild
     let channel_id = { channel_sfgen.lock().await.generate() };
-    match db
+    let channel = match db
         .channel()
-        .create(channel_id, guild_id, "general", None)
+        .create(
+            channel_id,
+            guild_id,
+            "general",
+            channel::ChannelKind::Text,
+            None,
+            None,
+        )
         .await
     {
-        Ok(()) => {}
-        Err(channel::CreateError::NextChannelDoesNotExist) => {
-            warn!("Next channel does not exist when creating guild");
+        Ok(channel) => channel,
+        Err(
+            channel::CreateError::NextChannelDoesNotExist
+            | channel::CreateError::NextChannelNotInParent
+            | channel::CreateError::ParentNotFound
+            | channel::CreateError::CategoryNested,
+        ) => {
+            warn!("Channel placement invalid when creating guild");
             return err!(INTERNAL_SERVER_ERROR => ISE);
         }
         Err(channel::CreateError::NotInserted) => {
@@ -89,6 +104,26 @@ pub async fn create(
         }
     };
 
+    // Notify the owner's other sessions of the new guild
+    match db.user().get(session.user_id).await {
+        Ok(Some(owner)) => {
+            let event = gateway::Event::GuildCreate(guild::Guild {
+                id: guild_id,
+                owner,
+                name: req.name.clone(),
+                folder_id: None,
+                channels: vec![channel::ChannelNode {
+                    channel,
+                    children: vec![],
+                    collapsed: false,
+                }],
+            });
+            gateway.dispatch(&[session.user_id], &event).await;
+        }
+        Ok(None) => warn!("Guild owner {} not found", session.user_id),
+        Err(e) => error!("Database error: {}", e),
+    }
+
     ok!(Response {
         guild_id,
         channel_id
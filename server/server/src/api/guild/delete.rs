@@ -0,0 +1,29 @@
+use crate::prelude::*;
+
+/// The guild was not found or the user is not its owner
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+
+/// Deletes a guild along with its channels, messages, roles and members.
+/// Only the owner can do this.
+pub async fn delete(
+    guild_id: web::Path<Snowflake>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let members = match db.guild().delete(*guild_id, session.user_id).await {
+        Ok(members) => members,
+        Err(guild::DeleteError::NotOwner) => return err!(GUILD_NOT_FOUND),
+        Err(guild::DeleteError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let event = gateway::Event::GuildDelete {
+        guild_id: *guild_id,
+    };
+    gateway.dispatch(&members, &event).await;
+
+    ok!(())
+}
@@ -0,0 +1,27 @@
+use crate::prelude::*;
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+
+/// Returns a single guild the user is in, with its channels, so clients can
+/// load or refresh one guild without fetching every guild they've joined.
+pub async fn get(
+    guild_id: web::Path<Snowflake>,
+    session: Session,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let guild = match db
+        .guild()
+        .get_joined_guild(session.user_id, *guild_id)
+        .await
+    {
+        Ok(Some(guild)) => guild,
+        Ok(None) => return err!(GUILD_NOT_FOUND),
+        Err(guild::GetJoinedError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(guild)
+}
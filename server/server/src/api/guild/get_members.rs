@@ -0,0 +1,65 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// Get the members who joined after this member
+    #[serde(default)]
+    after: Option<Snowflake>,
+    /// Only get the members whose username starts with this, ignoring case
+    #[serde(default)]
+    query: Option<String>,
+    /// How many members to get, between 1 and 100. Defaults to 50
+    #[serde(default)]
+    limit: Option<i64>,
+}
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The limit was not between 1 and 100
+pub const INVALID_LIMIT: &str = "InvalidLimit";
+/// The query was longer than 32 characters
+pub const QUERY_TOO_LONG: &str = "QueryTooLong";
+
+/// The amount of members returned if no limit is given.
+const DEFAULT_LIMIT: i64 = 50;
+/// The most members that can be requested at once.
+const MAX_LIMIT: i64 = 100;
+
+/// Returns a page of the members of a guild, in the order they joined.
+pub async fn get_members(
+    guild_id: web::Path<Snowflake>,
+    query: web::Query<Params>,
+    session: Session,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return err!(INVALID_LIMIT map!{ min => 1, max => MAX_LIMIT });
+    }
+    if query.query.as_ref().map_or(false, |query| query.len() > 32) {
+        return err!(QUERY_TOO_LONG 32);
+    }
+
+    match db.guild().get_permissions(*guild_id, session.user_id).await {
+        Ok(_) => {}
+        Err(guild::GetPermissionsError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let page = match db
+        .guild()
+        .get_members(*guild_id, query.after, query.query.as_deref(), limit)
+        .await
+    {
+        Ok(page) => page,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(page)
+}
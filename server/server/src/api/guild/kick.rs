@@ -0,0 +1,63 @@
+use crate::prelude::*;
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The user being kicked is not in the guild
+pub const MEMBER_NOT_FOUND: &str = "MemberNotFound";
+/// The user does not have permission to kick members, or the member is not
+/// ranked below their highest role
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Removes a member from a guild. They can join again with an invite.
+pub async fn kick(
+    path: web::Path<(Snowflake, Snowflake)>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, user_id) = path.into_inner();
+
+    let perms = match db.guild().get_permissions(guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::KICK_MEMBERS) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    // Members can only kick those ranked below them, which also stops
+    // anyone from kicking the owner
+    let target = match db.guild().get_permissions(guild_id, user_id).await {
+        Ok(target) => target,
+        Err(guild::GetPermissionsError::NotInGuild) => return err!(MEMBER_NOT_FOUND),
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.outranks(target.rank) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    match db.guild().remove_member(guild_id, user_id).await {
+        Ok(()) => {}
+        Err(guild::RemoveMemberError::NotInGuild) => return err!(MEMBER_NOT_FOUND),
+        Err(guild::RemoveMemberError::IsOwner) => return err!(UNAUTHORIZED => PERMISSION_DENIED),
+        Err(guild::RemoveMemberError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    gateway
+        .dispatch(&[user_id], &gateway::Event::GuildDelete { guild_id })
+        .await;
+    let event = gateway::Event::GuildMemberRemove { guild_id, user_id };
+    gateway.dispatch_to_guild(&db, guild_id, &event).await;
+
+    ok!(())
+}
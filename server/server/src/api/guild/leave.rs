@@ -0,0 +1,36 @@
+use crate::prelude::*;
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The owner must transfer ownership or delete the guild instead of leaving
+pub const OWNER_CANNOT_LEAVE: &str = "OwnerCannotLeave";
+
+/// Leaves a guild.
+pub async fn leave(
+    guild_id: web::Path<Snowflake>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    match db.guild().remove_member(*guild_id, session.user_id).await {
+        Ok(()) => {}
+        Err(guild::RemoveMemberError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+        Err(guild::RemoveMemberError::IsOwner) => return err!(OWNER_CANNOT_LEAVE),
+        Err(guild::RemoveMemberError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let event = gateway::Event::GuildDelete {
+        guild_id: *guild_id,
+    };
+    gateway.dispatch(&[session.user_id], &event).await;
+    let event = gateway::Event::GuildMemberRemove {
+        guild_id: *guild_id,
+        user_id: session.user_id,
+    };
+    gateway.dispatch_to_guild(&db, *guild_id, &event).await;
+
+    ok!(())
+}
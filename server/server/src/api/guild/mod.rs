@@ -1,2 +1,42 @@
 pub mod create;
-pub mod get_joined;
\ No newline at end of file
+pub mod delete;
+pub mod get;
+pub mod get_joined;
+pub mod get_members;
+pub mod kick;
+pub mod leave;
+pub mod rename;
+pub mod reorder;
+pub mod set_nickname;
+pub mod transfer;
+
+use crate::prelude::*;
+
+/// The name was shorter than 2 character
+pub const NAME_TOO_SHORT: &str = "NameTooShort";
+/// The name was longer than 60 characters
+pub const NAME_TOO_LONG: &str = "NameTooLong";
+
+/// Checks a guild name is between 2 and 60 characters, returning the error
+/// response to send if not.
+fn check_name(name: &str) -> Result<(), HttpResponse> {
+    if name.len() < 2 {
+        return Err(err!(NAME_TOO_SHORT 2));
+    }
+    if name.len() > 60 {
+        return Err(err!(NAME_TOO_LONG 60));
+    }
+    Ok(())
+}
+
+/// Tells every member of a guild its current name and owner.
+async fn dispatch_update(db: &DbPool, gateway: &Gateway, guild_id: Snowflake) {
+    match db.guild().get_summary(guild_id).await {
+        Ok(Some(summary)) => {
+            let event = gateway::Event::GuildUpdate(summary);
+            gateway.dispatch_to_guild(db, guild_id, &event).await;
+        }
+        Ok(None) => warn!("Guild {} not found after updating it", guild_id),
+        Err(e) => error!("Database error: {}", e),
+    }
+}
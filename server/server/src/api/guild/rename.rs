@@ -0,0 +1,50 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The new name of the guild, between 2 and 60 characters
+    name: String,
+}
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The user does not have permission to manage the guild
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Renames a guild.
+pub async fn rename(
+    guild_id: web::Path<Snowflake>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    if let Err(response) = super::check_name(&req.name) {
+        return response;
+    }
+
+    let perms = match db.guild().get_permissions(*guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_GUILD) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    match db.guild().rename(*guild_id, &req.name).await {
+        Ok(()) => {}
+        Err(guild::RenameError::NotFound) => return err!(GUILD_NOT_FOUND),
+        Err(guild::RenameError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    super::dispatch_update(&db, &gateway, *guild_id).await;
+
+    ok!(())
+}
@@ -0,0 +1,74 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// Every guild the user is in, from top to bottom. Can't be given along
+    /// with `guild_id`
+    #[serde(default)]
+    guilds: Option<Vec<Snowflake>>,
+    /// A single guild to move instead of giving the full order
+    #[serde(default)]
+    guild_id: Option<Snowflake>,
+    /// The guild to place `guild_id` before. Placed at the bottom if not
+    /// given
+    #[serde(default)]
+    place_before: Option<Snowflake>,
+}
+
+/// Either both or neither of `guilds` and `guild_id` were given
+pub const INVALID_PARAMS: &str = "InvalidParams";
+/// The guilds given were not exactly the guilds the user is in
+pub const GUILDS_MISMATCH: &str = "GuildsMismatch";
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The `place_before` guild was not found or the user is not in it
+pub const PLACE_BEFORE_NOT_FOUND: &str = "PlaceBeforeNotFound";
+
+/// Moves guilds in the user's sidebar, either all at once or one at a time.
+pub async fn reorder(
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let Params {
+        guilds,
+        guild_id,
+        place_before,
+    } = req.into_inner();
+
+    let guild_ids = match (guilds, guild_id) {
+        (Some(guilds), None) if place_before.is_none() => {
+            match db.guild().reorder_joined(session.user_id, &guilds).await {
+                Ok(()) => guilds,
+                Err(guild::ReorderJoinedError::Mismatch) => return err!(GUILDS_MISMATCH),
+                Err(guild::ReorderJoinedError::DatabaseError(e)) => {
+                    error!("Database error: {}", e);
+                    return err!(INTERNAL_SERVER_ERROR => ISE);
+                }
+            }
+        }
+        (None, Some(guild_id)) => {
+            match db
+                .guild()
+                .move_joined(session.user_id, guild_id, place_before)
+                .await
+            {
+                Ok(guild_ids) => guild_ids,
+                Err(guild::MoveJoinedError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+                Err(guild::MoveJoinedError::NextNotFound) => return err!(PLACE_BEFORE_NOT_FOUND),
+                Err(guild::MoveJoinedError::DatabaseError(e)) => {
+                    error!("Database error: {}", e);
+                    return err!(INTERNAL_SERVER_ERROR => ISE);
+                }
+            }
+        }
+        _ => return err!(INVALID_PARAMS),
+    };
+
+    // Keep the user's other sessions in sync
+    let event = gateway::Event::GuildOrderUpdate { guild_ids };
+    gateway.dispatch(&[session.user_id], &event).await;
+
+    ok!(())
+}
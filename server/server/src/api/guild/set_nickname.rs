@@ -0,0 +1,84 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The new nickname, up to 32 characters. Cleared if not given or empty
+    #[serde(default)]
+    nickname: Option<String>,
+}
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The user whose nickname is being set is not in the guild
+pub const MEMBER_NOT_FOUND: &str = "MemberNotFound";
+/// The user does not have permission to manage nicknames, or the member is
+/// not ranked below their highest role
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+/// The nickname was longer than 32 characters
+pub const NICKNAME_TOO_LONG: &str = "NicknameTooLong";
+
+/// Sets or clears the nickname of a member. Members can always change their
+/// own nickname.
+pub async fn set_nickname(
+    path: web::Path<(Snowflake, Snowflake)>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, user_id) = path.into_inner();
+
+    let nickname = req
+        .nickname
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty());
+    if nickname.map_or(false, |nickname| nickname.chars().count() > 32) {
+        return err!(NICKNAME_TOO_LONG 32);
+    }
+
+    let perms = match db.guild().get_permissions(guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if user_id != session.user_id {
+        if !perms.contains(role::Permissions::MANAGE_NICKNAMES) {
+            return err!(UNAUTHORIZED => PERMISSION_DENIED);
+        }
+        let target = match db.guild().get_permissions(guild_id, user_id).await {
+            Ok(target) => target,
+            Err(guild::GetPermissionsError::NotInGuild) => return err!(MEMBER_NOT_FOUND),
+            Err(guild::GetPermissionsError::DatabaseError(e)) => {
+                error!("Database error: {}", e);
+                return err!(INTERNAL_SERVER_ERROR => ISE);
+            }
+        };
+        if !perms.outranks(target.rank) {
+            return err!(UNAUTHORIZED => PERMISSION_DENIED);
+        }
+    }
+
+    match db.guild().set_nickname(guild_id, user_id, nickname).await {
+        Ok(()) => {}
+        Err(guild::SetNicknameError::NotInGuild) => return err!(MEMBER_NOT_FOUND),
+        Err(guild::SetNicknameError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    match db.guild().get_member(guild_id, user_id).await {
+        Ok(Some(member)) => {
+            let event = gateway::Event::GuildMemberUpdate(member);
+            gateway.dispatch_to_guild(&db, guild_id, &event).await;
+        }
+        Ok(None) => warn!("Member {} not found after setting nickname", user_id),
+        Err(e) => error!("Database error: {}", e),
+    }
+
+    ok!(())
+}
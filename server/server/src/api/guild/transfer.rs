@@ -0,0 +1,39 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The member to make the new owner
+    user_id: Snowflake,
+}
+
+/// The guild was not found or the user is not its owner
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The new owner is not a member of the guild
+pub const MEMBER_NOT_FOUND: &str = "MemberNotFound";
+
+/// Makes another member the owner of the guild. Only the owner can do this.
+pub async fn transfer(
+    guild_id: web::Path<Snowflake>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    match db
+        .guild()
+        .transfer(*guild_id, session.user_id, req.user_id)
+        .await
+    {
+        Ok(()) => {}
+        Err(guild::TransferError::NotOwner) => return err!(GUILD_NOT_FOUND),
+        Err(guild::TransferError::NotMember) => return err!(MEMBER_NOT_FOUND),
+        Err(guild::TransferError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    super::dispatch_update(&db, &gateway, *guild_id).await;
+
+    ok!(())
+}
@@ -0,0 +1,60 @@
+use crate::prelude::*;
+
+use super::{check_name, GUILD_NOT_FOUND, INVALID_COLOR, NO_GUILDS};
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The name of the folder, between 1 and 32 characters
+    name: String,
+    /// The hex color code of the folder, without the leading `#`
+    #[serde(default)]
+    color: Option<String>,
+    /// The guilds to put in the folder, which are taken out of any folder
+    /// they were in
+    guilds: Vec<Snowflake>,
+}
+
+/// Groups guilds in the user's sidebar under a folder.
+pub async fn create(
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+    folder_snowflake_gen: Data<Mutex<GuildFolderSnowflakeGen>>,
+) -> impl Responder {
+    if let Err(res) = check_name(&req.name) {
+        return res;
+    }
+    if let Some(color) = &req.color {
+        if !validation::validate_color(color) {
+            return err!(INVALID_COLOR);
+        }
+    }
+    if req.guilds.is_empty() {
+        return err!(NO_GUILDS);
+    }
+
+    let folder_id = { folder_snowflake_gen.lock().await.generate() };
+    let folder = match db
+        .guild_folder()
+        .create(
+            folder_id,
+            session.user_id,
+            &req.name,
+            req.color.as_deref(),
+            &req.guilds,
+        )
+        .await
+    {
+        Ok(folder) => folder,
+        Err(guild_folder::CreateError::GuildNotJoined) => return err!(GUILD_NOT_FOUND),
+        Err(guild_folder::CreateError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    super::dispatch_folders(&db, &gateway, session.user_id).await;
+
+    ok!(folder)
+}
@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+/// The folder was not found or belongs to another user
+pub const FOLDER_NOT_FOUND: &str = "FolderNotFound";
+
+/// Deletes a folder. The guilds in it stay where they are in the sidebar.
+pub async fn delete(
+    folder_id: web::Path<Snowflake>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    match db.guild_folder().delete(session.user_id, *folder_id).await {
+        Ok(()) => {}
+        Err(guild_folder::DeleteError::NotFound) => return err!(FOLDER_NOT_FOUND),
+        Err(guild_folder::DeleteError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    super::dispatch_folders(&db, &gateway, session.user_id).await;
+
+    ok!(())
+}
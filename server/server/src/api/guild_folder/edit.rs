@@ -0,0 +1,67 @@
+use crate::prelude::*;
+
+use super::{check_name, GUILD_NOT_FOUND, INVALID_COLOR, NO_GUILDS};
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The new name of the folder, between 1 and 32 characters
+    #[serde(default)]
+    name: Option<String>,
+    /// The new hex color code of the folder, without the leading `#`, or an
+    /// empty string to reset it
+    #[serde(default)]
+    color: Option<String>,
+    /// The guilds that should be in the folder, replacing the ones in it
+    #[serde(default)]
+    guilds: Option<Vec<Snowflake>>,
+}
+
+/// The folder was not found or belongs to another user
+pub const FOLDER_NOT_FOUND: &str = "FolderNotFound";
+
+/// Renames or recolours a folder, or changes which guilds are in it.
+pub async fn edit(
+    folder_id: web::Path<Snowflake>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    if let Some(name) = &req.name {
+        if let Err(res) = check_name(name) {
+            return res;
+        }
+    }
+    if let Some(color) = &req.color {
+        if !color.is_empty() && !validation::validate_color(color) {
+            return err!(INVALID_COLOR);
+        }
+    }
+    if req.guilds.as_ref().map_or(false, Vec::is_empty) {
+        return err!(NO_GUILDS);
+    }
+
+    let folder = match db
+        .guild_folder()
+        .edit(
+            session.user_id,
+            *folder_id,
+            req.name.as_deref(),
+            req.color.as_deref(),
+            req.guilds.as_deref(),
+        )
+        .await
+    {
+        Ok(folder) => folder,
+        Err(guild_folder::EditError::NotFound) => return err!(FOLDER_NOT_FOUND),
+        Err(guild_folder::EditError::GuildNotJoined) => return err!(GUILD_NOT_FOUND),
+        Err(guild_folder::EditError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    super::dispatch_folders(&db, &gateway, session.user_id).await;
+
+    ok!(folder)
+}
@@ -0,0 +1,14 @@
+use crate::prelude::*;
+
+/// Returns every folder in the user's sidebar.
+pub async fn list(session: Session, db: Data<DbPool>) -> impl Responder {
+    let folders = match db.guild_folder().get_all(session.user_id).await {
+        Ok(folders) => folders,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(folders)
+}
@@ -0,0 +1,41 @@
+pub mod create;
+pub mod delete;
+pub mod edit;
+pub mod list;
+
+use crate::prelude::*;
+
+/// The name was shorter than 1 character
+pub const NAME_TOO_SHORT: &str = "NameTooShort";
+/// The name was longer than 32 characters
+pub const NAME_TOO_LONG: &str = "NameTooLong";
+/// The color was not a 6-digit hex code
+pub const INVALID_COLOR: &str = "InvalidColor";
+/// No guilds were given to put in the folder
+pub const NO_GUILDS: &str = "NoGuilds";
+/// The user is not in one of the guilds given
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+
+/// Checks a folder name is between 1 and 32 characters, returning the error
+/// response to send if not.
+fn check_name(name: &str) -> Result<(), HttpResponse> {
+    if name.is_empty() {
+        return Err(err!(NAME_TOO_SHORT 1));
+    }
+    if name.chars().count() > 32 {
+        return Err(err!(NAME_TOO_LONG 32));
+    }
+    Ok(())
+}
+
+/// Sends the user's sessions every folder they have, since changing one
+/// folder can empty and delete others.
+async fn dispatch_folders(db: &DbPool, gateway: &Gateway, user_id: Snowflake) {
+    match db.guild_folder().get_all(user_id).await {
+        Ok(folders) => {
+            let event = gateway::Event::GuildFoldersUpdate { folders };
+            gateway.dispatch(&[user_id], &event).await;
+        }
+        Err(e) => error!("Database error: {}", e),
+    }
+}
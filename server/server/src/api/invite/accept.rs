@@ -0,0 +1,78 @@
+use crate::prelude::*;
+
+#[derive(Serialize, Debug)]
+pub struct Response {
+    /// The ID of the guild that was joined
+    guild_id: Snowflake,
+}
+
+/// The invite does not exist
+pub const INVITE_NOT_FOUND: &str = "InviteNotFound";
+/// The invite has expired
+pub const INVITE_EXPIRED: &str = "InviteExpired";
+/// The invite has been used the maximum amount of times
+pub const INVITE_EXHAUSTED: &str = "InviteExhausted";
+/// The user is already a member of the guild
+pub const ALREADY_MEMBER: &str = "AlreadyMember";
+/// The user is banned from the guild
+pub const BANNED: &str = "Banned";
+
+/// Joins the guild an invite leads to.
+pub async fn accept(
+    code: web::Path<String>,
+    session: Session,
+    guild_member_sfgen: Data<Mutex<GuildMemberSnowflakeGen>>,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    if let Err(response) =
+        verification::check(&db, session.user_id, verification::Action::JoinGuild).await
+    {
+        return response;
+    }
+
+    let guild_member_id = { guild_member_sfgen.lock().await.generate() };
+    let guild_id = match db
+        .invite()
+        .accept(&code, guild_member_id, session.user_id)
+        .await
+    {
+        Ok(guild_id) => guild_id,
+        Err(invite::AcceptError::NotFound) => return err!(INVITE_NOT_FOUND),
+        Err(invite::AcceptError::Expired) => return err!(INVITE_EXPIRED),
+        Err(invite::AcceptError::Exhausted) => return err!(INVITE_EXHAUSTED),
+        Err(invite::AcceptError::AlreadyMember) => return err!(ALREADY_MEMBER),
+        Err(invite::AcceptError::Banned) => return err!(FORBIDDEN => BANNED),
+        Err(invite::AcceptError::NotInserted) => {
+            warn!("Guild member not inserted into database");
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(invite::AcceptError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    // Tell the user's other sessions about the guild they joined
+    match db.guild().get_joined_guild(session.user_id, guild_id).await {
+        Ok(Some(guild)) => {
+            gateway
+                .dispatch(&[session.user_id], &gateway::Event::GuildCreate(guild))
+                .await;
+        }
+        Ok(None) => warn!("Guild {} not found after joining it", guild_id),
+        Err(guild::GetJoinedError::DatabaseError(e)) => error!("Database error: {}", e),
+    }
+
+    // And tell everyone else in the guild about the new member
+    match db.user().get(session.user_id).await {
+        Ok(Some(user)) => {
+            let event = gateway::Event::GuildMemberAdd { guild_id, user };
+            gateway.dispatch_to_guild(&db, guild_id, &event).await;
+        }
+        Ok(None) => warn!("User {} not found after joining guild", session.user_id),
+        Err(e) => error!("Database error: {}", e),
+    }
+
+    ok!(Response { guild_id })
+}
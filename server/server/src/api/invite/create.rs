@@ -0,0 +1,81 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// How many times the invite can be used, between 1 and 1000. Unlimited
+    /// if not given
+    #[serde(default)]
+    max_uses: Option<i32>,
+    /// How many seconds the invite lasts for, up to 30 days. Never expires
+    /// if not given
+    #[serde(default)]
+    max_age: Option<i64>,
+}
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The user does not have permission to create invites
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+/// `max_uses` was not between 1 and 1000
+pub const INVALID_MAX_USES: &str = "InvalidMaxUses";
+/// `max_age` was not between 1 second and 30 days
+pub const INVALID_MAX_AGE: &str = "InvalidMaxAge";
+
+/// The longest an invite can last for, in seconds.
+const MAX_AGE_LIMIT: i64 = 30 * 24 * 60 * 60;
+
+pub async fn create(
+    guild_id: web::Path<Snowflake>,
+    req: Json<Params>,
+    session: Session,
+    db: Data<DbPool>,
+) -> impl Responder {
+    if let Some(max_uses) = req.max_uses {
+        if !(1..=1000).contains(&max_uses) {
+            return err!(INVALID_MAX_USES map!{ min => 1, max => 1000 });
+        }
+    }
+    if let Some(max_age) = req.max_age {
+        if !(1..=MAX_AGE_LIMIT).contains(&max_age) {
+            return err!(INVALID_MAX_AGE map!{ min => 1, max => MAX_AGE_LIMIT });
+        }
+    }
+
+    // Check the user has permission to create an invite
+    let perms = match db.guild().get_permissions(*guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => {
+            return err!(GUILD_NOT_FOUND);
+        }
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::CREATE_INVITES) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    let invite = match db
+        .invite()
+        .create(
+            *guild_id,
+            session.user_id,
+            req.max_uses,
+            req.max_age.map(|age| age * 1000),
+        )
+        .await
+    {
+        Ok(invite) => invite,
+        Err(invite::CreateError::NotInserted) => {
+            warn!("Invite not inserted into database");
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(invite::CreateError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(invite)
+}
@@ -0,0 +1,38 @@
+use crate::prelude::*;
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The user does not have permission to manage the guild
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Returns every invite to a guild, including expired and used up ones.
+pub async fn list(
+    guild_id: web::Path<Snowflake>,
+    session: Session,
+    db: Data<DbPool>,
+) -> impl Responder {
+    // Check the user has permission to see the guild's invites
+    let perms = match db.guild().get_permissions(*guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => {
+            return err!(GUILD_NOT_FOUND);
+        }
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_GUILD) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    let invites = match db.invite().get_all(*guild_id).await {
+        Ok(invites) => invites,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(invites)
+}
@@ -0,0 +1,5 @@
+pub mod accept;
+pub mod create;
+pub mod list;
+pub mod preview;
+pub mod revoke;
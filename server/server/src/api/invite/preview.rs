@@ -0,0 +1,24 @@
+use crate::prelude::*;
+
+/// The invite does not exist
+pub const INVITE_NOT_FOUND: &str = "InviteNotFound";
+/// The invite has expired
+pub const INVITE_EXPIRED: &str = "InviteExpired";
+/// The invite has been used the maximum amount of times
+pub const INVITE_EXHAUSTED: &str = "InviteExhausted";
+
+/// Returns the guild an invite leads to. Does not require authentication.
+pub async fn preview(code: web::Path<String>, db: Data<DbPool>) -> impl Responder {
+    let preview = match db.invite().preview(&code).await {
+        Ok(preview) => preview,
+        Err(invite::PreviewError::NotFound) => return err!(INVITE_NOT_FOUND),
+        Err(invite::PreviewError::Expired) => return err!(INVITE_EXPIRED),
+        Err(invite::PreviewError::Exhausted) => return err!(INVITE_EXHAUSTED),
+        Err(invite::PreviewError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(preview)
+}
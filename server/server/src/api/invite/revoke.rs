@@ -0,0 +1,50 @@
+use crate::prelude::*;
+
+/// The invite does not exist
+pub const INVITE_NOT_FOUND: &str = "InviteNotFound";
+/// The user did not create the invite and does not have permission to
+/// manage the guild
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Deletes an invite so it can no longer be used.
+pub async fn revoke(code: web::Path<String>, session: Session, db: Data<DbPool>) -> impl Responder {
+    let invite = match db.invite().get(&code).await {
+        Ok(Some(invite)) => invite,
+        Ok(None) => return err!(INVITE_NOT_FOUND),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    // Anyone can revoke their own invites, but only managers can revoke
+    // someone else's
+    if invite.inviter_id != session.user_id {
+        match db
+            .guild()
+            .get_permissions(invite.guild_id, session.user_id)
+            .await
+        {
+            Ok(perms) if perms.contains(role::Permissions::MANAGE_GUILD) => {}
+            Ok(_) => return err!(UNAUTHORIZED => PERMISSION_DENIED),
+            Err(guild::GetPermissionsError::NotInGuild) => {
+                return err!(INVITE_NOT_FOUND);
+            }
+            Err(guild::GetPermissionsError::DatabaseError(e)) => {
+                error!("Database error: {}", e);
+                return err!(INTERNAL_SERVER_ERROR => ISE);
+            }
+        };
+    }
+
+    match db.invite().revoke(invite.guild_id, &code).await {
+        Ok(()) => {}
+        Err(invite::RevokeError::NotFound) => return err!(INVITE_NOT_FOUND),
+        Err(invite::RevokeError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(())
+}
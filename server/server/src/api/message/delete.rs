@@ -0,0 +1,61 @@
+use crate::prelude::*;
+
+/// The message was not found, was already deleted, or the user did not have
+/// permission to view its channel.
+pub const MESSAGE_NOT_FOUND: &str = "MessageNotFound";
+/// The user is not the author of the message and cannot manage messages
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Deletes a message, leaving a placeholder in its place.
+pub async fn delete(
+    path: web::Path<(Snowflake, Snowflake)>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (channel_id, message_id) = path.into_inner();
+
+    let perms = match db
+        .channel()
+        .get_user_permissions(channel_id, session.user_id)
+        .await
+    {
+        Ok(perms) if perms.contains(role::Permissions::VIEW_CHANNELS) => perms,
+        Ok(_) | Err(channel::GetPermissionsError::NotFound) => return err!(MESSAGE_NOT_FOUND),
+        Err(channel::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let message = match db.message().get_by_id(message_id).await {
+        Ok(Some(message)) if message.channel_id == channel_id && !message.deleted => message,
+        Ok(_) => return err!(MESSAGE_NOT_FOUND),
+        Err(message::GetError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    // Authors can always delete their own messages
+    if message.author.id != session.user_id && !perms.contains(role::Permissions::MANAGE_MESSAGES) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    match db.message().delete(channel_id, message_id).await {
+        Ok(()) => {}
+        Err(message::DeleteError::NotFound) => return err!(MESSAGE_NOT_FOUND),
+        Err(message::DeleteError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let event = gateway::Event::MessageDelete {
+        channel_id,
+        message_id,
+    };
+    gateway.dispatch_to_channel(&db, channel_id, &event).await;
+
+    ok!(())
+}
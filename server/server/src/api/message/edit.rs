@@ -0,0 +1,80 @@
+use crate::prelude::*;
+
+#[derive(Debug, Deserialize)]
+pub struct Params {
+    content: String,
+}
+
+/// The message was too short (empty)
+pub const MESSAGE_TOO_SHORT: &str = "MessageTooShort";
+/// The message was too long (over 10000 characters)
+pub const MESSAGE_TOO_LONG: &str = "MessageTooLong";
+/// The message was not found, was deleted, or the user did not have
+/// permission to view its channel.
+pub const MESSAGE_NOT_FOUND: &str = "MessageNotFound";
+/// The user is not the author of the message
+pub const NOT_AUTHOR: &str = "NotAuthor";
+
+/// Replaces the content of one of the user's own messages.
+pub async fn edit(
+    path: web::Path<(Snowflake, Snowflake)>,
+    body: Json<Params>,
+    session: Session,
+    message_revision_sfgen: Data<Mutex<MessageRevisionSnowflakeGen>>,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (channel_id, message_id) = path.into_inner();
+
+    if body.content.is_empty() {
+        return err!(MESSAGE_TOO_SHORT);
+    }
+    if body.content.len() > 10000 {
+        return err!(MESSAGE_TOO_LONG 10000);
+    }
+
+    // The user must still be able to see the channel to edit in it
+    match db
+        .channel()
+        .has_read_permission(channel_id, session.user_id)
+        .await
+    {
+        Ok(()) => {}
+        Err(channel::HasReadPermissionError::NotFound) => return err!(MESSAGE_NOT_FOUND),
+        Err(channel::HasReadPermissionError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let revision_id = { message_revision_sfgen.lock().await.generate() };
+    let message = match db
+        .message()
+        .edit(
+            revision_id,
+            channel_id,
+            message_id,
+            session.user_id,
+            &body.content,
+        )
+        .await
+    {
+        Ok(message) => message,
+        Err(message::EditError::NotFound) => return err!(MESSAGE_NOT_FOUND),
+        Err(message::EditError::NotAuthor) => return err!(UNAUTHORIZED => NOT_AUTHOR),
+        Err(message::EditError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    gateway
+        .dispatch_to_channel(
+            &db,
+            channel_id,
+            &gateway::Event::MessageUpdate(message.clone()),
+        )
+        .await;
+
+    ok!(message)
+}
@@ -1,14 +1,52 @@
 use crate::prelude::*;
 
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// Get the messages sent before this message
+    #[serde(default)]
+    before: Option<Snowflake>,
+    /// Get the messages sent after this message
+    #[serde(default)]
+    after: Option<Snowflake>,
+    /// Get the messages sent around this message, including itself
+    #[serde(default)]
+    around: Option<Snowflake>,
+    /// How many messages to get, between 1 and 100. Defaults to 50
+    #[serde(default)]
+    limit: Option<i64>,
+}
+
 /// The channel was not found or the user does not have permission to view it.
 pub const NOT_FOUND: &str = "NotFound";
+/// More than one of `before`, `after` and `around` was given
+pub const CONFLICTING_CURSORS: &str = "ConflictingCursors";
+/// The limit was not between 1 and 100
+pub const INVALID_LIMIT: &str = "InvalidLimit";
 
-/// Returns all the messages in a channel.
+/// The amount of messages returned if no limit is given.
+const DEFAULT_LIMIT: i64 = 50;
+/// The most messages that can be requested at once.
+const MAX_LIMIT: i64 = 100;
+
+/// Returns a page of the messages in a channel, newest first.
 pub async fn get(
     channel_id: web::Path<Snowflake>,
+    query: web::Query<Params>,
     session: Session,
     pool: web::Data<DbPool>,
 ) -> impl Responder {
+    let cursor = match (query.before, query.after, query.around) {
+        (None, None, None) => message::Cursor::Latest,
+        (Some(before), None, None) => message::Cursor::Before(before),
+        (None, Some(after), None) => message::Cursor::After(after),
+        (None, None, Some(around)) => message::Cursor::Around(around),
+        _ => return err!(CONFLICTING_CURSORS),
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return err!(INVALID_LIMIT map!{ min => 1, max => MAX_LIMIT });
+    }
+
     // Check the user has permission to view this channel
     match pool
         .channel()
@@ -24,13 +62,17 @@ pub async fn get(
     };
 
     // Get the messages
-    let messages = match pool.message().get(*channel_id, 50, 0).await {
-        Ok(messages) => messages,
+    let page = match pool
+        .message()
+        .get(*channel_id, session.user_id, cursor, limit)
+        .await
+    {
+        Ok(page) => page,
         Err(message::GetError::DatabaseError(e)) => {
             error!("Database error: {}", e);
             return err!(INTERNAL_SERVER_ERROR => ISE);
         }
     };
 
-    ok!(messages)
+    ok!(page)
 }
@@ -1,2 +1,5 @@
+pub mod delete;
+pub mod edit;
 pub mod get;
+pub mod revisions;
 pub mod send;
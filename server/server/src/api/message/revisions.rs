@@ -0,0 +1,46 @@
+use crate::prelude::*;
+
+/// The message was not found, or the user did not have permission to view
+/// its channel.
+pub const MESSAGE_NOT_FOUND: &str = "MessageNotFound";
+/// The user does not have permission to manage messages
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Returns every prior version of a message, oldest first. Only available
+/// to moderators.
+pub async fn revisions(
+    path: web::Path<(Snowflake, Snowflake)>,
+    session: Session,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (channel_id, message_id) = path.into_inner();
+
+    match db
+        .channel()
+        .get_user_permissions(channel_id, session.user_id)
+        .await
+    {
+        Ok(perms) if !perms.contains(role::Permissions::VIEW_CHANNELS) => {
+            return err!(MESSAGE_NOT_FOUND)
+        }
+        Ok(perms) if !perms.contains(role::Permissions::MANAGE_MESSAGES) => {
+            return err!(UNAUTHORIZED => PERMISSION_DENIED)
+        }
+        Ok(_) => {}
+        Err(channel::GetPermissionsError::NotFound) => return err!(MESSAGE_NOT_FOUND),
+        Err(channel::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let revisions = match db.message().get_revisions(channel_id, message_id).await {
+        Ok(revisions) => revisions,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(revisions)
+}
@@ -16,12 +16,15 @@ pub const MESSAGE_TOO_SHORT: &str = "MessageTooShort";
 pub const MESSAGE_TOO_LONG: &str = "MessageTooLong";
 /// The channel was not found or the user did not have permission to view it.
 pub const CHANNEL_NOT_FOUND: &str = "ChannelNotFound";
+/// The user does not have permission to send messages in the channel.
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
 
 pub async fn send(
     channel_id: web::Path<Snowflake>,
     body: Json<Params>,
     session: Session,
     message_sfgen: Data<Mutex<MessageSnowflakeGen>>,
+    gateway: Data<Gateway>,
     db: Data<DbPool>,
 ) -> impl Responder {
     if body.content.is_empty() {
@@ -31,6 +34,12 @@ pub async fn send(
         return err!(MESSAGE_TOO_LONG 10000);
     }
 
+    if let Err(response) =
+        verification::check(&db, session.user_id, verification::Action::SendMessage).await
+    {
+        return response;
+    }
+
     let message_id = { message_sfgen.lock().await.generate() };
     match db
         .message()
@@ -41,6 +50,9 @@ pub async fn send(
         Err(message::CreateError::NotFound) => {
             return err!(CHANNEL_NOT_FOUND);
         }
+        Err(message::CreateError::PermissionDenied) => {
+            return err!(UNAUTHORIZED => PERMISSION_DENIED);
+        }
         Err(message::CreateError::NotInserted) => {
             warn!("Message not inserted into database");
             return err!(INTERNAL_SERVER_ERROR => ISE);
@@ -51,5 +63,16 @@ pub async fn send(
         }
     };
 
+    // Notify everyone who can see the channel
+    match db.message().get_by_id(message_id).await {
+        Ok(Some(message)) => {
+            gateway
+                .dispatch_to_channel(&db, *channel_id, &gateway::Event::MessageCreate(message))
+                .await;
+        }
+        Ok(None) => warn!("Message not found after insertion"),
+        Err(message::GetError::DatabaseError(e)) => error!("Database error: {}", e),
+    }
+
     ok!(Response { message_id })
 }
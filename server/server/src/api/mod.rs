@@ -1,12 +1,24 @@
 mod account;
 pub mod authentication;
+mod ban;
 pub mod channel;
+mod dm;
 mod guild;
+mod guild_folder;
+mod invite;
 mod message;
+pub mod rate_limit;
+mod reaction;
+mod relationship;
 pub mod result;
+mod role;
+mod user;
+pub mod verification;
 
 use crate::prelude::*;
 
+// Every route is declared in the one `route!` table below
+#[allow(clippy::too_many_lines)]
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
     let json_config =
         web::JsonConfig::default()
@@ -37,6 +49,12 @@ pub fn init_routes(cfg: &mut web::ServiceConfig) {
 
     cfg.app_data(json_config);
 
+    let query_config = web::QueryConfig::default().error_handler(
+        |err, _req| actix_err!(BAD_REQUEST => QUERY_DESERIALIZE_ERROR err.to_string()),
+    );
+
+    cfg.app_data(query_config);
+
     cfg.default_service(web::route().to(|| async {
         #[allow(unused_mut)]
         let mut vec: Vec<String> = Vec::new();
@@ -47,28 +65,204 @@ pub fn init_routes(cfg: &mut web::ServiceConfig) {
     route!(cfg: {
         "/account" => {
             "/login" => {
-                post => (account::login::login),
+                "/mfa" => {
+                    post => (:(RateLimit::per_ip(10, 60)) account::login_mfa::login_mfa),
+                },
+                "/failed" => {
+                    get => (:(AuthMiddleware) account::list_failed_logins::list_failed_logins),
+                },
+                post => (:(RateLimit::per_ip(10, 60)) account::login::login),
             },
             "/register" => {
-                post => (account::register::register),
+                post => (:(RateLimit::per_ip(5, 60 * 60)) account::register::register),
+            },
+            "/me" => {
+                get => (:(AuthMiddleware) account::get_me::get_me),
+                patch => (:(AuthMiddleware) account::edit_me::edit_me),
+            },
+            "/logout" => {
+                post => (:(AuthMiddleware) account::logout::logout),
+            },
+            "/mfa" => {
+                "/enrol" => {
+                    post => (:(AuthMiddleware) account::enrol_mfa::enrol_mfa),
+                },
+                "/confirm" => {
+                    post => (:(AuthMiddleware) account::confirm_mfa::confirm_mfa),
+                },
+                "/disable" => {
+                    post => (:(AuthMiddleware) account::disable_mfa::disable_mfa),
+                },
+            },
+            "/password" => {
+                "/reset" => {
+                    "/confirm" => {
+                        post => (account::reset_password::reset_password),
+                    },
+                    post => (:(RateLimit::per_ip(5, 60 * 60)) account::request_password_reset::request_password_reset),
+                },
+                post => (:(AuthMiddleware) account::change_password::change_password),
+            },
+            "/verify_email" => {
+                "/resend" => {
+                    post => (:(RateLimit::per_user(3, 60 * 60), AuthMiddleware) account::resend_verification::resend_verification),
+                },
+                post => (account::verify_email::verify_email),
+            },
+            "/sessions" => {
+                "/{session_id}" => {
+                    delete => (:(AuthMiddleware) account::revoke_session::revoke_session),
+                },
+                get => (:(AuthMiddleware) account::list_sessions::list_sessions),
+                delete => (:(AuthMiddleware) account::revoke_other_sessions::revoke_other_sessions),
             },
         },
         "/guild" => {
             "/create" => {
-                post => (:(AuthMiddleware) guild::create::create),
+                post => (:(RateLimit::per_user(10, 60 * 60), AuthMiddleware) guild::create::create),
             },
             "/get_joined" => {
                 get => (:(AuthMiddleware) guild::get_joined::get_joined),
             },
+            "/order" => {
+                patch => (:(AuthMiddleware) guild::reorder::reorder),
+            },
+            "/folders" => {
+                "/{folder_id}" => {
+                    patch => (:(AuthMiddleware) guild_folder::edit::edit),
+                    delete => (:(AuthMiddleware) guild_folder::delete::delete),
+                },
+                get => (:(AuthMiddleware) guild_folder::list::list),
+                post => (:(AuthMiddleware) guild_folder::create::create),
+            },
+            "/{guild_id}" => {
+                "/invites" => {
+                    get => (:(AuthMiddleware) invite::list::list),
+                    post => (:(AuthMiddleware) invite::create::create),
+                },
+                "/transfer" => {
+                    post => (:(AuthMiddleware) guild::transfer::transfer),
+                },
+                "/leave" => {
+                    post => (:(AuthMiddleware) guild::leave::leave),
+                },
+                "/members" => {
+                    "/{user_id}" => {
+                        patch => (:(AuthMiddleware) guild::set_nickname::set_nickname),
+                        delete => (:(AuthMiddleware) guild::kick::kick),
+                    },
+                    get => (:(AuthMiddleware) guild::get_members::get_members),
+                },
+                "/bans" => {
+                    "/{user_id}" => {
+                        put => (:(AuthMiddleware) ban::create::create),
+                        delete => (:(AuthMiddleware) ban::revoke::revoke),
+                    },
+                    get => (:(AuthMiddleware) ban::list::list),
+                },
+                get => (:(AuthMiddleware) guild::get::get),
+                patch => (:(AuthMiddleware) guild::rename::rename),
+                delete => (:(AuthMiddleware) guild::delete::delete),
+            },
+        },
+        "/invite/{code}" => {
+            get => (invite::preview::preview),
+            post => (:(RateLimit::per_user(10, 60), AuthMiddleware) invite::accept::accept),
+            delete => (:(AuthMiddleware) invite::revoke::revoke),
         },
         "/channel/{guild_id}" => {
             "/create" => {
                 post => (:(AuthMiddleware) channel::create::create),
-            }
+            },
+            "/order" => {
+                patch => (:(AuthMiddleware) channel::reorder::reorder),
+            },
+            "/{channel_id}" => {
+                "/overwrites" => {
+                    "/{target_id}" => {
+                        put => (:(AuthMiddleware) channel::upsert_overwrite::upsert_overwrite),
+                        delete => (:(AuthMiddleware) channel::delete_overwrite::delete_overwrite),
+                    },
+                    get => (:(AuthMiddleware) channel::list_overwrites::list_overwrites),
+                },
+                "/move" => {
+                    post => (:(AuthMiddleware) channel::relocate::relocate),
+                },
+                "/collapse" => {
+                    put => (:(AuthMiddleware) channel::set_collapsed::set_collapsed),
+                },
+                patch => (:(AuthMiddleware) channel::edit::edit),
+                delete => (:(AuthMiddleware) channel::delete::delete),
+            },
+        },
+        "/dm" => {
+            "/group" => {
+                post => (:(RateLimit::per_user(10, 60 * 60), AuthMiddleware) dm::create_group::create_group),
+            },
+            "/{channel_id}" => {
+                "/members/{user_id}" => {
+                    put => (:(AuthMiddleware) dm::add_member::add_member),
+                    delete => (:(AuthMiddleware) dm::remove_member::remove_member),
+                },
+            },
+            get => (:(AuthMiddleware) dm::list::list),
+            post => (:(AuthMiddleware) dm::open::open),
+        },
+        "/relationships" => {
+            "/{user_id}" => {
+                "/accept" => {
+                    post => (:(AuthMiddleware) relationship::accept::accept),
+                },
+                "/decline" => {
+                    post => (:(AuthMiddleware) relationship::decline::decline),
+                },
+                "/block" => {
+                    post => (:(AuthMiddleware) relationship::block::block),
+                },
+                delete => (:(AuthMiddleware) relationship::remove::remove),
+            },
+            get => (:(AuthMiddleware) relationship::list::list),
+            post => (:(RateLimit::per_user(20, 60 * 60), AuthMiddleware) relationship::request::request),
+        },
+        "/user/{user_id}" => {
+            get => (:(AuthMiddleware) user::get::get),
+        },
+        "/role/{guild_id}" => {
+            "/order" => {
+                patch => (:(AuthMiddleware) role::reorder::reorder),
+            },
+            "/{role_id}" => {
+                "/members/{user_id}" => {
+                    put => (:(AuthMiddleware) role::assign::assign),
+                    delete => (:(AuthMiddleware) role::unassign::unassign),
+                },
+                patch => (:(AuthMiddleware) role::edit::edit),
+                delete => (:(AuthMiddleware) role::delete::delete),
+            },
+            get => (:(AuthMiddleware) role::list::list),
+            post => (:(AuthMiddleware) role::create::create),
         },
         "/message/{channel_id}" => {
-            post => (:(AuthMiddleware) message::send::send),
+            "/{message_id}" => {
+                "/revisions" => {
+                    get => (:(AuthMiddleware) message::revisions::revisions),
+                },
+                "/reactions/{emoji}" => {
+                    "/{user_id}" => {
+                        delete => (:(AuthMiddleware) reaction::remove_user::remove_user),
+                    },
+                    put => (:(RateLimit::per_user(10, 5), AuthMiddleware) reaction::add::add),
+                    delete => (:(AuthMiddleware) reaction::remove::remove),
+                    get => (:(AuthMiddleware) reaction::list::list),
+                },
+                patch => (:(RateLimit::per_user(5, 5), AuthMiddleware) message::edit::edit),
+                delete => (:(AuthMiddleware) message::delete::delete),
+            },
+            post => (:(RateLimit::per_user(5, 5), AuthMiddleware) message::send::send),
             get => (:(AuthMiddleware) message::get::get),
         },
+        "/gateway" => {
+            get => (gateway::connect),
+        },
     });
 }
@@ -0,0 +1,306 @@
+//! A middleware to limit how often an endpoint can be used.
+//!
+//! Every endpoint wrapped in a [`RateLimit`] has a token bucket for each
+//! user, access token or IP address that uses it. Each request takes a token
+//! from the bucket, and the bucket refills at a steady rate. Requests made
+//! when the bucket is empty are rejected with `RateLimited`.
+//!
+//! Every response includes the state of the bucket in headers:
+//! - `X-RateLimit-Limit`: How many requests can be made at once.
+//! - `X-RateLimit-Remaining`: How many requests can be made right now.
+//! - `X-RateLimit-Reset-After`: How many seconds until the bucket is full.
+//!
+//! Setting `DISABLE_RATE_LIMITS` to `true` turns off every limit, which is
+//! only meant for development.
+
+use crate::prelude::*;
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error::InternalError,
+    http::header::{self, HeaderMap, HeaderName, HeaderValue},
+    Error, HttpMessage,
+};
+use std::{
+    collections::HashMap,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    sync::Mutex,
+};
+
+/// How many requests to the [`MemoryStore`] are made between removing full
+/// buckets from it.
+const SWEEP_INTERVAL: u64 = 1024;
+
+/// What requests are counted together.
+#[derive(Debug, Clone, Copy)]
+pub enum Key {
+    /// Every request by the same user. Requests without a [`Session`] are
+    /// counted by IP address.
+    User,
+    /// Every request made with the same access token. Requests without a
+    /// [`Session`] are counted by IP address.
+    Token,
+    /// Every request from the same IP address.
+    Ip,
+}
+
+/// The most requests that can be made at once, and how quickly more can be
+/// made afterwards.
+#[derive(Debug, Clone, Copy)]
+pub struct Limit {
+    /// The size of the bucket.
+    pub capacity: u32,
+    /// How long it takes an empty bucket to refill, in milliseconds.
+    pub period: i64,
+}
+impl Limit {
+    /// How many tokens are added to the bucket every millisecond.
+    fn rate(self) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let period = self.period as f64;
+        f64::from(self.capacity) / period
+    }
+}
+
+/// The result of taking a token from a bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct Decision {
+    /// Whether the request can be made.
+    pub allowed: bool,
+    /// How many more requests can be made right now.
+    pub remaining: u32,
+    /// How long until the bucket is full again, in milliseconds.
+    pub reset_after: i64,
+    /// How long until the next request can be made, in milliseconds. Zero if
+    /// the request was allowed.
+    pub retry_after: i64,
+}
+
+/// Somewhere to keep buckets.
+pub trait Store: Send + Sync {
+    /// Takes a token from the bucket with the key, creating a full bucket if
+    /// there isn't one. `now` is in milliseconds.
+    fn take(&self, key: &str, limit: Limit, now: i64) -> Decision;
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: i64,
+}
+
+/// Keeps buckets in memory, so limits are per server and reset when it
+/// restarts.
+#[derive(Default)]
+pub struct MemoryStore {
+    buckets: Mutex<HashMap<String, (Bucket, Limit)>>,
+    requests: std::sync::atomic::AtomicU64,
+}
+impl MemoryStore {
+    /// Forgets buckets that have refilled, as they're the same as new ones.
+    fn sweep(buckets: &mut HashMap<String, (Bucket, Limit)>, now: i64) {
+        buckets.retain(|_, (bucket, limit)| {
+            #[allow(clippy::cast_precision_loss)]
+            let refilled = bucket.tokens + (now - bucket.updated_at) as f64 * limit.rate();
+            refilled < f64::from(limit.capacity)
+        });
+    }
+}
+impl Store for MemoryStore {
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn take(&self, key: &str, limit: Limit, now: i64) -> Decision {
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let requests = self
+            .requests
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if requests % SWEEP_INTERVAL == 0 {
+            Self::sweep(&mut buckets, now);
+        }
+
+        let capacity = f64::from(limit.capacity);
+        let rate = limit.rate();
+        let (bucket, _) = buckets.entry(key.to_string()).or_insert((
+            Bucket {
+                tokens: capacity,
+                updated_at: now,
+            },
+            limit,
+        ));
+
+        let elapsed = (now - bucket.updated_at).max(0) as f64;
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated_at = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        let retry_after = if allowed {
+            bucket.tokens -= 1.0;
+            0
+        } else {
+            ((1.0 - bucket.tokens) / rate).ceil() as i64
+        };
+
+        Decision {
+            allowed,
+            remaining: bucket.tokens.floor() as u32,
+            reset_after: ((capacity - bucket.tokens) / rate).ceil() as i64,
+            retry_after,
+        }
+    }
+}
+
+/// Limits how often the endpoint it wraps can be used.
+///
+/// Middlewares later in a route's `:(...)` list run first, so limits keyed
+/// by user or token must come before [`AuthMiddleware`], like
+/// `:(RateLimit::per_user(5, 5), AuthMiddleware)`.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    key: Key,
+    limit: Limit,
+}
+impl RateLimit {
+    /// Allows `capacity` requests every `seconds` seconds for each user.
+    pub const fn per_user(capacity: u32, seconds: i64) -> Self {
+        Self::new(Key::User, capacity, seconds)
+    }
+
+    /// Allows `capacity` requests every `seconds` seconds for each access
+    /// token.
+    pub const fn per_token(capacity: u32, seconds: i64) -> Self {
+        Self::new(Key::Token, capacity, seconds)
+    }
+
+    /// Allows `capacity` requests every `seconds` seconds for each IP
+    /// address.
+    pub const fn per_ip(capacity: u32, seconds: i64) -> Self {
+        Self::new(Key::Ip, capacity, seconds)
+    }
+
+    const fn new(key: Key, capacity: u32, seconds: i64) -> Self {
+        Self {
+            key,
+            limit: Limit {
+                capacity,
+                period: seconds * 1000,
+            },
+        }
+    }
+
+    /// The key of the bucket the request takes from. Each route has its own
+    /// buckets.
+    fn bucket_key(&self, req: &ServiceRequest) -> String {
+        let session = req.extensions().get::<Session>().cloned();
+        let key = match (self.key, session) {
+            (Key::User, Some(session)) => format!("user:{}", session.user_id),
+            (Key::Token, Some(session)) => format!("token:{}", session.token),
+            (Key::User | Key::Token, None) | (Key::Ip, _) => {
+                format!("ip:{}", ip::from_request(req.request()))
+            }
+        };
+        let route = req
+            .match_pattern()
+            .unwrap_or_else(|| req.path().to_string());
+        format!("{} {route} {key}", req.method())
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = InnerLimiter<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(InnerLimiter {
+            service: Rc::new(service),
+            rate_limit: *self,
+        }))
+    }
+}
+
+pub struct InnerLimiter<S> {
+    service: Rc<S>,
+    rate_limit: RateLimit,
+}
+
+impl<S, B> Service<ServiceRequest> for InnerLimiter<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let rate_limit = self.rate_limit;
+
+        Box::pin(async move {
+            if dotenvy::var("DISABLE_RATE_LIMITS").map_or(false, |disable| disable == "true") {
+                return service.call(req).await;
+            }
+
+            let (Some(store), Some(clock)) = (
+                req.app_data::<Data<dyn Store>>(),
+                req.app_data::<Data<dyn time::Clock>>(),
+            ) else {
+                warn!("No rate limit store or clock found in request");
+                return Err(actix_err!(INTERNAL_SERVER_ERROR => ISE));
+            };
+
+            let decision = store.take(&rate_limit.bucket_key(&req), rate_limit.limit, clock.now());
+
+            if !decision.allowed {
+                #[allow(clippy::cast_precision_loss)]
+                let retry_after = decision.retry_after as f64 / 1000.0;
+                let mut response = err!(TOO_MANY_REQUESTS => RATE_LIMITED map! { retry_after });
+                insert_headers(response.headers_mut(), rate_limit.limit, decision);
+                // Whole seconds, rounded up
+                response.headers_mut().insert(
+                    header::RETRY_AFTER,
+                    HeaderValue::from((decision.retry_after + 999) / 1000),
+                );
+                return Err(InternalError::from_response(RATE_LIMITED, response).into());
+            }
+
+            let mut response = service.call(req).await?;
+            insert_headers(response.headers_mut(), rate_limit.limit, decision);
+            Ok(response)
+        })
+    }
+}
+
+/// Adds the `X-RateLimit-*` headers to a response.
+fn insert_headers(headers: &mut HeaderMap, limit: Limit, decision: Decision) {
+    #[allow(clippy::cast_precision_loss)]
+    let reset_after = decision.reset_after as f64 / 1000.0;
+    let values = [
+        ("x-ratelimit-limit", limit.capacity.to_string()),
+        ("x-ratelimit-remaining", decision.remaining.to_string()),
+        ("x-ratelimit-reset-after", reset_after.to_string()),
+    ];
+    for (name, value) in values {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(HeaderName::from_static(name), value);
+        }
+    }
+}
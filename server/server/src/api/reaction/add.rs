@@ -0,0 +1,53 @@
+use crate::prelude::*;
+
+use super::{check_message, parse_emoji};
+
+/// The user does not have permission to send messages in the channel
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+/// The custom emoji does not exist, or belongs to a guild other than the
+/// message's
+pub const EMOJI_NOT_FOUND: &str = "EmojiNotFound";
+
+/// Reacts to a message with an emoji. Reacting again with the same emoji
+/// does nothing.
+pub async fn add(
+    path: web::Path<(Snowflake, Snowflake, String)>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (channel_id, message_id, emoji) = path.into_inner();
+    let emoji = match parse_emoji(&emoji) {
+        Ok(emoji) => emoji,
+        Err(res) => return res,
+    };
+
+    let perms = match check_message(&db, channel_id, message_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(res) => return res,
+    };
+    if !perms.contains(role::Permissions::SEND_MESSAGES) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    let added = match db.reaction().add(message_id, session.user_id, &emoji).await {
+        Ok(added) => added,
+        Err(reaction::AddError::EmojiNotFound) => return err!(EMOJI_NOT_FOUND),
+        Err(reaction::AddError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    if added {
+        let event = gateway::Event::MessageReactionAdd {
+            channel_id,
+            message_id,
+            user_id: session.user_id,
+            emoji,
+        };
+        gateway.dispatch_to_channel(&db, channel_id, &event).await;
+    }
+
+    ok!(())
+}
@@ -0,0 +1,58 @@
+use crate::prelude::*;
+
+use super::{check_message, parse_emoji};
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// Get the users after this user
+    #[serde(default)]
+    after: Option<Snowflake>,
+    /// How many users to get, between 1 and 100. Defaults to 25
+    #[serde(default)]
+    limit: Option<i64>,
+}
+
+/// The limit was not between 1 and 100
+pub const INVALID_LIMIT: &str = "InvalidLimit";
+
+/// The amount of users returned if no limit is given.
+const DEFAULT_LIMIT: i64 = 25;
+/// The most users that can be requested at once.
+const MAX_LIMIT: i64 = 100;
+
+/// Returns the users who reacted to a message with an emoji, in order of
+/// their IDs.
+pub async fn list(
+    path: web::Path<(Snowflake, Snowflake, String)>,
+    query: web::Query<Params>,
+    session: Session,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (channel_id, message_id, emoji) = path.into_inner();
+    let emoji = match parse_emoji(&emoji) {
+        Ok(emoji) => emoji,
+        Err(res) => return res,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return err!(INVALID_LIMIT map!{ min => 1, max => MAX_LIMIT });
+    }
+
+    if let Err(res) = check_message(&db, channel_id, message_id, session.user_id).await {
+        return res;
+    }
+
+    let users = match db
+        .reaction()
+        .get_users(message_id, &emoji, query.after, limit)
+        .await
+    {
+        Ok(users) => users,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(users)
+}
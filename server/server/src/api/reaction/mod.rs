@@ -0,0 +1,47 @@
+pub mod add;
+pub mod list;
+pub mod remove;
+pub mod remove_user;
+
+use crate::prelude::*;
+
+/// The message was not found, was deleted, or the user does not have
+/// permission to view its channel
+pub const MESSAGE_NOT_FOUND: &str = "MessageNotFound";
+/// The emoji was neither a Unicode emoji nor the ID of a custom emoji
+pub const INVALID_EMOJI: &str = "InvalidEmoji";
+
+/// Parses the emoji from a reaction URL, returning the error response to send
+/// if it isn't one.
+fn parse_emoji(emoji: &str) -> Result<reaction::Emoji, HttpResponse> {
+    reaction::Emoji::parse(emoji).ok_or_else(|| err!(INVALID_EMOJI))
+}
+
+/// Checks the user can see a message that hasn't been deleted, returning
+/// their permissions in its channel, or the error response to send if not.
+async fn check_message(
+    db: &DbPool,
+    channel_id: Snowflake,
+    message_id: Snowflake,
+    user_id: Snowflake,
+) -> Result<role::MemberPermissions, HttpResponse> {
+    let perms = match db.channel().get_user_permissions(channel_id, user_id).await {
+        Ok(perms) if perms.contains(role::Permissions::VIEW_CHANNELS) => perms,
+        Ok(_) | Err(channel::GetPermissionsError::NotFound) => {
+            return Err(err!(MESSAGE_NOT_FOUND));
+        }
+        Err(channel::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return Err(err!(INTERNAL_SERVER_ERROR => ISE));
+        }
+    };
+
+    match db.message().get_by_id(message_id).await {
+        Ok(Some(message)) if message.channel_id == channel_id && !message.deleted => Ok(perms),
+        Ok(_) => Err(err!(MESSAGE_NOT_FOUND)),
+        Err(message::GetError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            Err(err!(INTERNAL_SERVER_ERROR => ISE))
+        }
+    }
+}
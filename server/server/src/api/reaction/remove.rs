@@ -0,0 +1,47 @@
+use crate::prelude::*;
+
+use super::{check_message, parse_emoji};
+
+/// The user has not reacted to the message with the emoji
+pub const REACTION_NOT_FOUND: &str = "ReactionNotFound";
+
+/// Removes the user's own reaction from a message.
+pub async fn remove(
+    path: web::Path<(Snowflake, Snowflake, String)>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (channel_id, message_id, emoji) = path.into_inner();
+    let emoji = match parse_emoji(&emoji) {
+        Ok(emoji) => emoji,
+        Err(res) => return res,
+    };
+
+    if let Err(res) = check_message(&db, channel_id, message_id, session.user_id).await {
+        return res;
+    }
+
+    match db
+        .reaction()
+        .remove(message_id, session.user_id, &emoji)
+        .await
+    {
+        Ok(()) => {}
+        Err(reaction::RemoveError::NotFound) => return err!(REACTION_NOT_FOUND),
+        Err(reaction::RemoveError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let event = gateway::Event::MessageReactionRemove {
+        channel_id,
+        message_id,
+        user_id: session.user_id,
+        emoji,
+    };
+    gateway.dispatch_to_channel(&db, channel_id, &event).await;
+
+    ok!(())
+}
@@ -0,0 +1,50 @@
+use crate::prelude::*;
+
+use super::{check_message, parse_emoji};
+
+/// The user has not reacted to the message with the emoji
+pub const REACTION_NOT_FOUND: &str = "ReactionNotFound";
+/// The user does not have permission to manage messages
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Removes another user's reaction from a message. Needs permission to
+/// manage messages, unless it is the user's own reaction.
+pub async fn remove_user(
+    path: web::Path<(Snowflake, Snowflake, String, Snowflake)>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (channel_id, message_id, emoji, user_id) = path.into_inner();
+    let emoji = match parse_emoji(&emoji) {
+        Ok(emoji) => emoji,
+        Err(res) => return res,
+    };
+
+    let perms = match check_message(&db, channel_id, message_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(res) => return res,
+    };
+    if user_id != session.user_id && !perms.contains(role::Permissions::MANAGE_MESSAGES) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    match db.reaction().remove(message_id, user_id, &emoji).await {
+        Ok(()) => {}
+        Err(reaction::RemoveError::NotFound) => return err!(REACTION_NOT_FOUND),
+        Err(reaction::RemoveError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let event = gateway::Event::MessageReactionRemove {
+        channel_id,
+        message_id,
+        user_id,
+        emoji,
+    };
+    gateway.dispatch_to_channel(&db, channel_id, &event).await;
+
+    ok!(())
+}
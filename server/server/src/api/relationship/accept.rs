@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+/// The other user has not sent the user a friend request
+pub const NO_REQUEST: &str = "NoFriendRequest";
+
+/// Accepts a friend request, making the users friends.
+pub async fn accept(
+    user_id: web::Path<Snowflake>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    match db.relationship().accept(session.user_id, *user_id).await {
+        Ok(()) => {}
+        Err(relationship::AcceptError::NoRequest) => return err!(NO_REQUEST),
+        Err(relationship::AcceptError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    super::notify(&db, &gateway, session.user_id, *user_id).await;
+
+    ok!(())
+}
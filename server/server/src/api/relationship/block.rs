@@ -0,0 +1,29 @@
+use crate::prelude::*;
+
+/// The user tried to block themselves
+pub const CANNOT_BLOCK_SELF: &str = "CannotBlockSelf";
+/// The user being blocked does not exist
+pub const USER_NOT_FOUND: &str = "UserNotFound";
+
+/// Blocks a user, removing them as a friend. They can no longer send the
+/// user friend requests or DMs.
+pub async fn block(
+    user_id: web::Path<Snowflake>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    match db.relationship().block(session.user_id, *user_id).await {
+        Ok(()) => {}
+        Err(relationship::BlockError::CannotBlockSelf) => return err!(CANNOT_BLOCK_SELF),
+        Err(relationship::BlockError::UserNotFound) => return err!(USER_NOT_FOUND),
+        Err(relationship::BlockError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    super::notify(&db, &gateway, session.user_id, *user_id).await;
+
+    ok!(())
+}
@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+/// The other user has not sent the user a friend request
+pub const NO_REQUEST: &str = "NoFriendRequest";
+
+/// Declines a friend request.
+pub async fn decline(
+    user_id: web::Path<Snowflake>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    match db.relationship().decline(session.user_id, *user_id).await {
+        Ok(()) => {}
+        Err(relationship::DeclineError::NoRequest) => return err!(NO_REQUEST),
+        Err(relationship::DeclineError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    super::notify(&db, &gateway, session.user_id, *user_id).await;
+
+    ok!(())
+}
@@ -0,0 +1,13 @@
+use crate::prelude::*;
+
+/// Returns the user's friends, friend requests and blocked users, newest
+/// first.
+pub async fn list(session: Session, db: Data<DbPool>) -> impl Responder {
+    match db.relationship().get_all(session.user_id).await {
+        Ok(relationships) => ok!(relationships),
+        Err(e) => {
+            error!("Database error: {}", e);
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+    }
+}
@@ -0,0 +1,24 @@
+pub mod accept;
+pub mod block;
+pub mod decline;
+pub mod list;
+pub mod remove;
+pub mod request;
+
+use crate::prelude::*;
+
+/// Sends both users how they now relate to each other, after their
+/// relationship changed.
+async fn notify(db: &DbPool, gateway: &Gateway, user: Snowflake, target: Snowflake) {
+    for (user, target) in [(user, target), (target, user)] {
+        let event = match db.relationship().get(user, target).await {
+            Ok(Some(relationship)) => gateway::Event::RelationshipAdd(relationship),
+            Ok(None) => gateway::Event::RelationshipRemove { user_id: target },
+            Err(e) => {
+                error!("Database error: {}", e);
+                continue;
+            }
+        };
+        gateway.dispatch(&[user], &event).await;
+    }
+}
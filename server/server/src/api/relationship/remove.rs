@@ -0,0 +1,26 @@
+use crate::prelude::*;
+
+/// The user has no relationship with the other user
+pub const RELATIONSHIP_NOT_FOUND: &str = "RelationshipNotFound";
+
+/// Removes a friend, cancels or declines a friend request, or unblocks a
+/// user, depending on how the user relates to them.
+pub async fn remove(
+    user_id: web::Path<Snowflake>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    match db.relationship().remove(session.user_id, *user_id).await {
+        Ok(()) => {}
+        Err(relationship::RemoveError::NotFound) => return err!(RELATIONSHIP_NOT_FOUND),
+        Err(relationship::RemoveError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    super::notify(&db, &gateway, session.user_id, *user_id).await;
+
+    ok!(())
+}
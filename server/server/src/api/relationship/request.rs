@@ -0,0 +1,60 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The username of the user to send the request to
+    username: String,
+    /// The discriminator of the user to send the request to
+    discrim: i16,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Response {
+    /// `outgoing` if the request was sent, or `friend` if the other user had
+    /// already sent one and so they are now friends
+    kind: relationship::RelationshipKind,
+}
+
+/// No user has the username and discriminator
+pub const USER_NOT_FOUND: &str = "UserNotFound";
+/// The user tried to send a friend request to themselves
+pub const CANNOT_FRIEND_SELF: &str = "CannotFriendSelf";
+/// The user has blocked the other user, or been blocked by them
+pub const BLOCKED: &str = "Blocked";
+/// The users are already friends
+pub const ALREADY_FRIENDS: &str = "AlreadyFriends";
+/// The user already sent a friend request to the other user
+pub const ALREADY_REQUESTED: &str = "AlreadyRequested";
+
+/// Sends a friend request to a user by their username and discriminator.
+pub async fn request(
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let target = match db.user().get_by_tag(&req.username, req.discrim).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return err!(USER_NOT_FOUND),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let kind = match db.relationship().request(session.user_id, target.id).await {
+        Ok(kind) => kind,
+        Err(relationship::RequestError::CannotFriendSelf) => return err!(CANNOT_FRIEND_SELF),
+        Err(relationship::RequestError::Blocked) => return err!(FORBIDDEN => BLOCKED),
+        Err(relationship::RequestError::AlreadyFriends) => return err!(ALREADY_FRIENDS),
+        Err(relationship::RequestError::AlreadyRequested) => return err!(ALREADY_REQUESTED),
+        Err(relationship::RequestError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    super::notify(&db, &gateway, session.user_id, target.id).await;
+
+    ok!(Response { kind })
+}
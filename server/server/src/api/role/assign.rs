@@ -0,0 +1,61 @@
+use crate::prelude::*;
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The role was not found in the guild, or is the default role
+pub const ROLE_NOT_FOUND: &str = "RoleNotFound";
+/// The user being given the role is not in the guild
+pub const MEMBER_NOT_FOUND: &str = "MemberNotFound";
+/// The user does not have permission to manage roles or the role is not
+/// below their highest role
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Gives a member a role.
+pub async fn assign(
+    path: web::Path<(Snowflake, Snowflake, Snowflake)>,
+    session: Session,
+    guild_member_role_sfgen: Data<Mutex<GuildMemberRoleSnowflakeGen>>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, role_id, user_id) = path.into_inner();
+
+    // Check the user has permission to assign this role
+    let perms = match db.guild().get_permissions(guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => {
+            return err!(GUILD_NOT_FOUND);
+        }
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_ROLES) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    let role = match db.role().get(guild_id, role_id).await {
+        Ok(Some(role)) => role,
+        Ok(None) => return err!(ROLE_NOT_FOUND),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.outranks(role.order) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    let id = { guild_member_role_sfgen.lock().await.generate() };
+    match db.role().assign(id, guild_id, role_id, user_id).await {
+        Ok(()) => {}
+        Err(role::AssignError::RoleNotFound) => return err!(ROLE_NOT_FOUND),
+        Err(role::AssignError::MemberNotFound) => return err!(MEMBER_NOT_FOUND),
+        Err(role::AssignError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(())
+}
@@ -0,0 +1,90 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// Name of the role, between 1 and 32 characters
+    name: String,
+    /// The hex color code of the role, without the leading `#`
+    #[serde(default)]
+    color: Option<String>,
+    /// The permissions the role grants
+    #[serde(default)]
+    permissions: Option<role::Permissions>,
+}
+
+/// The name was shorter than 1 character
+pub const NAME_TOO_SHORT: &str = "NameTooShort";
+/// The name was longer than 32 characters
+pub const NAME_TOO_LONG: &str = "NameTooLong";
+/// The color was not a 6-digit hex code
+pub const INVALID_COLOR: &str = "InvalidColor";
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The user does not have permission to manage roles, or tried to grant
+/// permissions they do not have themselves
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+pub async fn create(
+    guild_id: web::Path<Snowflake>,
+    req: Json<Params>,
+    session: Session,
+    role_sfgen: Data<Mutex<RoleSnowflakeGen>>,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    if req.name.is_empty() {
+        return err!(NAME_TOO_SHORT 1);
+    }
+    if req.name.chars().count() > 32 {
+        return err!(NAME_TOO_LONG 32);
+    }
+    let color = req.color.as_deref().unwrap_or("000000");
+    if !validation::validate_color(color) {
+        return err!(INVALID_COLOR);
+    }
+    let permissions = req.permissions.unwrap_or(role::Permissions::empty());
+
+    // Check the user has permission to create a role
+    let perms = match db.guild().get_permissions(*guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => {
+            return err!(GUILD_NOT_FOUND);
+        }
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_ROLES) || !perms.contains(permissions) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    let role_id = { role_sfgen.lock().await.generate() };
+    let role = match db
+        .role()
+        .create(
+            role_id,
+            *guild_id,
+            &req.name,
+            &color.to_lowercase(),
+            permissions,
+        )
+        .await
+    {
+        Ok(role) => role,
+        Err(role::CreateError::NotInserted) => {
+            warn!("Role not inserted into database");
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+        Err(role::CreateError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    gateway
+        .dispatch_to_guild(&db, *guild_id, &gateway::Event::RoleCreate(role.clone()))
+        .await;
+
+    ok!(role)
+}
@@ -0,0 +1,62 @@
+use crate::prelude::*;
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The role was not found in the guild
+pub const ROLE_NOT_FOUND: &str = "RoleNotFound";
+/// The default role of a guild cannot be deleted
+pub const DEFAULT_ROLE: &str = "DefaultRole";
+/// The user does not have permission to manage roles or the role is not
+/// below their highest role
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+pub async fn delete(
+    path: web::Path<(Snowflake, Snowflake)>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, role_id) = path.into_inner();
+
+    // Check the user has permission to delete this role
+    let perms = match db.guild().get_permissions(guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => {
+            return err!(GUILD_NOT_FOUND);
+        }
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_ROLES) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    let role = match db.role().get(guild_id, role_id).await {
+        Ok(Some(role)) => role,
+        Ok(None) => return err!(ROLE_NOT_FOUND),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.outranks(role.order) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    match db.role().delete(guild_id, role_id).await {
+        Ok(()) => {}
+        Err(role::DeleteError::DefaultRole) => return err!(DEFAULT_ROLE),
+        Err(role::DeleteError::NotFound) => return err!(ROLE_NOT_FOUND),
+        Err(role::DeleteError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let event = gateway::Event::RoleDelete { guild_id, role_id };
+    gateway.dispatch_to_guild(&db, guild_id, &event).await;
+
+    ok!(())
+}
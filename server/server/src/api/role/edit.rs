@@ -0,0 +1,110 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// The new name of the role, between 1 and 32 characters
+    #[serde(default)]
+    name: Option<String>,
+    /// The new hex color code of the role, without the leading `#`
+    #[serde(default)]
+    color: Option<String>,
+    /// The new permissions the role grants
+    #[serde(default)]
+    permissions: Option<role::Permissions>,
+}
+
+/// The name was shorter than 1 character
+pub const NAME_TOO_SHORT: &str = "NameTooShort";
+/// The name was longer than 32 characters
+pub const NAME_TOO_LONG: &str = "NameTooLong";
+/// The color was not a 6-digit hex code
+pub const INVALID_COLOR: &str = "InvalidColor";
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The role was not found in the guild
+pub const ROLE_NOT_FOUND: &str = "RoleNotFound";
+/// The user does not have permission to manage roles, the role is not below
+/// their highest role, or they tried to grant permissions they do not have
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+pub async fn edit(
+    path: web::Path<(Snowflake, Snowflake)>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, role_id) = path.into_inner();
+
+    if let Some(name) = &req.name {
+        if name.is_empty() {
+            return err!(NAME_TOO_SHORT 1);
+        }
+        if name.chars().count() > 32 {
+            return err!(NAME_TOO_LONG 32);
+        }
+    }
+    if let Some(color) = &req.color {
+        if !validation::validate_color(color) {
+            return err!(INVALID_COLOR);
+        }
+    }
+
+    // Check the user has permission to edit this role
+    let perms = match db.guild().get_permissions(guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => {
+            return err!(GUILD_NOT_FOUND);
+        }
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_ROLES) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+    if let Some(permissions) = req.permissions {
+        if !perms.contains(permissions) {
+            return err!(UNAUTHORIZED => PERMISSION_DENIED);
+        }
+    }
+
+    let role = match db.role().get(guild_id, role_id).await {
+        Ok(Some(role)) => role,
+        Ok(None) => return err!(ROLE_NOT_FOUND),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.outranks(role.order) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    let color = req.color.as_deref().map(str::to_lowercase);
+    let role = match db
+        .role()
+        .edit(
+            guild_id,
+            role_id,
+            req.name.as_deref(),
+            color.as_deref(),
+            req.permissions,
+        )
+        .await
+    {
+        Ok(role) => role,
+        Err(role::EditError::NotFound) => return err!(ROLE_NOT_FOUND),
+        Err(role::EditError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    gateway
+        .dispatch_to_guild(&db, guild_id, &gateway::Event::RoleUpdate(role.clone()))
+        .await;
+
+    ok!(role)
+}
@@ -0,0 +1,31 @@
+use crate::prelude::*;
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+
+/// Returns every role in a guild, from highest to lowest.
+pub async fn list(
+    guild_id: web::Path<Snowflake>,
+    session: Session,
+    db: Data<DbPool>,
+) -> impl Responder {
+    // Only members of the guild can see its roles
+    match db.guild().get_permissions(*guild_id, session.user_id).await {
+        Ok(_) => {}
+        Err(guild::GetPermissionsError::NotInGuild) => return err!(GUILD_NOT_FOUND),
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    let roles = match db.role().get_all(*guild_id).await {
+        Ok(roles) => roles,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(roles)
+}
@@ -0,0 +1,7 @@
+pub mod assign;
+pub mod create;
+pub mod delete;
+pub mod edit;
+pub mod list;
+pub mod reorder;
+pub mod unassign;
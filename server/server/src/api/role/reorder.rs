@@ -0,0 +1,86 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    /// Every role in the guild except the default role, from highest to
+    /// lowest
+    roles: Vec<Snowflake>,
+}
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The roles given were not exactly the roles in the guild
+pub const ROLES_MISMATCH: &str = "RolesMismatch";
+/// The user does not have permission to manage roles, or tried to move a
+/// role that is not below their highest role
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+pub async fn reorder(
+    guild_id: web::Path<Snowflake>,
+    req: Json<Params>,
+    session: Session,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> impl Responder {
+    // Check the user has permission to reorder roles
+    let perms = match db.guild().get_permissions(*guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => {
+            return err!(GUILD_NOT_FOUND);
+        }
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_ROLES) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    let roles = match db.role().get_all(*guild_id).await {
+        Ok(roles) => roles,
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    // Every role that moves must stay below the user's highest role.
+    // The first role listed ends up with the highest order.
+    let count = req.roles.len();
+    let mut moved = vec![];
+    for (index, role_id) in req.roles.iter().enumerate() {
+        let Some(role) = roles.iter().find(|r| r.id == *role_id) else {
+            return err!(ROLES_MISMATCH);
+        };
+        let Ok(order) = i32::try_from(count - index) else {
+            return err!(ROLES_MISMATCH);
+        };
+        if order != role.order {
+            if !perms.outranks(role.order) || !perms.outranks(order) {
+                return err!(UNAUTHORIZED => PERMISSION_DENIED);
+            }
+            moved.push(role::Role {
+                order,
+                ..role.clone()
+            });
+        }
+    }
+
+    match db.role().reorder(*guild_id, &req.roles).await {
+        Ok(()) => {}
+        Err(role::ReorderError::Mismatch) => return err!(ROLES_MISMATCH),
+        Err(role::ReorderError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    for role in moved {
+        gateway
+            .dispatch_to_guild(&db, *guild_id, &gateway::Event::RoleUpdate(role))
+            .await;
+    }
+
+    ok!(())
+}
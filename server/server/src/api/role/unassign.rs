@@ -0,0 +1,58 @@
+use crate::prelude::*;
+
+/// The guild was not found or the user is not in the guild
+pub const GUILD_NOT_FOUND: &str = "GuildNotFound";
+/// The role was not found in the guild
+pub const ROLE_NOT_FOUND: &str = "RoleNotFound";
+/// The member does not have the role
+pub const NOT_ASSIGNED: &str = "NotAssigned";
+/// The user does not have permission to manage roles or the role is not
+/// below their highest role
+pub const PERMISSION_DENIED: &str = "PermissionDenied";
+
+/// Takes a role away from a member.
+pub async fn unassign(
+    path: web::Path<(Snowflake, Snowflake, Snowflake)>,
+    session: Session,
+    db: Data<DbPool>,
+) -> impl Responder {
+    let (guild_id, role_id, user_id) = path.into_inner();
+
+    // Check the user has permission to unassign this role
+    let perms = match db.guild().get_permissions(guild_id, session.user_id).await {
+        Ok(perms) => perms,
+        Err(guild::GetPermissionsError::NotInGuild) => {
+            return err!(GUILD_NOT_FOUND);
+        }
+        Err(guild::GetPermissionsError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.contains(role::Permissions::MANAGE_ROLES) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    let role = match db.role().get(guild_id, role_id).await {
+        Ok(Some(role)) => role,
+        Ok(None) => return err!(ROLE_NOT_FOUND),
+        Err(e) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+    if !perms.outranks(role.order) {
+        return err!(UNAUTHORIZED => PERMISSION_DENIED);
+    }
+
+    match db.role().unassign(guild_id, role_id, user_id).await {
+        Ok(()) => {}
+        Err(role::UnassignError::NotAssigned) => return err!(NOT_ASSIGNED),
+        Err(role::UnassignError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            return err!(INTERNAL_SERVER_ERROR => ISE);
+        }
+    };
+
+    ok!(())
+}
@@ -0,0 +1,16 @@
+use crate::prelude::*;
+
+/// The user does not exist
+pub const USER_NOT_FOUND: &str = "UserNotFound";
+
+/// Gets a user's public profile.
+pub async fn get(user_id: web::Path<Snowflake>, db: Data<DbPool>) -> impl Responder {
+    match db.user().get(*user_id).await {
+        Ok(Some(user)) => ok!(user),
+        Ok(None) => err!(USER_NOT_FOUND),
+        Err(e) => {
+            error!("Database error: {}", e);
+            err!(INTERNAL_SERVER_ERROR => ISE)
+        }
+    }
+}
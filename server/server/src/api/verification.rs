@@ -0,0 +1,58 @@
+//! Gating actions behind a verified email address.
+//!
+//! Which actions need a verified email address is configured with the
+//! `REQUIRE_VERIFIED_EMAIL` environment variable, a comma separated list of
+//! [`Action`] names such as `create_guild,send_message`. By default no
+//! actions need it.
+
+use crate::prelude::*;
+
+/// An action that can be configured to need a verified email address.
+#[derive(Debug, Clone, Copy)]
+pub enum Action {
+    CreateGuild,
+    JoinGuild,
+    SendMessage,
+}
+impl Action {
+    /// The name of the action in `REQUIRE_VERIFIED_EMAIL`.
+    fn name(self) -> &'static str {
+        match self {
+            Self::CreateGuild => "create_guild",
+            Self::JoinGuild => "join_guild",
+            Self::SendMessage => "send_message",
+        }
+    }
+
+    /// Whether the action has been configured to need a verified email
+    /// address.
+    fn is_gated(self) -> bool {
+        dotenvy::var("REQUIRE_VERIFIED_EMAIL").map_or(false, |actions| {
+            actions
+                .split(',')
+                .any(|action| action.trim() == self.name())
+        })
+    }
+}
+
+/// Checks the user is allowed to take the action, returning the error
+/// response to send if they need to verify their email address first.
+///
+/// # Errors
+///
+/// Returns an `EmailNotVerified` response if the action is gated and the
+/// user has not verified their email address.
+pub async fn check(db: &DbPool, user: Snowflake, action: Action) -> Result<(), HttpResponse> {
+    if !action.is_gated() {
+        return Ok(());
+    }
+
+    match db.user().is_email_verified(user).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(err!(FORBIDDEN => EMAIL_NOT_VERIFIED action.name())),
+        Err(e) => {
+            error!("Database error: {}", e);
+            Err(err!(INTERNAL_SERVER_ERROR => ISE))
+        }
+    }
+}
@@ -0,0 +1,327 @@
+//! A single gateway connection.
+//!
+//! 1. The server sends `HELLO` with the heartbeat interval as soon as the
+//!    socket opens.
+//! 2. Within one interval, the client sends `IDENTIFY` with its access token,
+//!    or `RESUME` with its access token, resume token and last sequence number.
+//! 3. The server replies with `READY`, or replays the missed events followed
+//!    by `RESUMED`, and from then on forwards every event as a `DISPATCH`.
+//!
+//! The client must send a `HEARTBEAT` at least once every interval, which the
+//! server acknowledges with a `HEARTBEAT_ACK`. Connections that go quiet for
+//! longer than [`HEARTBEAT_TIMEOUT`] are closed.
+//!
+//! The access token is checked again every [`TOKEN_CHECK_INTERVAL`], and the
+//! session is ended once it expires, the same as if it had been revoked.
+
+use super::{
+    protocol::{ClientPayload, ServerPayload},
+    Gateway,
+};
+use crate::prelude::*;
+use actix_ws::{CloseCode, CloseReason, Message, MessageStream};
+use std::time::Duration;
+use tokio::{
+    sync::mpsc,
+    time::{sleep_until, timeout_at, Instant},
+};
+
+/// How often the client is asked to send a heartbeat.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(41_250);
+/// How long the server waits for a heartbeat before closing the connection.
+/// Slightly longer than the interval to account for latency.
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_millis(41_250 * 3 / 2);
+/// How often the access token of an identified connection is checked again.
+pub const TOKEN_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The client sent a payload that could not be decoded.
+pub const DECODE_ERROR: u16 = 4001;
+/// The client did not identify before its first heartbeat was due.
+pub const NOT_AUTHENTICATED: u16 = 4003;
+/// The access token sent to `IDENTIFY` or `RESUME` was invalid.
+pub const AUTHENTICATION_FAILED: u16 = 4004;
+/// The client sent `IDENTIFY` or `RESUME` after already being identified.
+pub const ALREADY_AUTHENTICATED: u16 = 4005;
+/// The session was resumed by another connection.
+pub const SESSION_REPLACED: u16 = 4007;
+/// The client did not send a heartbeat in time.
+pub const SESSION_TIMED_OUT: u16 = 4009;
+/// The access token the session was authenticated with was revoked or
+/// expired, i.e. the user logged out.
+pub const SESSION_REVOKED: u16 = 4010;
+
+/// Upgrades the request to a `WebSocket` and starts a gateway connection.
+///
+/// # Errors
+///
+/// Returns an error if the request is not a valid `WebSocket` handshake.
+#[allow(clippy::unused_async)]
+pub async fn connect(
+    req: HttpRequest,
+    body: web::Payload,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (response, ws, stream) = actix_ws::handle(&req, body)?;
+    actix_web::rt::spawn(run(ws, stream, gateway, db));
+    Ok(response)
+}
+
+/// A connection that has been attached to a session in the [`Gateway`].
+struct Attached {
+    user_id: Snowflake,
+    access_token: i64,
+    resume_token: String,
+    events: mpsc::UnboundedReceiver<String>,
+    /// Serialized dispatches to send before any new events. `None` if the
+    /// session was newly identified rather than resumed.
+    replay: Option<Vec<String>>,
+}
+
+async fn run(
+    mut ws: actix_ws::Session,
+    mut stream: MessageStream,
+    gateway: Data<Gateway>,
+    db: Data<DbPool>,
+) {
+    #[allow(clippy::cast_possible_truncation)]
+    let hello = ServerPayload::Hello {
+        heartbeat_interval: HEARTBEAT_INTERVAL.as_millis() as u64,
+    };
+    if send(&mut ws, &hello).await.is_err() {
+        return;
+    }
+
+    let mut attached = match handshake(&mut ws, &mut stream, &gateway, &db).await {
+        Ok(attached) => attached,
+        Err(reason) => {
+            let _ = ws.close(reason).await;
+            return;
+        }
+    };
+
+    let reason = forward(&mut ws, &mut stream, &gateway, &db, &mut attached).await;
+
+    // Drop the receiver first so the gateway can tell whether the session
+    // was taken over by another connection before detaching it.
+    let Attached {
+        resume_token,
+        events,
+        ..
+    } = attached;
+    drop(events);
+    gateway.detach(&resume_token).await;
+    let _ = ws.close(reason).await;
+}
+
+/// Waits for the client to `IDENTIFY` or `RESUME`.
+///
+/// Returns the reason to close the connection with if it fails.
+async fn handshake(
+    ws: &mut actix_ws::Session,
+    stream: &mut MessageStream,
+    gateway: &Gateway,
+    db: &DbPool,
+) -> Result<Attached, Option<CloseReason>> {
+    let deadline = Instant::now() + HEARTBEAT_INTERVAL;
+
+    loop {
+        let text = match timeout_at(deadline, stream.recv()).await {
+            Err(_) => return Err(Some(close(NOT_AUTHENTICATED, "Did not identify in time"))),
+            Ok(None | Some(Err(_) | Ok(Message::Close(_)))) => return Err(None),
+            Ok(Some(Ok(Message::Text(text)))) => text,
+            Ok(Some(Ok(Message::Ping(bytes)))) => {
+                ws.pong(&bytes).await.map_err(|_| None)?;
+                continue;
+            }
+            Ok(Some(Ok(_))) => continue,
+        };
+
+        match serde_json::from_str(&text) {
+            Ok(ClientPayload::Identify { token }) => {
+                let (user_id, access_token) = authenticate(db, &token).await?;
+                let (resume_token, events) = gateway.identify(user_id, access_token).await;
+                debug!("Gateway session identified for {user_id}");
+
+                return Ok(Attached {
+                    user_id,
+                    access_token,
+                    resume_token,
+                    events,
+                    replay: None,
+                });
+            }
+            Ok(ClientPayload::Resume {
+                token,
+                resume_token,
+                seq,
+            }) => {
+                let (user_id, access_token) = authenticate(db, &token).await?;
+                if let Some((events, replay)) = gateway
+                    .resume(&resume_token, user_id, access_token, seq)
+                    .await
+                {
+                    debug!("Gateway session resumed for {user_id}");
+
+                    return Ok(Attached {
+                        user_id,
+                        access_token,
+                        resume_token,
+                        events,
+                        replay: Some(replay),
+                    });
+                }
+
+                // Let the client fall back to identifying again.
+                send(ws, &ServerPayload::InvalidSession)
+                    .await
+                    .map_err(|_| None)?;
+            }
+            Ok(ClientPayload::Heartbeat) => {
+                send(ws, &ServerPayload::HeartbeatAck)
+                    .await
+                    .map_err(|_| None)?;
+            }
+            Err(e) => return Err(Some(close(DECODE_ERROR, &e.to_string()))),
+        }
+    }
+}
+
+/// Forwards events to the client until the connection should close.
+///
+/// Returns the reason to close the connection with.
+async fn forward(
+    ws: &mut actix_ws::Session,
+    stream: &mut MessageStream,
+    gateway: &Gateway,
+    db: &DbPool,
+    attached: &mut Attached,
+) -> Option<CloseReason> {
+    let greeting = match attached.replay.take() {
+        None => {
+            send(
+                ws,
+                &ServerPayload::Ready {
+                    user_id: attached.user_id,
+                    resume_token: &attached.resume_token,
+                },
+            )
+            .await
+        }
+        Some(replay) => {
+            let replayed = replay.len();
+            let mut result = Ok(());
+            for dispatch in replay {
+                result = ws.text(dispatch).await;
+                if result.is_err() {
+                    break;
+                }
+            }
+            match result {
+                Ok(()) => send(ws, &ServerPayload::Resumed { replayed }).await,
+                Err(e) => Err(e),
+            }
+        }
+    };
+    if greeting.is_err() {
+        return None;
+    }
+
+    let mut heartbeat_deadline = Instant::now() + HEARTBEAT_TIMEOUT;
+    let mut token_check = Instant::now() + TOKEN_CHECK_INTERVAL;
+    loop {
+        tokio::select! {
+            msg = stream.recv() => {
+                let text = match msg {
+                    None | Some(Err(_) | Ok(Message::Close(_))) => return None,
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if ws.pong(&bytes).await.is_err() {
+                            return None;
+                        }
+                        continue;
+                    }
+                    Some(Ok(_)) => continue,
+                };
+
+                match serde_json::from_str(&text) {
+                    Ok(ClientPayload::Heartbeat) => {
+                        heartbeat_deadline = Instant::now() + HEARTBEAT_TIMEOUT;
+                        if send(ws, &ServerPayload::HeartbeatAck).await.is_err() {
+                            return None;
+                        }
+                    }
+                    Ok(ClientPayload::Identify { .. } | ClientPayload::Resume { .. }) => {
+                        return Some(close(ALREADY_AUTHENTICATED, "Already identified"));
+                    }
+                    Err(e) => return Some(close(DECODE_ERROR, &e.to_string())),
+                }
+            }
+            dispatch = attached.events.recv() => {
+                let Some(dispatch) = dispatch else {
+                    if gateway.exists(&attached.resume_token).await {
+                        return Some(close(SESSION_REPLACED, "Session was resumed elsewhere"));
+                    }
+                    return Some(close(SESSION_REVOKED, "Access token was revoked"));
+                };
+                if ws.text(dispatch).await.is_err() {
+                    return None;
+                }
+            }
+            () = sleep_until(heartbeat_deadline) => {
+                return Some(close(SESSION_TIMED_OUT, "Did not receive a heartbeat in time"));
+            }
+            () = sleep_until(token_check) => {
+                token_check = Instant::now() + TOKEN_CHECK_INTERVAL;
+                match db.access_token().is_valid(attached.access_token).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        gateway.revoke(&[attached.access_token]).await;
+                        return Some(close(SESSION_REVOKED, "Access token expired"));
+                    }
+                    // Try again at the next check rather than dropping the
+                    // connection
+                    Err(e) => error!("Database error: {}", e),
+                }
+            }
+        }
+    }
+}
+
+/// Checks an access token the same way [`AuthMiddleware`] does.
+/// Returns the user's ID and the access token.
+async fn authenticate(db: &DbPool, token: &str) -> Result<(Snowflake, i64), Option<CloseReason>> {
+    match db.access_token().check(token).await {
+        Ok(data) => Ok(data),
+        Err(access_token::CheckError::InvalidToken | access_token::CheckError::JwtDecoding(_)) => {
+            Err(Some(close(AUTHENTICATION_FAILED, "Invalid access token")))
+        }
+        Err(access_token::CheckError::DatabaseError(e)) => {
+            error!("Database error: {}", e);
+            Err(Some(CloseReason {
+                code: CloseCode::Error,
+                description: None,
+            }))
+        }
+    }
+}
+
+async fn send(
+    ws: &mut actix_ws::Session,
+    payload: &ServerPayload<'_>,
+) -> Result<(), actix_ws::Closed> {
+    match serde_json::to_string(payload) {
+        Ok(text) => ws.text(text).await,
+        Err(e) => {
+            error!("Could not serialize gateway payload: {}", e);
+            Ok(())
+        }
+    }
+}
+
+fn close(code: u16, description: &str) -> CloseReason {
+    CloseReason {
+        code: CloseCode::Other(code),
+        description: Some(description.to_string()),
+    }
+}
@@ -0,0 +1,304 @@
+//! The real-time gateway.
+//!
+//! Clients open a `WebSocket` to `/gateway` and, once identified, are pushed
+//! [`Event`]s for every guild they are a member of. See [`connection`] for
+//! the protocol spoken over the socket.
+//!
+//! Each identified connection owns a session in the [`Gateway`]. The session
+//! outlives the socket for [`RESUME_WINDOW`] so that a client that briefly
+//! drops its connection can `RESUME` and have the events it missed replayed
+//! from the session's buffer instead of refetching everything.
+
+mod connection;
+mod protocol;
+
+pub use connection::connect;
+pub use protocol::Event;
+
+use crate::prelude::*;
+use protocol::Dispatch;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+
+/// How long a session is kept around after its socket closes.
+pub const RESUME_WINDOW: Duration = Duration::from_secs(120);
+/// The maximum amount of events kept around for replaying on resume.
+pub const REPLAY_BUFFER_SIZE: usize = 256;
+
+/// The hub that keeps track of every gateway session.
+#[derive(Default)]
+pub struct Gateway {
+    /// All the live and recently-disconnected sessions, keyed by their
+    /// resume token.
+    sessions: Mutex<HashMap<String, GatewaySession>>,
+}
+
+struct GatewaySession {
+    user_id: Snowflake,
+    /// The access token the session was identified or last resumed with.
+    access_token: i64,
+    /// The sequence number of the last event dispatched to this session.
+    seq: u64,
+    /// The last [`REPLAY_BUFFER_SIZE`] serialized dispatches and their
+    /// sequence numbers.
+    buffer: VecDeque<(u64, String)>,
+    /// The connection the events are forwarded to, or `None` if the socket
+    /// closed and the session is waiting to be resumed.
+    sender: Option<mpsc::UnboundedSender<String>>,
+    /// When the socket closed, used to expire the session.
+    disconnected_at: Option<Instant>,
+}
+
+impl GatewaySession {
+    fn push(&mut self, event: &Event) {
+        self.seq += 1;
+        let dispatch = match serde_json::to_string(&Dispatch::new(self.seq, event)) {
+            Ok(dispatch) => dispatch,
+            Err(e) => {
+                error!("Could not serialize gateway event: {}", e);
+                return;
+            }
+        };
+
+        if self.buffer.len() == REPLAY_BUFFER_SIZE {
+            self.buffer.pop_front();
+        }
+        self.buffer.push_back((self.seq, dispatch.clone()));
+
+        if let Some(sender) = &self.sender {
+            // If the receiver was dropped the socket is closing, and the
+            // session will be detached shortly. The event is buffered anyway.
+            let _ = sender.send(dispatch);
+        }
+    }
+}
+
+impl Gateway {
+    /// Creates a new session for the user and returns its resume token and
+    /// the receiver that events for the session are pushed to.
+    async fn identify(
+        &self,
+        user_id: Snowflake,
+        access_token: i64,
+    ) -> (String, mpsc::UnboundedReceiver<String>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let resume_token = new_resume_token();
+
+        let mut sessions = self.sessions.lock().await;
+        prune(&mut sessions);
+        sessions.insert(
+            resume_token.clone(),
+            GatewaySession {
+                user_id,
+                access_token,
+                seq: 0,
+                buffer: VecDeque::new(),
+                sender: Some(sender),
+                disconnected_at: None,
+            },
+        );
+
+        (resume_token, receiver)
+    }
+
+    /// Reattaches a connection to an existing session.
+    ///
+    /// Returns the receiver for new events and every buffered event after
+    /// `seq`, or `None` if the session does not exist, belongs to another
+    /// user, or has already dropped events the client has not seen.
+    async fn resume(
+        &self,
+        resume_token: &str,
+        user_id: Snowflake,
+        access_token: i64,
+        seq: u64,
+    ) -> Option<(mpsc::UnboundedReceiver<String>, Vec<String>)> {
+        let mut sessions = self.sessions.lock().await;
+        prune(&mut sessions);
+
+        let session = sessions.get_mut(resume_token)?;
+        if session.user_id != user_id || seq > session.seq {
+            return None;
+        }
+        // The event right after `seq` must still be in the buffer, otherwise
+        // the client would silently miss events.
+        let oldest = session.buffer.front().map_or(session.seq + 1, |(s, _)| *s);
+        if seq + 1 < oldest {
+            sessions.remove(resume_token);
+            return None;
+        }
+
+        let replay = session
+            .buffer
+            .iter()
+            .filter(|(s, _)| *s > seq)
+            .map(|(_, dispatch)| dispatch.clone())
+            .collect();
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        session.sender = Some(sender);
+        session.access_token = access_token;
+        session.disconnected_at = None;
+        Some((receiver, replay))
+    }
+
+    /// Marks a session as disconnected so it can be resumed later.
+    ///
+    /// The connection's receiver must be dropped beforehand. If the session
+    /// has since been resumed by another connection, it is left untouched.
+    async fn detach(&self, resume_token: &str) {
+        if let Some(session) = self.sessions.lock().await.get_mut(resume_token) {
+            if session
+                .sender
+                .as_ref()
+                .map_or(true, mpsc::UnboundedSender::is_closed)
+            {
+                session.sender = None;
+                session.disconnected_at = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Whether a session still exists. Sessions are only removed while
+    /// attached if their access token was revoked.
+    async fn exists(&self, resume_token: &str) -> bool {
+        self.sessions.lock().await.contains_key(resume_token)
+    }
+
+    /// Ends every session that was authenticated with one of the access
+    /// tokens, closing their connections.
+    pub async fn revoke(&self, access_tokens: &[i64]) {
+        self.sessions
+            .lock()
+            .await
+            .retain(|_, session| !access_tokens.contains(&session.access_token));
+    }
+
+    /// Pushes an event to every session belonging to one of the users.
+    pub async fn dispatch(&self, users: &[Snowflake], event: &Event) {
+        let mut sessions = self.sessions.lock().await;
+        prune(&mut sessions);
+
+        for session in sessions.values_mut() {
+            if users.contains(&session.user_id) {
+                session.push(event);
+            }
+        }
+    }
+
+    /// Pushes an event to every member of a guild.
+    pub async fn dispatch_to_guild(&self, db: &DbPool, guild_id: Snowflake, event: &Event) {
+        match db.guild().get_member_ids(guild_id).await {
+            Ok(members) => self.dispatch(&members, event).await,
+            Err(e) => error!("Could not get members to dispatch event to: {}", e),
+        }
+    }
+
+    /// Pushes an event to every user that can see a channel.
+    pub async fn dispatch_to_channel(&self, db: &DbPool, channel_id: Snowflake, event: &Event) {
+        match db.channel().get_recipient_ids(channel_id).await {
+            Ok(recipients) => self.dispatch(&recipients, event).await,
+            Err(e) => error!("Could not get recipients to dispatch event to: {}", e),
+        }
+    }
+
+    /// Pushes a `ChannelReorder` with the current order of a guild's
+    /// channels to every member, leaving out the channels each can't view.
+    pub async fn dispatch_channel_order(&self, db: &DbPool, guild_id: Snowflake) {
+        let orders = match db.channel().get_visible_orders(guild_id).await {
+            Ok(orders) => orders,
+            Err(e) => {
+                error!("Could not get channels to dispatch event to: {}", e);
+                return;
+            }
+        };
+
+        // Members that can view the same channels are sent the same event
+        let mut recipients: HashMap<Vec<Snowflake>, Vec<Snowflake>> = HashMap::new();
+        for (user_id, channel_ids) in orders {
+            recipients.entry(channel_ids).or_default().push(user_id);
+        }
+        for (channel_ids, users) in recipients {
+            let event = Event::ChannelReorder {
+                guild_id,
+                channel_ids,
+            };
+            self.dispatch(&users, &event).await;
+        }
+    }
+
+    /// Pushes an event about a change to a channel's permissions to every
+    /// user that can see the channel afterwards. `before` are the users that
+    /// could see it before the change: those who can now see it are sent a
+    /// `ChannelCreate`, and those who no longer can a `ChannelDelete`.
+    pub async fn dispatch_access_change(
+        &self,
+        db: &DbPool,
+        guild_id: Snowflake,
+        channel_id: Snowflake,
+        before: &[Snowflake],
+        event: &Event,
+    ) {
+        let after = match db.channel().get_recipient_ids(channel_id).await {
+            Ok(recipients) => recipients,
+            Err(e) => {
+                error!("Could not get recipients to dispatch event to: {}", e);
+                return;
+            }
+        };
+
+        let before_set: HashSet<_> = before.iter().collect();
+        let after_set: HashSet<_> = after.iter().collect();
+        let gained: Vec<Snowflake> = after
+            .iter()
+            .filter(|user| !before_set.contains(user))
+            .copied()
+            .collect();
+        let lost: Vec<Snowflake> = before
+            .iter()
+            .filter(|user| !after_set.contains(user))
+            .copied()
+            .collect();
+
+        if !gained.is_empty() {
+            match db.channel().get(guild_id, channel_id).await {
+                Ok(Some(channel)) => {
+                    let event = Event::ChannelCreate { guild_id, channel };
+                    self.dispatch(&gained, &event).await;
+                }
+                Ok(None) => warn!("Channel {} not found after changing it", channel_id),
+                Err(e) => error!("Database error: {}", e),
+            }
+        }
+        self.dispatch(&after, event).await;
+        if !lost.is_empty() {
+            let event = Event::ChannelDelete {
+                guild_id,
+                channel_id,
+            };
+            self.dispatch(&lost, &event).await;
+        }
+    }
+}
+
+/// Removes every session whose resume window has passed.
+fn prune(sessions: &mut HashMap<String, GatewaySession>) {
+    sessions.retain(|_, session| {
+        session
+            .disconnected_at
+            .map_or(true, |at| at.elapsed() < RESUME_WINDOW)
+    });
+}
+
+/// Generates a random, unguessable resume token.
+fn new_resume_token() -> String {
+    use base64::Engine;
+    use rand::RngCore;
+
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
@@ -0,0 +1,199 @@
+//! The JSON payloads sent over a gateway connection.
+//!
+//! Every payload is an object with an `op` field naming the operation and
+//! an optional `d` field with its data. Dispatches additionally carry the
+//! sequence number `s` and the event name `t`.
+
+use crate::prelude::*;
+
+/// An event pushed to clients through the gateway.
+#[derive(Debug, Serialize)]
+#[serde(tag = "t", content = "d", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Event {
+    /// A message was sent in a channel.
+    MessageCreate(message::Message),
+    /// A message was edited.
+    MessageUpdate(message::Message),
+    /// A message was deleted.
+    MessageDelete {
+        channel_id: Snowflake,
+        message_id: Snowflake,
+    },
+    /// Several messages in a channel were deleted at once.
+    MessageDeleteBulk {
+        channel_id: Snowflake,
+        message_ids: Vec<Snowflake>,
+    },
+    /// A user reacted to a message.
+    MessageReactionAdd {
+        channel_id: Snowflake,
+        message_id: Snowflake,
+        user_id: Snowflake,
+        emoji: reaction::Emoji,
+    },
+    /// A user's reaction was removed from a message.
+    MessageReactionRemove {
+        channel_id: Snowflake,
+        message_id: Snowflake,
+        user_id: Snowflake,
+        emoji: reaction::Emoji,
+    },
+    /// A channel was created in a guild.
+    ChannelCreate {
+        guild_id: Snowflake,
+        channel: channel::Channel,
+    },
+    /// A channel was renamed, its topic changed, or it was moved into or out
+    /// of a category.
+    ChannelUpdate {
+        guild_id: Snowflake,
+        channel: channel::Channel,
+    },
+    /// The channels in a guild were reordered.
+    ChannelReorder {
+        guild_id: Snowflake,
+        /// Every channel in the guild the user can view, from top to bottom.
+        channel_ids: Vec<Snowflake>,
+    },
+    /// The user collapsed or expanded a category in their sidebar.
+    CategoryCollapse {
+        guild_id: Snowflake,
+        channel_id: Snowflake,
+        collapsed: bool,
+    },
+    /// A channel was deleted from a guild.
+    ChannelDelete {
+        guild_id: Snowflake,
+        channel_id: Snowflake,
+    },
+    /// A permission overwrite on a channel was created or changed.
+    ChannelOverwriteUpdate {
+        guild_id: Snowflake,
+        overwrite: channel_overwrite::Overwrite,
+    },
+    /// A permission overwrite was removed from a channel.
+    ChannelOverwriteDelete {
+        guild_id: Snowflake,
+        channel_id: Snowflake,
+        target_id: Snowflake,
+    },
+    /// The user joined (or created) a guild.
+    GuildCreate(guild::Guild),
+    /// A guild was renamed or given a new owner.
+    GuildUpdate(guild::GuildSummary),
+    /// The guild was deleted, or the user left or was kicked from it.
+    GuildDelete { guild_id: Snowflake },
+    /// The user moved guilds in their sidebar.
+    GuildOrderUpdate {
+        /// Every guild the user is in, from top to bottom.
+        guild_ids: Vec<Snowflake>,
+    },
+    /// The user created, changed or deleted a sidebar folder.
+    GuildFoldersUpdate {
+        /// Every folder the user has.
+        folders: Vec<guild_folder::Folder>,
+    },
+    /// A user joined a guild.
+    GuildMemberAdd {
+        guild_id: Snowflake,
+        user: user::User,
+    },
+    /// A member's nickname was changed.
+    GuildMemberUpdate(guild::Member),
+    /// A user left or was kicked from a guild.
+    GuildMemberRemove {
+        guild_id: Snowflake,
+        user_id: Snowflake,
+    },
+    /// A role was created in a guild.
+    RoleCreate(role::Role),
+    /// A role was edited or moved.
+    RoleUpdate(role::Role),
+    /// A role was deleted from a guild.
+    RoleDelete {
+        guild_id: Snowflake,
+        role_id: Snowflake,
+    },
+    /// The user was added to a DM, or opened a new one.
+    DmCreate(dm::DmChannel),
+    /// A user was added to a group DM.
+    DmMemberAdd {
+        channel_id: Snowflake,
+        user: user::User,
+    },
+    /// The user's relationship with another user was created or changed.
+    RelationshipAdd(relationship::Relationship),
+    /// The user no longer has a relationship with another user.
+    RelationshipRemove { user_id: Snowflake },
+    /// A user left or was removed from a group DM.
+    DmMemberRemove {
+        channel_id: Snowflake,
+        user_id: Snowflake,
+        /// The owner of the group DM afterwards, as it changes when the
+        /// owner leaves.
+        owner_id: Option<Snowflake>,
+    },
+}
+
+/// A payload sent from the server to the client.
+#[derive(Debug, Serialize)]
+#[serde(tag = "op", content = "d", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ServerPayload<'a> {
+    /// Sent as soon as the socket opens.
+    Hello {
+        /// How often, in milliseconds, the client must send a `HEARTBEAT`.
+        heartbeat_interval: u64,
+    },
+    /// The client identified successfully.
+    Ready {
+        user_id: Snowflake,
+        /// Pass this back in a `RESUME` to continue this session.
+        resume_token: &'a str,
+    },
+    /// The client resumed successfully and missed events were replayed.
+    Resumed { replayed: usize },
+    /// Acknowledges a `HEARTBEAT`.
+    HeartbeatAck,
+    /// The session could not be identified or resumed. The client should
+    /// fetch its state again and send a fresh `IDENTIFY`.
+    InvalidSession,
+}
+
+/// A payload sent from the client to the server.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", content = "d", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ClientPayload {
+    /// Starts a new session.
+    Identify {
+        /// The same JWT sent in the `Authorization` header to the REST API.
+        token: String,
+    },
+    /// Continues a session after reconnecting.
+    Resume {
+        token: String,
+        resume_token: String,
+        /// The sequence number of the last event the client received.
+        seq: u64,
+    },
+    /// Keeps the connection alive.
+    Heartbeat,
+}
+
+/// An [`Event`] pushed to a specific session.
+#[derive(Debug, Serialize)]
+pub struct Dispatch<'a> {
+    op: &'static str,
+    s: u64,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+impl<'a> Dispatch<'a> {
+    pub fn new(seq: u64, event: &'a Event) -> Self {
+        Self {
+            op: "DISPATCH",
+            s: seq,
+            event,
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Working out where requests come from.
+
+use crate::prelude::*;
+
+/// Returns the IP address a request came from.
+///
+/// If `TRUST_PROXY` is `true`, the address in the `Forwarded` or
+/// `X-Forwarded-For` header set by a reverse proxy is used instead of the
+/// address of the connection, which would be the proxy's. Don't set it if
+/// the server can be reached without the proxy, as the headers can be
+/// forged.
+pub fn from_request(req: &HttpRequest) -> String {
+    let trust_proxy = dotenvy::var("TRUST_PROXY").map_or(false, |trust| trust == "true");
+    let info = req.connection_info();
+    let ip = if trust_proxy {
+        info.realip_remote_addr()
+    } else {
+        info.peer_addr()
+    };
+    ip.unwrap_or("unknown").to_string()
+}
@@ -0,0 +1,226 @@
+//! Outgoing email.
+//!
+//! Mail is sent through a [`Mailer`], chosen by the `MAIL_TRANSPORT`
+//! environment variable:
+//! - `smtp` sends it through the SMTP server at `SMTP_HOST`.
+//! - `file` writes each email to its own file in `MAIL_DIR`.
+//! - `stdout` prints each email. This is the default, so that nothing needs
+//!   to be set up for development.
+
+use crate::prelude::*;
+use lettre::{
+    message::Mailbox,
+    transport::smtp::{authentication::Credentials, SmtpTransport},
+    Message, Transport,
+};
+use std::{io::Write, path::PathBuf, sync::Arc};
+
+/// An email to send to a single recipient.
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    /// The plain text body of the email.
+    pub body: String,
+}
+
+/// Something that can send emails.
+pub trait Mailer: Send + Sync {
+    /// Sends an email. This blocks until the email is sent, so it should be
+    /// called through [`send`] from async code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the email could not be built or sent.
+    fn send(&self, mail: &Mail) -> Result<(), SendError>;
+}
+
+#[derive(Debug, Error)]
+pub enum SendError {
+    #[error("The email address is invalid: {0}")]
+    InvalidAddress(#[from] lettre::address::AddressError),
+    #[error("The email could not be built: {0}")]
+    Build(#[from] lettre::error::Error),
+    #[error("The email could not be sent over SMTP: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+    #[error("The email could not be written: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("The email could not be sent because the thread pool is gone")]
+    Canceled,
+}
+
+/// Creates the [`Mailer`] chosen by the `MAIL_TRANSPORT` environment
+/// variable.
+///
+/// # Panics
+///
+/// Panics if `MAIL_TRANSPORT` is not a known transport, or if the transport
+/// is missing its configuration.
+pub fn from_env() -> Arc<dyn Mailer> {
+    let transport = dotenvy::var("MAIL_TRANSPORT").unwrap_or("stdout".to_string());
+    match transport.as_str() {
+        "smtp" => Arc::new(SmtpMailer::from_env()),
+        "file" => Arc::new(FileMailer::from_env()),
+        "stdout" => Arc::new(StdoutMailer::from_env()),
+        _ => panic!("MAIL_TRANSPORT must be one of `smtp`, `file` or `stdout`"),
+    }
+}
+
+/// Sends an email on the blocking thread pool.
+///
+/// # Errors
+///
+/// Returns an error if the email could not be built or sent.
+pub async fn send(mailer: Data<dyn Mailer>, mail: Mail) -> Result<(), SendError> {
+    web::block(move || mailer.send(&mail))
+        .await
+        .map_err(|_| SendError::Canceled)?
+}
+
+/// Returns the link to a page of the web client, read from the `WEB_URL`
+/// environment variable.
+pub fn web_url(path: &str) -> String {
+    let base = dotenvy::var("WEB_URL").unwrap_or("http://localhost:5173".to_string());
+    format!("{}{path}", base.trim_end_matches('/'))
+}
+
+/// Returns who emails are sent from, read from the `MAIL_FROM` environment
+/// variable.
+fn from_address() -> Mailbox {
+    dotenvy::var("MAIL_FROM")
+        .unwrap_or("Campfire <noreply@localhost>".to_string())
+        .parse()
+        .expect("MAIL_FROM is not a valid mailbox")
+}
+
+fn build(from: &Mailbox, mail: &Mail) -> Result<Message, SendError> {
+    Ok(Message::builder()
+        .from(from.clone())
+        .to(mail.to.parse()?)
+        .subject(&mail.subject)
+        .body(mail.body.clone())?)
+}
+
+/// Sends emails through an SMTP server.
+pub struct SmtpMailer {
+    transport: SmtpTransport,
+    from: Mailbox,
+}
+impl SmtpMailer {
+    /// Connects to the server at `SMTP_HOST`, optionally on `SMTP_PORT`.
+    ///
+    /// `SMTP_TLS` is one of `tls` (the default), `starttls` or `none`.
+    /// `SMTP_USERNAME` and `SMTP_PASSWORD` are used to log in if set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `SMTP_HOST` is not set or the configuration is invalid.
+    pub fn from_env() -> Self {
+        let host = dotenvy::var("SMTP_HOST").expect("SMTP_HOST not set");
+        let tls = dotenvy::var("SMTP_TLS").unwrap_or("tls".to_string());
+        let mut builder = match tls.as_str() {
+            "tls" => SmtpTransport::relay(&host).expect("Invalid SMTP_HOST"),
+            "starttls" => SmtpTransport::starttls_relay(&host).expect("Invalid SMTP_HOST"),
+            "none" => SmtpTransport::builder_dangerous(host),
+            _ => panic!("SMTP_TLS must be one of `tls`, `starttls` or `none`"),
+        };
+
+        if let Ok(port) = dotenvy::var("SMTP_PORT") {
+            builder = builder.port(port.parse().expect("SMTP_PORT is not a port"));
+        }
+        if let Ok(username) = dotenvy::var("SMTP_USERNAME") {
+            let password = dotenvy::var("SMTP_PASSWORD").expect("SMTP_PASSWORD not set");
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Self {
+            transport: builder.build(),
+            from: from_address(),
+        }
+    }
+}
+impl Mailer for SmtpMailer {
+    fn send(&self, mail: &Mail) -> Result<(), SendError> {
+        self.transport.send(&build(&self.from, mail)?)?;
+        Ok(())
+    }
+}
+
+/// Writes each email to its own `.eml` file in a directory.
+pub struct FileMailer {
+    dir: PathBuf,
+    from: Mailbox,
+}
+impl FileMailer {
+    /// Writes emails to `MAIL_DIR`, creating it if it doesn't exist.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `MAIL_DIR` is not set or cannot be created.
+    pub fn from_env() -> Self {
+        let dir = PathBuf::from(dotenvy::var("MAIL_DIR").expect("MAIL_DIR not set"));
+        std::fs::create_dir_all(&dir).expect("Unable to create MAIL_DIR");
+
+        Self {
+            dir,
+            from: from_address(),
+        }
+    }
+}
+impl Mailer for FileMailer {
+    fn send(&self, mail: &Mail) -> Result<(), SendError> {
+        let message = build(&self.from, mail)?;
+        let name = format!("{}-{}.eml", time::now(), rand::random::<u32>());
+        std::fs::write(self.dir.join(name), message.formatted())?;
+        Ok(())
+    }
+}
+
+/// Prints each email to stdout.
+pub struct StdoutMailer {
+    from: Mailbox,
+}
+impl StdoutMailer {
+    pub fn from_env() -> Self {
+        Self {
+            from: from_address(),
+        }
+    }
+}
+impl Mailer for StdoutMailer {
+    fn send(&self, mail: &Mail) -> Result<(), SendError> {
+        let message = build(&self.from, mail)?;
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(&message.formatted())?;
+        stdout.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+/// Keeps every email instead of sending it, so tests can check what was
+/// sent.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryMailer {
+    sent: std::sync::Mutex<Vec<Mail>>,
+}
+#[cfg(test)]
+impl MemoryMailer {
+    /// Every email sent so far, oldest first.
+    pub fn sent(&self) -> Vec<Mail> {
+        self.sent
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+}
+#[cfg(test)]
+impl Mailer for MemoryMailer {
+    fn send(&self, mail: &Mail) -> Result<(), SendError> {
+        self.sent
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(mail.clone());
+        Ok(())
+    }
+}
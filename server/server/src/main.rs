@@ -2,12 +2,16 @@
 #![allow(clippy::must_use_candidate)]
 
 mod api;
+pub mod gateway;
+pub mod ip;
 pub mod logger;
 mod macros;
+pub mod mail;
 pub mod prelude;
 
 use crate::{logger::Logger, prelude::*};
 use actix_web::{middleware::NormalizePath, App, HttpServer};
+use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing_subscriber::layer::SubscriberExt;
 
@@ -22,9 +26,17 @@ pub struct ChannelSnowflakeGen(pub snowflake::SnowflakeGenerator);
 #[derive(Deref, DerefMut)]
 pub struct MessageSnowflakeGen(pub snowflake::SnowflakeGenerator);
 #[derive(Deref, DerefMut)]
+pub struct MessageRevisionSnowflakeGen(pub snowflake::SnowflakeGenerator);
+#[derive(Deref, DerefMut)]
+pub struct AccessTokenSnowflakeGen(pub snowflake::SnowflakeGenerator);
+#[derive(Deref, DerefMut)]
 pub struct GuildMemberSnowflakeGen(pub snowflake::SnowflakeGenerator);
 #[derive(Deref, DerefMut)]
 pub struct GuildMemberRoleSnowflakeGen(pub snowflake::SnowflakeGenerator);
+#[derive(Deref, DerefMut)]
+pub struct GuildFolderSnowflakeGen(pub snowflake::SnowflakeGenerator);
+#[derive(Deref, DerefMut)]
+pub struct LoginAttemptSnowflakeGen(pub snowflake::SnowflakeGenerator);
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -56,12 +68,30 @@ async fn main() -> std::io::Result<()> {
     let message_snowflake_gen = Data::new(Mutex::new(MessageSnowflakeGen(
         snowflake::SnowflakeGenerator::new(machine_id),
     )));
+    let message_revision_snowflake_gen = Data::new(Mutex::new(MessageRevisionSnowflakeGen(
+        snowflake::SnowflakeGenerator::new(machine_id),
+    )));
+    let access_token_snowflake_gen = Data::new(Mutex::new(AccessTokenSnowflakeGen(
+        snowflake::SnowflakeGenerator::new(machine_id),
+    )));
     let guild_member_snowflake_gen = Data::new(Mutex::new(GuildMemberSnowflakeGen(
         snowflake::SnowflakeGenerator::new(machine_id),
     )));
     let guild_member_role_snowflake_gen = Data::new(Mutex::new(GuildMemberRoleSnowflakeGen(
         snowflake::SnowflakeGenerator::new(machine_id),
     )));
+    let guild_folder_snowflake_gen = Data::new(Mutex::new(GuildFolderSnowflakeGen(
+        snowflake::SnowflakeGenerator::new(machine_id),
+    )));
+    let login_attempt_snowflake_gen = Data::new(Mutex::new(LoginAttemptSnowflakeGen(
+        snowflake::SnowflakeGenerator::new(machine_id),
+    )));
+
+    let gateway = Data::new(Gateway::default());
+    let mailer = Data::from(mail::from_env());
+    let clock = Data::from(Arc::new(time::SystemClock) as Arc<dyn time::Clock>);
+    let rate_limit_store =
+        Data::from(Arc::new(rate_limit::MemoryStore::default()) as Arc<dyn rate_limit::Store>);
 
     let ip = "0.0.0.0";
     let port = 8080;
@@ -80,8 +110,16 @@ async fn main() -> std::io::Result<()> {
             .app_data(role_snowflake_gen.clone())
             .app_data(channel_snowflake_gen.clone())
             .app_data(message_snowflake_gen.clone())
+            .app_data(message_revision_snowflake_gen.clone())
+            .app_data(access_token_snowflake_gen.clone())
             .app_data(guild_member_snowflake_gen.clone())
             .app_data(guild_member_role_snowflake_gen.clone())
+            .app_data(guild_folder_snowflake_gen.clone())
+            .app_data(login_attempt_snowflake_gen.clone())
+            .app_data(gateway.clone())
+            .app_data(mailer.clone())
+            .app_data(clock.clone())
+            .app_data(rate_limit_store.clone())
             .configure(api::init_routes)
             .wrap(cors)
             .wrap(Logger::new(
@@ -1,8 +1,18 @@
 pub use crate::{
     actix_err,
-    api::authentication::{AuthMiddleware, Session},
-    err, map, ok, route, ChannelSnowflakeGen, GuildMemberRoleSnowflakeGen, GuildMemberSnowflakeGen,
-    GuildSnowflakeGen, MessageSnowflakeGen, RoleSnowflakeGen, UserSnowflakeGen,
+    api::{
+        authentication::{AuthMiddleware, Session},
+        rate_limit::{self, RateLimit},
+        verification,
+    },
+    err,
+    gateway::{self, Gateway},
+    ip,
+    mail::{self, Mailer},
+    map, ok, route, AccessTokenSnowflakeGen, ChannelSnowflakeGen, GuildFolderSnowflakeGen,
+    GuildMemberRoleSnowflakeGen, GuildMemberSnowflakeGen, GuildSnowflakeGen,
+    LoginAttemptSnowflakeGen, MessageRevisionSnowflakeGen, MessageSnowflakeGen, RoleSnowflakeGen,
+    UserSnowflakeGen,
 };
 pub use actix_web::{
     get,
@@ -26,6 +36,11 @@ pub const METHOD_NOT_ALLOWED: &str = "MethodNotAllowed";
 pub const NO_AUTH_TOKEN: &str = "NoAuthToken";
 /// Could not decode the JWT
 pub const BAD_AUTH_TOKEN: &str = "BadAuthToken";
+/// The user must verify their email address before doing this
+pub const EMAIL_NOT_VERIFIED: &str = "EmailNotVerified";
+/// Too many requests were made to the endpoint. Try again after `retry_after`
+/// seconds
+pub const RATE_LIMITED: &str = "RateLimited";
 /// The toklen does not exist. This could also mean the session has expired.
 pub const INVALID_AUTH_TOKEN: &str = "InvalidAuthToken";
 pub const JSON_PAYLOAD_TOO_LARGE: &str = "JSON:PayloadTooLarge";
@@ -34,3 +49,4 @@ pub const JSON_DESERIALIZE_ERROR: &str = "JSON:UnknownDeserializeError";
 pub const JSON_SERIALIZE_ERROR: &str = "JSON:UnknownSerializeError";
 pub const JSON_READING_PAYLOAD_ERROR: &str = "JSON:UnknownErrorReadingPayload";
 pub const MISC_JSON_ERROR: &str = "JSON:UnknownError";
+pub const QUERY_DESERIALIZE_ERROR: &str = "Query:DeserializeError";
@@ -18,6 +18,19 @@ export type I18nKey =
     // {user} - The user's username
     // {discrim} - The user's discriminator
     | "LOGGED_IN_AS"
+    // {seconds} - How many seconds until logging in can be tried again
+    | "LOGIN_TOO_MANY_ATTEMPTS"
+    | "MESSAGE_DELETED"
+    | "EMAIL_NOT_VERIFIED"
+    | "MFA_CODE_PROMPT"
+    | "MFA_CODE_INVALID"
+    | "MFA_TICKET_EXPIRED"
+    | "PASSWORD_TOO_SHORT"
+    | "PASSWORD_TOO_LONG"
+    | "PASSWORD_TOO_WEAK"
+    | "PASSWORD_TOO_COMMON"
+    | "PASSWORD_LIKE_USERNAME"
+    | "PASSWORD_LIKE_EMAIL"
     | I18nKey_Date
     | "SERVER_ERROR_FETCHING_GUILDS"
     | "FETCH_ERROR_FETCHING_GUILDS"
@@ -13,6 +13,18 @@ export const en: I18n = {
     "LOGIN": "Login",
     "LOGOUT": "Logout",
     "LOGGED_IN_AS": "Logged in as {user}#{discrim}",
+    "LOGIN_TOO_MANY_ATTEMPTS": "Too many failed attempts to log in. Try again in {seconds} seconds.",
+    "MESSAGE_DELETED": "This message was deleted.",
+    "EMAIL_NOT_VERIFIED": "Verify your email address to send messages. Check your inbox for the link.",
+    "MFA_CODE_PROMPT": "Enter the code from your authenticator app, or a recovery code.",
+    "MFA_CODE_INVALID": "That code is incorrect or has already been used.",
+    "MFA_TICKET_EXPIRED": "Your login has expired, please log in again.",
+    "PASSWORD_TOO_SHORT": "Your password must be at least 8 characters long.",
+    "PASSWORD_TOO_LONG": "Your password must be at most 64 characters long.",
+    "PASSWORD_TOO_WEAK": "Your password is too easy to guess. Try a longer password or a few random words.",
+    "PASSWORD_TOO_COMMON": "That password is too common. Please choose another.",
+    "PASSWORD_LIKE_USERNAME": "Your password is too similar to your username.",
+    "PASSWORD_LIKE_EMAIL": "Your password is too similar to your email address.",
 
     "DATE_FORMAT_TODAY": "Today at {24HH}:{MM}",
     "DATE_FORMAT_YESTERDAY": "Yetserday at {24HH}:{MM}",
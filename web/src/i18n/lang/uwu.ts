@@ -13,6 +13,18 @@ export const uwu: I18n = {
     "LOGIN": "who r u???",
     "LOGOUT": "bye bye!",
     "LOGGED_IN_AS": "you is {user}#{discrim}",
+    "LOGIN_TOO_MANY_ATTEMPTS": "too many twies! wait {seconds} seconds pwease >_<",
+    "MESSAGE_DELETED": "this message went bye bye",
+    "EMAIL_NOT_VERIFIED": "pwease vewify ur email fiwst! check ur inbox >w<",
+    "MFA_CODE_PROMPT": "pwease type the code fwom ur authenticatow app (ow a wecovewy code)",
+    "MFA_CODE_INVALID": "that code is wwong ow was awweady used >.<",
+    "MFA_TICKET_EXPIRED": "u took too wong! pwease wog in again",
+    "PASSWORD_TOO_SHORT": "ur passwowd needs 8 chawactews at weast",
+    "PASSWORD_TOO_LONG": "ur passwowd is too wong! 64 chawactews max",
+    "PASSWORD_TOO_WEAK": "ur passwowd is too easy to guess >.< twy some wandom wowds",
+    "PASSWORD_TOO_COMMON": "evewyone uses that passwowd! pick anothew one",
+    "PASSWORD_LIKE_USERNAME": "ur passwowd wooks wike ur usewname",
+    "PASSWORD_LIKE_EMAIL": "ur passwowd wooks wike ur emaiw",
 
     "DATE_FORMAT_TODAY": "today @ {24HH}:{MM}",
     "DATE_FORMAT_YESTERDAY": "yesterday @ {24HH}:{MM}",
@@ -1,2 +1,3 @@
 export * from './login';
+export * from './login_mfa';
 export * from './register';
@@ -19,13 +19,20 @@ export type LoginBody = {
 	password: string;
 };
 
-export type LoginResponse = {
-	access_token: string;
-	user: User;
-};
+export type LoginResponse =
+	| {
+			access_token: string;
+			user: User;
+	  }
+	| {
+			/** The user has 2FA enabled. Exchange this for an access token
+			 *  with `loginMfa` along with a code. */
+			mfa_ticket: string;
+	  };
 
 export type Login = {
 	onInvalidCredentials(handler: Handler): Login;
+	onTooManyAttempts(handler: Handler<[{ retry_after: number }]>): Login;
 } & FetchErrors<Login> &
 	ServerErrors<Login> &
 	JsonErrors<Login> &
@@ -44,6 +51,12 @@ export function login(body: LoginBody) {
 			req.register('InvalidCredentials', handler);
 			return this;
 		},
+		onTooManyAttempts(handler: Handler<[{ retry_after: number }]>) {
+			req.register('TooManyAttempts', (msg, data) =>
+				handler(msg, data as { retry_after: number })
+			);
+			return this;
+		},
 		...anyErrors(req),
 		...fetchErrors(req),
 		...serverErrors(req),
@@ -0,0 +1,67 @@
+import {
+	SdkRequest,
+	fetchRequest,
+	type FetchErrors,
+	fetchErrors,
+	type ServerErrors,
+	serverErrors,
+	type JsonErrors,
+	jsonErrors,
+	type AuthErrors,
+	authErrors,
+	type AnyErrors,
+	anyErrors
+} from '$/sdk/req';
+import type { Handler, User } from '$/sdk/types';
+
+export type LoginMfaBody = {
+	/** The `mfa_ticket` returned by `login`. */
+	ticket: string;
+	/** A code from the authenticator app, or a recovery code. */
+	code: string;
+};
+
+export type LoginMfaResponse = {
+	access_token: string;
+	user: User;
+};
+
+export type LoginMfa = {
+	onInvalidMfaTicket(handler: Handler): LoginMfa;
+	onInvalidMfaCode(handler: Handler): LoginMfa;
+	onTooManyAttempts(handler: Handler<[{ retry_after: number }]>): LoginMfa;
+} & FetchErrors<LoginMfa> &
+	ServerErrors<LoginMfa> &
+	JsonErrors<LoginMfa> &
+	AuthErrors<LoginMfa> &
+	AnyErrors<SdkRequest<LoginMfaResponse, LoginMfaResponse>>;
+
+export function loginMfa(body: LoginMfaBody) {
+	const req = new SdkRequest<LoginMfaResponse, LoginMfaResponse>(() =>
+		fetchRequest<LoginMfaResponse>('/account/login/mfa', 'POST', {
+			body
+		})
+	);
+
+	return {
+		onInvalidMfaTicket(handler: Handler) {
+			req.register('InvalidMfaTicket', handler);
+			return this;
+		},
+		onInvalidMfaCode(handler: Handler) {
+			req.register('InvalidMfaCode', handler);
+			return this;
+		},
+		onTooManyAttempts(handler: Handler<[{ retry_after: number }]>) {
+			req.register('TooManyAttempts', (msg, data) =>
+				handler(msg, data as { retry_after: number })
+			);
+			return this;
+		},
+		...anyErrors(req),
+		...fetchErrors(req),
+		...serverErrors(req),
+		...jsonErrors(req),
+		...authErrors(req)
+	} as unknown as LoginMfa;
+}
@@ -20,6 +20,19 @@ export type RegisterBody = {
 	password: string;
 };
 
+/** Why a password was rejected, sent with every password error. */
+export type InvalidPassword = {
+	rule:
+		| 'too_short'
+		| 'too_long'
+		| 'too_weak'
+		| 'too_common'
+		| 'too_similar_to_username'
+		| 'too_similar_to_email';
+	/** How hard the password is to guess, from 0 to 4. */
+	score: number;
+};
+
 export type RegisterResponse = {
 	access_token: string;
 	user: User;
@@ -30,12 +43,12 @@ export type Register = {
 	onUsernameTaken(handler: Handler): Register;
 	onInvalidEmail(handler: Handler): Register;
 	onEmailTaken(handler: Handler): Register;
-	onPasswordTooWeak(handler: Handler): Register;
-	onPasswordTooLong(handler: Handler): Register;
-	onPasswordTooShort(handler: Handler): Register;
-	onPasswordTooCommon(handler: Handler): Register;
-	onPasswordLikeUsername(handler: Handler): Register;
-	onPasswordLikeEmail(handler: Handler): Register;
+	onPasswordTooWeak(handler: Handler<[password: InvalidPassword]>): Register;
+	onPasswordTooLong(handler: Handler<[password: InvalidPassword]>): Register;
+	onPasswordTooShort(handler: Handler<[password: InvalidPassword]>): Register;
+	onPasswordTooCommon(handler: Handler<[password: InvalidPassword]>): Register;
+	onPasswordLikeUsername(handler: Handler<[password: InvalidPassword]>): Register;
+	onPasswordLikeEmail(handler: Handler<[password: InvalidPassword]>): Register;
 } & FetchErrors<Register> &
 	ServerErrors<Register> &
 	JsonErrors<Register> &
@@ -66,28 +79,28 @@ export function register(body: RegisterBody) {
 			req.register('EmailTaken', handler);
 			return this;
 		},
-		onPasswordTooWeak(handler: Handler) {
-			req.register('PasswordTooWeak', handler);
+		onPasswordTooWeak(handler: Handler<[password: InvalidPassword]>) {
+			req.register('PasswordTooWeak', (msg, data) => handler(msg, data as InvalidPassword));
 			return this;
 		},
-		onPasswordTooLong(handler: Handler) {
-			req.register('PasswordTooLong', handler);
+		onPasswordTooLong(handler: Handler<[password: InvalidPassword]>) {
+			req.register('PasswordTooLong', (msg, data) => handler(msg, data as InvalidPassword));
 			return this;
 		},
-		onPasswordTooShort(handler: Handler) {
-			req.register('PasswordTooShort', handler);
+		onPasswordTooShort(handler: Handler<[password: InvalidPassword]>) {
+			req.register('PasswordTooShort', (msg, data) => handler(msg, data as InvalidPassword));
 			return this;
 		},
-		onPasswordTooCommon(handler: Handler) {
-			req.register('PasswordTooCommon', handler);
+		onPasswordTooCommon(handler: Handler<[password: InvalidPassword]>) {
+			req.register('PasswordTooCommon', (msg, data) => handler(msg, data as InvalidPassword));
 			return this;
 		},
-		onPasswordLikeUsername(handler: Handler) {
-			req.register('PasswordLikeUsername', handler);
+		onPasswordLikeUsername(handler: Handler<[password: InvalidPassword]>) {
+			req.register('PasswordLikeUsername', (msg, data) => handler(msg, data as InvalidPassword));
 			return this;
 		},
-		onPasswordLikeEmail(handler: Handler) {
-			req.register('PasswordLikeEmail', handler);
+		onPasswordLikeEmail(handler: Handler<[password: InvalidPassword]>) {
+			req.register('PasswordLikeEmail', (msg, data) => handler(msg, data as InvalidPassword));
 			return this;
 		},
 		...anyErrors(req),
@@ -11,3 +11,10 @@ export function profileImage(user?: Type.User, size: ImgSize = 64): string {
 	return '';
 	// return `http://localhost:8080/cdn/`;
 }
+
+/** Every text channel in a guild from top to bottom, including those in categories. */
+export function textChannels(guild?: Type.Guild): Type.Channel[] {
+	return (guild?.channels ?? []).flatMap((node) =>
+		node.kind === 'category' ? node.children : [node]
+	);
+}
@@ -0,0 +1,45 @@
+import {
+	SdkRequest,
+	fetchRequest,
+	type FetchErrors,
+	fetchErrors,
+	type ServerErrors,
+	serverErrors,
+	type JsonErrors,
+	jsonErrors,
+	type AuthErrors,
+	authErrors,
+	type AnyErrors,
+	anyErrors
+} from '$/sdk/req';
+import type { Guild, Handler, Snowflake } from '$/sdk/types';
+
+export type GetResponse = Guild;
+
+export type Get = {
+	onGuildNotFound(handler: Handler): Get;
+} & FetchErrors<Get> &
+	ServerErrors<Get> &
+	JsonErrors<Get> &
+	AuthErrors<Get> &
+	AnyErrors<SdkRequest<GetResponse, GetResponse>>;
+
+export function get(authToken: string, guildId: Snowflake): Get {
+	const req = new SdkRequest<GetResponse, GetResponse>(() =>
+		fetchRequest<GetResponse>('/guild/' + guildId, 'GET', {
+			authToken
+		})
+	);
+
+	return {
+		onGuildNotFound(handler: Handler) {
+			req.register('GuildNotFound', handler);
+			return this;
+		},
+		...anyErrors(req),
+		...fetchErrors(req),
+		...serverErrors(req),
+		...jsonErrors(req),
+		...authErrors(req)
+	} as unknown as Get;
+}
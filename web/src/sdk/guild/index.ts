@@ -1 +1,2 @@
+export * from './get';
 export * from './get_joined';
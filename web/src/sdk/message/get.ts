@@ -16,12 +16,27 @@ import type { Handler, Message, Snowflake } from '$/sdk/types';
 
 export type GetBody = {
 	channelId: Snowflake;
+	/** Get the messages sent before this message. */
+	before?: Snowflake;
+	/** Get the messages sent after this message. */
+	after?: Snowflake;
+	/** Get the messages sent around this message, including itself. */
+	around?: Snowflake;
+	/** How many messages to get, between 1 and 100. Defaults to 50. */
+	limit?: number;
 };
 
-export type GetResponse = Message[];
+export type GetResponse = {
+	/** The messages, newest first. */
+	messages: Message[];
+	has_more_before: boolean;
+	has_more_after: boolean;
+};
 
 export type Get = {
 	onNotFound(handler: Handler): Get;
+	onConflictingCursors(handler: Handler): Get;
+	onInvalidLimit(handler: Handler<[{ min: number; max: number }]>): Get;
 } & FetchErrors<Get> &
 	ServerErrors<Get> &
 	JsonErrors<Get> &
@@ -29,10 +44,21 @@ export type Get = {
 	AnyErrors<SdkRequest<GetResponse, GetResponse>>;
 
 export function get(authToken: string, params: GetBody): Get {
+	const query = new URLSearchParams();
+	for (const key of ['before', 'after', 'around', 'limit'] as const) {
+		const value = params[key];
+		if (value !== undefined) query.set(key, value.toString());
+	}
+	const search = query.toString();
+
 	const req = new SdkRequest<GetResponse, GetResponse>(() =>
-		fetchRequest<GetResponse>('/message/' + params.channelId, 'GET', {
-			authToken
-		})
+		fetchRequest<GetResponse>(
+			'/message/' + params.channelId + (search ? '?' + search : ''),
+			'GET',
+			{
+				authToken
+			}
+		)
 	);
 
 	return {
@@ -40,6 +66,16 @@ export function get(authToken: string, params: GetBody): Get {
 			req.register('NotFound', handler);
 			return this;
 		},
+		onConflictingCursors(handler: Handler) {
+			req.register('ConflictingCursors', handler);
+			return this;
+		},
+		onInvalidLimit(handler: Handler<[{ min: number; max: number }]>) {
+			req.register('InvalidLimit', (code, data) =>
+				handler(code, data as { min: number; max: number })
+			);
+			return this;
+		},
 		...anyErrors(req),
 		...fetchErrors(req),
 		...serverErrors(req),
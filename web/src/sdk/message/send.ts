@@ -27,6 +27,8 @@ export type Send = {
 	onMessageTooShort(handler: Handler<[minLength: number]>): Send;
 	onMessageTooLong(handler: Handler<[maxLength: number]>): Send;
 	onChannelNotFound(handler: Handler): Send;
+	/** The server requires a verified email address to send messages. */
+	onEmailNotVerified(handler: Handler): Send;
 } & FetchErrors<Send> &
 	ServerErrors<Send> &
 	JsonErrors<Send> &
@@ -56,6 +58,10 @@ export function send(authToken: string, body: SendBody) {
 			req.register('ChannelNotFound', handler);
 			return this;
 		},
+		onEmailNotVerified(handler: Handler) {
+			req.register('EmailNotVerified', handler);
+			return this;
+		},
 		...anyErrors(req),
 		...fetchErrors(req),
 		...serverErrors(req),
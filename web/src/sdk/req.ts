@@ -541,6 +541,10 @@ export interface ServerErrors<T> {
 	 *  us.
 	 */
 	onMethodNotAllowed(handler: Handler<[permittedMethods: string[]]>): T;
+	/** Too many requests were made to the endpoint. `retry_after` is how many
+	 *  seconds until it can be used again.
+	 */
+	onRateLimited(handler: Handler<[{ retry_after: number }]>): T;
 	/** A server error occured.
 	 *
 	 *  Alias for registering all unregistered `ServerErrors` with the same callback.
@@ -565,6 +569,10 @@ export const serverErrors = (req: AnyRequest) => ({
 		});
 		return this;
 	},
+	onRateLimited(handler: Handler<[{ retry_after: number }]>) {
+		req.register('RateLimited', (code, data) => handler(code, data as { retry_after: number }));
+		return this;
+	},
 	onServerError(handler: Handler) {
 		req.ignoreDuplicates(() => {
 			this.onInternalServerError(handler);
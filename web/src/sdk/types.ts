@@ -64,7 +64,15 @@ export interface Guild {
 	id: Snowflake;
 	owner: User;
 	name: string;
-	channels: Channel[];
+	folder_id?: Snowflake;
+	channels: ChannelNode[];
+}
+
+export interface GuildFolder {
+	id: Snowflake;
+	name: string;
+	color?: string;
+	guild_ids: Snowflake[];
 }
 
 export interface User {
@@ -80,6 +88,17 @@ export interface User {
 export interface Channel {
 	id: Snowflake;
 	name: string;
+	topic: string | null;
+	kind: 'text' | 'category';
+	/** The category the channel is in, if any. */
+	parent_id: Snowflake | null;
+}
+
+/** A channel at the top of the sidebar, with the channels in it if it is a category. */
+export interface ChannelNode extends Channel {
+	children: Channel[];
+	/** Whether the user collapsed the category. */
+	collapsed: boolean;
 }
 
 export interface Message {
@@ -89,4 +108,18 @@ export interface Message {
 	content: string;
 	sent_at: number;
 	updated_at: number;
+	/** Deleted messages are kept as a placeholder with no content. */
+	deleted: boolean;
+	/** Only included when fetching pages of messages. */
+	reactions?: Reaction[];
+}
+
+/** A Unicode emoji, or the ID of a custom emoji. */
+export type Emoji = { unicode: string } | { custom: Snowflake };
+
+export interface Reaction {
+	emoji: Emoji;
+	count: number;
+	/** Whether the current user reacted with this emoji. */
+	me: boolean;
 }